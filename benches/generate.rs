@@ -0,0 +1,33 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+
+/// The amount of Spaceflakes generated per batch.
+const BATCH: usize = 100_000;
+
+fn generate(c: &mut Criterion) {
+    let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+    // The sequence of the default layout caps a worker to 4095 Spaceflakes per millisecond, so a wide sequence is
+    // used to measure the cost of generation rather than the wait for the next millisecond.
+    let mut worker = node.new_worker();
+    worker.layout = spaceflake::BitLayout::new(41, 1, 1, 20).expect("Failed creating the layout");
+
+    let mut group = c.benchmark_group("generate");
+    group.throughput(Throughput::Elements(BATCH as u64));
+    group.bench_function("generate", |b| {
+        b.iter(|| {
+            for _ in 0..BATCH {
+                worker.generate().expect("Failed generating");
+            }
+        })
+    });
+    group.bench_function("generate_many", |b| {
+        b.iter_batched_ref(
+            || Vec::with_capacity(BATCH),
+            |ids| worker.generate_many(BATCH, ids),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, generate);
+criterion_main!(benches);
@@ -1,7 +1,7 @@
 extern crate spaceflake;
 
 fn main() {
-    let mut node = spaceflake::Node::new(1);
+    let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
     let worker = node.new_worker();
     let sf = worker.generate();
     match sf {
@@ -13,7 +13,7 @@ fn main() {
         }
     }
 
-    let node_one = spaceflake::Node::new(1);
+    let node_one = spaceflake::Node::try_new(1).expect("Failed creating the node");
     spaceflakes = node_one.bulk_generate(1_000_000);
     match spaceflakes {
         Ok(value) => {
@@ -25,7 +25,7 @@ fn main() {
         }
     }
 
-    let mut node_two = spaceflake::Node::new(2);
+    let mut node_two = spaceflake::Node::try_new(2).expect("Failed creating the node");
     let worker = node_two.new_worker();
     spaceflakes = worker.bulk_generate(500_000);
     match spaceflakes {
@@ -0,0 +1,119 @@
+use std::future::{ready, Ready};
+
+use actix_web::dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::http::StatusCode;
+use actix_web::{
+    Error, FromRequest, HttpMessage, HttpRequest, HttpResponse, Responder, ResponseError,
+};
+
+use crate::extract::{SpaceflakePath, SpaceflakeRejection};
+use crate::{Spaceflake, SpaceflakeError, Worker, REQUEST_ID_HEADER};
+
+/// The `actix-web` extraction of a Spaceflake from the single parameter of the path.
+impl FromRequest for SpaceflakePath {
+    type Error = SpaceflakeRejection;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let mut parameters = req.match_info().iter();
+        let result = match (parameters.next(), parameters.next()) {
+            (Some((_, id)), None) => id.parse().map(SpaceflakePath).map_err(SpaceflakeRejection),
+            _ => Err(SpaceflakeRejection(SpaceflakeError::InvalidArgument(
+                "Expected a single path parameter".to_string(),
+            ))),
+        };
+        ready(result)
+    }
+}
+
+/// The `actix-web` response of a rejected Spaceflake.
+impl ResponseError for SpaceflakeRejection {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code())
+            .content_type("application/json")
+            .body(self.body().to_string())
+    }
+}
+
+/// The `actix-web` response of a Spaceflake, its ID as plain text.
+impl Responder for Spaceflake {
+    type Body = String;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<String> {
+        self.to_string().respond_to(req)
+    }
+}
+
+/// An `actix-web` middleware generating a Spaceflake per request, injecting it in the [`REQUEST_ID_HEADER`] header
+/// and exposing it in the extensions of the request, so the request IDs are sortable by time.
+///
+/// Requests that already have a request ID header are left untouched, and if the generation fails, the request is
+/// passed through without a request ID.
+#[derive(Debug, Clone)]
+pub struct SpaceflakeRequestId {
+    /// The worker generating the request IDs.
+    worker: Worker,
+}
+
+/// The default implementation of a request ID middleware.
+impl SpaceflakeRequestId {
+    /// Create a new middleware generating the request IDs with the given worker, sharing its sequence with its clones.
+    pub fn new(worker: Worker) -> Self {
+        SpaceflakeRequestId { worker }
+    }
+}
+
+/// The `actix-web` transform implementation of a request ID middleware.
+impl<S, B> Transform<S, ServiceRequest> for SpaceflakeRequestId
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = SpaceflakeRequestIdMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(SpaceflakeRequestIdMiddleware {
+            service,
+            worker: self.worker.clone(),
+        }))
+    }
+}
+
+/// The service of the request ID middleware, see [`SpaceflakeRequestId`].
+pub struct SpaceflakeRequestIdMiddleware<S> {
+    /// The wrapped service.
+    service: S,
+    /// The worker generating the request IDs.
+    worker: Worker,
+}
+
+/// The `actix-web` service implementation of a request ID middleware.
+impl<S, B> Service<ServiceRequest> for SpaceflakeRequestIdMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = S::Future;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let header = HeaderName::from_static(REQUEST_ID_HEADER);
+        if !req.headers().contains_key(&header) {
+            if let Ok(id) = self.worker.generate() {
+                req.headers_mut().insert(header, HeaderValue::from(id.id));
+                req.extensions_mut().insert(id);
+            }
+        }
+        self.service.call(req)
+    }
+}
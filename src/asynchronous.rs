@@ -0,0 +1,184 @@
+use std::future::Future;
+use std::num::NonZeroU32;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+
+use futures_core::Stream;
+use tokio::time::{interval, sleep, Interval, MissedTickBehavior, Sleep};
+
+use tokio::sync::oneshot;
+
+use crate::{
+    BulkGeneratorSettings, BulkWorkers, GeneratorService, Node, Spaceflake, SpaceflakeError,
+    Worker, MAX_12_BITS, MAX_5_BITS,
+};
+
+/// The async implementation of a worker.
+impl Worker {
+    /// Generate a new Spaceflake on this worker, waiting for the next millisecond with [`tokio::time::sleep`] instead
+    /// of blocking the thread.
+    pub async fn generate_async(&self) -> Result<Spaceflake, SpaceflakeError> {
+        loop {
+            match self.try_generate() {
+                Err(SpaceflakeError::WouldBlock { retry_after_ms }) => {
+                    sleep(Duration::from_millis(retry_after_ms)).await
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Generate an amount of Spaceflakes on this worker, like [`Worker::bulk_generate`], but waiting for the next
+    /// millisecond with [`tokio::time::sleep`] instead of blocking the thread.
+    pub async fn bulk_generate_async(
+        &self,
+        amount: usize,
+    ) -> Result<Vec<Spaceflake>, SpaceflakeError> {
+        let mut spaceflakes = Vec::<Spaceflake>::with_capacity(amount);
+        for _ in 0..amount {
+            spaceflakes.push(self.generate_async().await?);
+        }
+        Ok(spaceflakes)
+    }
+
+    /// Returns a stream of Spaceflakes generated on this worker, waiting for the next millisecond with
+    /// [`tokio::time::sleep`] instead of blocking the thread.
+    ///
+    /// The stream ends at the first error that is not [`SpaceflakeError::WouldBlock`], such as a clock regression.
+    pub fn stream(&self) -> SpaceflakeStream {
+        SpaceflakeStream {
+            worker: self.clone(),
+            sleep: None,
+            period: None,
+            interval: None,
+            ticked: false,
+            done: false,
+        }
+    }
+}
+
+/// The async implementation of a node.
+impl Node {
+    /// Generate an amount of Spaceflakes on the node, like [`Node::bulk_generate`], but waiting with
+    /// [`tokio::time::sleep`] instead of blocking the thread.
+    pub async fn bulk_generate_async(
+        &self,
+        amount: usize,
+    ) -> Result<Vec<Spaceflake>, SpaceflakeError> {
+        let mut node = Node::unchecked(self.id);
+        let mut worker = node.new_worker();
+        let mut spaceflakes = Vec::<Spaceflake>::with_capacity(amount);
+        for i in 1..=amount {
+            if i > 1 && (i - 1) % MAX_12_BITS as usize == 0 {
+                if node.workers.len() == MAX_5_BITS as usize {
+                    sleep(Duration::from_millis(1)).await;
+                    node.workers.clear();
+                }
+                worker = node.new_worker();
+            }
+            spaceflakes.push(worker.generate_async().await?);
+        }
+        Ok(spaceflakes)
+    }
+}
+
+/// The async implementation of a generator service.
+impl GeneratorService {
+    /// Request a new Spaceflake from the thread of the service, waiting for the response without blocking the thread.
+    pub async fn generate_async(&self) -> Result<Spaceflake, SpaceflakeError> {
+        let (sender, receiver) = oneshot::channel();
+        self.request(Box::new(move |result| {
+            let _ = sender.send(result);
+        }))?;
+        receiver.await.map_err(|_| GeneratorService::stopped())?
+    }
+}
+
+/// A stream of Spaceflakes generated on a worker, created by [`Worker::stream`].
+#[derive(Debug)]
+pub struct SpaceflakeStream {
+    /// The worker generating the Spaceflakes.
+    worker: Worker,
+    /// The sleep until the next millisecond, when the sequence of the worker is exhausted.
+    sleep: Option<Pin<Box<Sleep>>>,
+    /// The minimum period between two Spaceflakes, see [`SpaceflakeStream::rate_limit`].
+    period: Option<Duration>,
+    /// The interval of the rate limit, created on the first poll to be bound to the runtime polling the stream.
+    interval: Option<Interval>,
+    /// Whether the current tick of the interval was consumed by a Spaceflake that is not generated yet.
+    ticked: bool,
+    /// Whether the stream ended after an error.
+    done: bool,
+}
+
+/// The default implementation of a Spaceflake stream.
+impl SpaceflakeStream {
+    /// Limit the stream to an amount of Spaceflakes per second, with [`tokio::time::interval`].
+    ///
+    /// Ticks missed by a slow consumer are not caught up with, so the rate is never exceeded.
+    pub fn rate_limit(mut self, ids_per_sec: NonZeroU32) -> Self {
+        let period = Duration::from_secs(1) / ids_per_sec.get();
+        self.period = Some(period.max(Duration::from_nanos(1)));
+        self.interval = None;
+        self
+    }
+}
+
+impl Stream for SpaceflakeStream {
+    type Item = Spaceflake;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+        if let Some(period) = this.period {
+            let interval = this.interval.get_or_insert_with(|| {
+                let mut interval = interval(period);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                interval
+            });
+            if !this.ticked {
+                ready!(interval.poll_tick(cx));
+                this.ticked = true;
+            }
+        }
+
+        loop {
+            if let Some(sleep) = this.sleep.as_mut() {
+                ready!(sleep.as_mut().poll(cx));
+                this.sleep = None;
+            }
+            match this.worker.try_generate() {
+                Ok(spaceflake) => {
+                    this.ticked = false;
+                    return Poll::Ready(Some(spaceflake));
+                }
+                Err(SpaceflakeError::WouldBlock { retry_after_ms }) => {
+                    this.sleep = Some(Box::pin(sleep(Duration::from_millis(retry_after_ms))));
+                }
+                Err(_) => {
+                    this.done = true;
+                    return Poll::Ready(None);
+                }
+            }
+        }
+    }
+}
+
+/// Generate an amount of Spaceflakes for the given settings, like [`crate::bulk_generate`], but waiting with
+/// [`tokio::time::sleep`] instead of blocking the thread.
+pub async fn bulk_generate_async(
+    settings: BulkGeneratorSettings,
+) -> Result<Vec<Spaceflake>, SpaceflakeError> {
+    let mut workers = BulkWorkers::new(&settings)?;
+    let mut spaceflakes = Vec::<Spaceflake>::with_capacity(settings.amount);
+    for i in 1..=settings.amount {
+        if i > 1 && (i - 1) % MAX_12_BITS as usize == 0 && workers.rotate() {
+            sleep(Duration::from_millis(1)).await;
+        }
+        spaceflakes.push(workers.worker().generate_async().await?);
+    }
+    Ok(spaceflakes)
+}
@@ -0,0 +1,167 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+use crate::{current_time, IdGenerator, Spaceflake, SpaceflakeError};
+
+/// A record of the issuance of a Spaceflake, appended to an [`AuditSink`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// The time, in milliseconds since the Unix epoch, at which the Spaceflake was issued.
+    pub issued_at: u64,
+    /// The ID of the issued Spaceflake.
+    pub id: u64,
+    /// The node ID of the issued Spaceflake.
+    pub node_id: u64,
+    /// The worker ID of the issued Spaceflake.
+    pub worker_id: u64,
+}
+
+/// The default implementation of an audit record.
+impl AuditRecord {
+    /// Create a new audit record for a Spaceflake issued now.
+    pub fn new(spaceflake: &Spaceflake) -> Self {
+        AuditRecord {
+            issued_at: current_time(),
+            id: spaceflake.id,
+            node_id: spaceflake.node_id(),
+            worker_id: spaceflake.worker_id(),
+        }
+    }
+}
+
+/// An append-only destination of [`AuditRecord`]s.
+pub trait AuditSink {
+    /// Append the records to the sink, in order.
+    fn append(&self, records: &[AuditRecord]) -> Result<(), SpaceflakeError>;
+}
+
+/// An audit sink appending the records as lines of comma-separated `issued_at,id,node_id,worker_id` values to a file,
+/// which is rotated once it reaches a maximum size.
+///
+/// Rotated files get a numbered suffix, `.1` being the most recent one, and only a maximum amount of them are kept.
+#[derive(Debug)]
+pub struct FileAuditSink {
+    /// The path of the file the records are appended to.
+    path: PathBuf,
+    /// The size, in bytes, after which the file is rotated.
+    max_bytes: u64,
+    /// The maximum amount of rotated files to keep.
+    max_files: usize,
+    /// The file the records are appended to.
+    file: Mutex<File>,
+}
+
+/// The default implementation of a file audit sink.
+impl FileAuditSink {
+    /// Create a new file audit sink appending to the file at the given path, rotating it after the given size in
+    /// bytes and keeping the given amount of rotated files.
+    pub fn new<P: AsRef<Path>>(
+        path: P,
+        max_bytes: u64,
+        max_files: usize,
+    ) -> Result<Self, SpaceflakeError> {
+        let path = path.as_ref().to_path_buf();
+        let file = open_append(&path)?;
+        Ok(FileAuditSink {
+            path,
+            max_bytes,
+            max_files,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Returns the path of the rotated file with the given number.
+    fn rotated_path(&self, number: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", number));
+        PathBuf::from(path)
+    }
+
+    /// Rotate the file, shifting the already rotated ones and removing the oldest one.
+    fn rotate(&self, file: &mut File) -> Result<(), SpaceflakeError> {
+        if self.max_files == 0 {
+            *file = File::create(&self.path)?;
+            return Ok(());
+        }
+        for number in (1..self.max_files).rev() {
+            let from = self.rotated_path(number);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(number + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated_path(1))?;
+        *file = open_append(&self.path)?;
+        Ok(())
+    }
+}
+
+/// The sink implementation of a file audit sink.
+impl AuditSink for FileAuditSink {
+    fn append(&self, records: &[AuditRecord]) -> Result<(), SpaceflakeError> {
+        let mut lines = String::new();
+        for record in records {
+            lines.push_str(&format!(
+                "{},{},{},{}\n",
+                record.issued_at, record.id, record.node_id, record.worker_id
+            ));
+        }
+
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        let size = file.metadata()?.len();
+        if size > 0 && size + lines.len() as u64 > self.max_bytes {
+            self.rotate(&mut file)?;
+        }
+        file.write_all(lines.as_bytes())?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+/// Open the file at the given path for appending, creating it if needed.
+fn open_append(path: &Path) -> Result<File, SpaceflakeError> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(SpaceflakeError::from)
+}
+
+/// A generator appending every Spaceflake it issues to an [`AuditSink`], so it can be proven when a Spaceflake was
+/// issued.
+///
+/// A Spaceflake that could not be appended to the sink is never returned, the error of the sink is returned instead.
+#[derive(Debug)]
+pub struct AuditedGenerator<G: IdGenerator, S: AuditSink> {
+    /// The generator issuing the Spaceflakes.
+    generator: G,
+    /// The sink the issued Spaceflakes are appended to.
+    sink: S,
+}
+
+/// The default implementation of an audited generator.
+impl<G: IdGenerator, S: AuditSink> AuditedGenerator<G, S> {
+    /// Create a new audited generator appending the Spaceflakes issued by the generator to the sink.
+    pub fn new(generator: G, sink: S) -> Self {
+        AuditedGenerator { generator, sink }
+    }
+
+    /// Append a batch of Spaceflakes issued by other means, such as a bulk generation, to the sink at once.
+    pub fn record_batch(&self, spaceflakes: &[Spaceflake]) -> Result<(), SpaceflakeError> {
+        let records = spaceflakes
+            .iter()
+            .map(AuditRecord::new)
+            .collect::<Vec<AuditRecord>>();
+        self.sink.append(&records)
+    }
+}
+
+/// The generator implementation of an audited generator.
+impl<G: IdGenerator, S: AuditSink> IdGenerator for AuditedGenerator<G, S> {
+    fn generate(&self) -> Result<Spaceflake, SpaceflakeError> {
+        let spaceflake = self.generator.generate()?;
+        self.sink.append(&[AuditRecord::new(&spaceflake)])?;
+        Ok(spaceflake)
+    }
+}
@@ -0,0 +1,84 @@
+use apache_avro::types::Value;
+use apache_avro::{from_avro_datum, to_avro_datum, Schema};
+
+use crate::{parse_node_id, parse_worker_id, Spaceflake, SpaceflakeError};
+
+/// The canonical Avro schema of a decomposed Spaceflake.
+pub const SCHEMA: &str = r#"{
+    "type": "record",
+    "name": "Spaceflake",
+    "namespace": "spaceflake",
+    "fields": [
+        { "name": "id", "type": "long" },
+        { "name": "time", "type": "long" },
+        { "name": "node_id", "type": "long" },
+        { "name": "worker_id", "type": "long" },
+        { "name": "sequence", "type": "long" },
+        { "name": "version", "type": "long" }
+    ]
+}"#;
+
+/// Returns the parsed canonical Avro schema of a decomposed Spaceflake.
+pub fn schema() -> Schema {
+    Schema::parse_str(SCHEMA).expect("The Spaceflake Avro schema is invalid")
+}
+
+/// The Avro implementation of a Spaceflake.
+impl Spaceflake {
+    /// Encode the decomposed Spaceflake as an Avro datum following [`SCHEMA`].
+    pub fn to_avro(&self) -> Result<Vec<u8>, SpaceflakeError> {
+        let record = Value::Record(vec![
+            ("id".to_string(), Value::Long(self.id as i64)),
+            ("time".to_string(), Value::Long(self.time() as i64)),
+            ("node_id".to_string(), Value::Long(self.node_id() as i64)),
+            (
+                "worker_id".to_string(),
+                Value::Long(self.worker_id() as i64),
+            ),
+            ("sequence".to_string(), Value::Long(self.sequence() as i64)),
+            ("version".to_string(), Value::Long(self.version() as i64)),
+        ]);
+        to_avro_datum(&schema(), record)
+            .map_err(|error| SpaceflakeError::InvalidEncoding(error.to_string()))
+    }
+
+    /// Decode a Spaceflake from an Avro datum following [`SCHEMA`].
+    ///
+    /// The base epoch is recovered from the encoded time, and the node and worker IDs are checked against the ID.
+    pub fn from_avro(mut datum: &[u8]) -> Result<Spaceflake, SpaceflakeError> {
+        let value = from_avro_datum(&schema(), &mut datum, None)
+            .map_err(|error| SpaceflakeError::InvalidEncoding(error.to_string()))?;
+        let fields = match value {
+            Value::Record(fields) => fields,
+            _ => {
+                return Err(SpaceflakeError::InvalidEncoding(String::from(
+                    "Avro datum is not a Spaceflake record",
+                )))
+            }
+        };
+        let field = |name: &str| -> Result<u64, SpaceflakeError> {
+            match fields.iter().find(|(key, _)| key == name) {
+                Some((_, Value::Long(value))) if *value >= 0 => Ok(*value as u64),
+                _ => Err(SpaceflakeError::InvalidEncoding(format!(
+                    "Avro field {} is missing or invalid",
+                    name
+                ))),
+            }
+        };
+
+        let id = field("id")?;
+        let time = field("time")?;
+        if time < id >> 22 {
+            return Err(SpaceflakeError::InvalidEncoding(String::from(
+                "Avro field time is before the ID's timestamp",
+            )));
+        }
+        if field("node_id")? != parse_node_id(id) || field("worker_id")? != parse_worker_id(id) {
+            return Err(SpaceflakeError::InvalidEncoding(String::from(
+                "Avro fields node_id and worker_id do not match the ID",
+            )));
+        }
+
+        Ok(Spaceflake::new(id, time - (id >> 22)))
+    }
+}
@@ -0,0 +1,39 @@
+use crate::SpaceflakeError;
+
+/// The digits of the base62 encoding, in ASCII order so encoded IDs of the same length sort like the IDs.
+const ALPHABET: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Returns the ID encoded in base62, at most 11 characters long, for example to use it as a compact URL slug.
+pub fn encode_base62(mut id: u64) -> String {
+    let mut digits = Vec::with_capacity(11);
+    loop {
+        digits.push(ALPHABET[(id % 62) as usize]);
+        id /= 62;
+        if id == 0 {
+            break;
+        }
+    }
+    digits.iter().rev().map(|&digit| digit as char).collect()
+}
+
+/// Returns the ID decoded from a string returned by [`encode_base62`].
+///
+/// Returns an error if the string is empty, has a character outside of the base62 alphabet or overflows 64 bits.
+pub fn decode_base62(encoded: &str) -> Result<u64, SpaceflakeError> {
+    let invalid =
+        || SpaceflakeError::InvalidEncoding(format!("Invalid base62 Spaceflake ID: {}", encoded));
+    if encoded.is_empty() {
+        return Err(invalid());
+    }
+    encoded.bytes().try_fold(0u64, |id, byte| {
+        let digit = match byte {
+            b'0'..=b'9' => byte - b'0',
+            b'A'..=b'Z' => byte - b'A' + 10,
+            b'a'..=b'z' => byte - b'a' + 36,
+            _ => return Err(invalid()),
+        };
+        id.checked_mul(62)
+            .and_then(|id| id.checked_add(digit as u64))
+            .ok_or_else(invalid)
+    })
+}
@@ -0,0 +1,185 @@
+use std::io::{self, BufRead, Write};
+
+use chrono_tz::Tz;
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use spaceflake::{Spaceflake, SpaceflakeError};
+
+/// Generate, decompose and compare Spaceflakes.
+#[derive(Debug, Parser)]
+#[command(name = "spaceflake", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// The commands of the CLI.
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Generate Spaceflakes.
+    Generate {
+        /// The node ID to generate the Spaceflakes on.
+        #[arg(long, default_value_t = 0)]
+        node: u64,
+        /// The worker ID to generate the Spaceflakes on.
+        #[arg(long, default_value_t = 0)]
+        worker: u64,
+        /// The base epoch, in milliseconds, to generate the Spaceflakes with.
+        #[arg(long, default_value_t = spaceflake::EPOCH)]
+        epoch: u64,
+        /// The amount of Spaceflakes to generate.
+        #[arg(long, default_value_t = 1)]
+        count: usize,
+    },
+    /// Decompose Spaceflake IDs into their parts.
+    Decompose {
+        /// The Spaceflake IDs to decompose.
+        #[arg(required = true)]
+        ids: Vec<u64>,
+        /// The base epoch, in milliseconds, the Spaceflakes were generated with.
+        #[arg(long, default_value_t = spaceflake::EPOCH)]
+        epoch: u64,
+        /// The timezone to render the time of the Spaceflakes in, for example `Europe/Paris`.
+        #[arg(long)]
+        timezone: Option<Tz>,
+    },
+    /// Compare two Spaceflake IDs.
+    Compare {
+        /// The first Spaceflake ID.
+        first: u64,
+        /// The second Spaceflake ID.
+        second: u64,
+        /// The base epoch, in milliseconds, the Spaceflakes were generated with.
+        #[arg(long, default_value_t = spaceflake::EPOCH)]
+        epoch: u64,
+    },
+    /// Start an interactive session to decompose and compare pasted Spaceflake IDs.
+    Repl {
+        /// The base epoch, in milliseconds, the Spaceflakes were generated with.
+        #[arg(long, default_value_t = spaceflake::EPOCH)]
+        epoch: u64,
+        /// The timezone to render the time of the Spaceflakes in, for example `Europe/Paris`.
+        #[arg(long)]
+        timezone: Option<Tz>,
+    },
+    /// Print the completions of the CLI for the given shell.
+    Completions {
+        /// The shell to print the completions for.
+        shell: Shell,
+    },
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Generate {
+            node,
+            worker,
+            epoch,
+            count,
+        } => generate(node, worker, epoch, count),
+        Command::Decompose {
+            ids,
+            epoch,
+            timezone,
+        } => {
+            for id in ids {
+                println!("{}", describe(id, epoch, timezone));
+            }
+            Ok(())
+        }
+        Command::Compare {
+            first,
+            second,
+            epoch,
+        } => {
+            println!("{}", compare(first, second, epoch));
+            Ok(())
+        }
+        Command::Repl { epoch, timezone } => repl(epoch, timezone),
+        Command::Completions { shell } => {
+            clap_complete::generate(shell, &mut Cli::command(), "spaceflake", &mut io::stdout());
+            Ok(())
+        }
+    };
+
+    if let Err(error) = result {
+        eprintln!("Error: {}", error);
+        std::process::exit(1);
+    }
+}
+
+/// Generate an amount of Spaceflakes on the given node and worker, and print them.
+fn generate(node_id: u64, worker_id: u64, epoch: u64, count: usize) -> Result<(), SpaceflakeError> {
+    let mut worker = spaceflake::Worker::standalone(node_id, worker_id)?;
+    worker.base_epoch = epoch;
+    for spaceflake in worker.bulk_generate(count)? {
+        println!("{}", spaceflake);
+    }
+    Ok(())
+}
+
+/// Returns the description of each part of a Spaceflake ID, with the time rendered in the timezone if any.
+fn describe(id: u64, epoch: u64, timezone: Option<Tz>) -> String {
+    let time = match timezone {
+        Some(tz) => {
+            let spaceflake = Spaceflake::from_bytes(id.to_be_bytes(), epoch);
+            format!(
+                "{} ({})",
+                spaceflake.time(),
+                spaceflake.time_in(tz).to_rfc3339()
+            )
+        }
+        None => spaceflake::parse_time(id, epoch).to_string(),
+    };
+    format!(
+        "id: {}\ntime: {}\nnode_id: {}\nworker_id: {}\nsequence: {}",
+        id,
+        time,
+        spaceflake::parse_node_id(id),
+        spaceflake::parse_worker_id(id),
+        spaceflake::parse_sequence(id)
+    )
+}
+
+/// Returns the explanation of the differences between two Spaceflake IDs.
+fn compare(first: u64, second: u64, epoch: u64) -> String {
+    let first = Spaceflake::from_bytes(first.to_be_bytes(), epoch);
+    let second = Spaceflake::from_bytes(second.to_be_bytes(), epoch);
+    first.diff(&second).to_string()
+}
+
+/// Read commands from the standard input until it is closed or the session is exited.
+fn repl(epoch: u64, timezone: Option<Tz>) -> Result<(), SpaceflakeError> {
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("spaceflake> ");
+        io::stdout().flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => return Ok(()),
+        };
+
+        let words = line.split_whitespace().collect::<Vec<&str>>();
+        match words.as_slice() {
+            [] => {}
+            ["exit"] | ["quit"] => return Ok(()),
+            ["help"] => println!(
+                "<id>                 Decompose a Spaceflake ID\ncompare <id> <id>    Compare two Spaceflake IDs\nexit                 Exit the session"
+            ),
+            ["compare", first, second] => match (first.parse(), second.parse()) {
+                (Ok(first), Ok(second)) => println!("{}", compare(first, second, epoch)),
+                _ => println!("Invalid Spaceflake IDs"),
+            },
+            ids => {
+                for id in ids {
+                    match id.parse() {
+                        Ok(id) => println!("{}", describe(id, epoch, timezone)),
+                        Err(_) => println!("Invalid Spaceflake ID: {}, type help for help", id),
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use crate::splitmix64;
+
+/// The amount of hash functions of the bloom filters.
+const BLOOM_HASHES: u64 = 7;
+
+/// A bloom filter sized for a capacity with a false positive rate of about 1%.
+struct Bloom {
+    /// The bits of the filter.
+    bits: Vec<u64>,
+    /// The amount of Spaceflakes inserted in the filter.
+    len: usize,
+}
+
+impl Bloom {
+    fn new(capacity: usize) -> Self {
+        Bloom {
+            bits: vec![0; capacity.saturating_mul(10).div_ceil(64).max(1)],
+            len: 0,
+        }
+    }
+
+    /// Returns the positions of the bits of the Spaceflake ID.
+    fn positions(&self, id: u64) -> impl Iterator<Item = usize> {
+        let amount_of_bits = self.bits.len() as u64 * 64;
+        let first = splitmix64(id);
+        let second = splitmix64(first) | 1;
+        (0..BLOOM_HASHES)
+            .map(move |i| (first.wrapping_add(i.wrapping_mul(second)) % amount_of_bits) as usize)
+    }
+
+    fn contains(&self, id: u64) -> bool {
+        self.positions(id)
+            .all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    fn insert(&mut self, id: u64) {
+        let positions = self.positions(id).collect::<Vec<usize>>();
+        for bit in positions {
+            self.bits[bit / 64] |= 1 << (bit % 64);
+        }
+        self.len += 1;
+    }
+}
+
+/// Two bloom filters, the oldest being cleared once the newest is full, so the memory stays bounded.
+pub(crate) struct RollingBloom {
+    current: Bloom,
+    previous: Bloom,
+    capacity: usize,
+}
+
+impl RollingBloom {
+    /// Create new rolling bloom filters, each holding the given amount of Spaceflake IDs before they are rotated.
+    pub(crate) fn new(capacity: usize) -> Self {
+        RollingBloom {
+            current: Bloom::new(capacity),
+            previous: Bloom::new(capacity),
+            capacity,
+        }
+    }
+
+    /// Insert the Spaceflake ID and returns whether it may have been inserted before.
+    pub(crate) fn check_and_insert(&mut self, id: u64) -> bool {
+        let seen = self.current.contains(id) || self.previous.contains(id);
+        if self.current.len >= self.capacity {
+            self.previous = std::mem::replace(&mut self.current, Bloom::new(self.capacity));
+        }
+        self.current.insert(id);
+        seen
+    }
+}
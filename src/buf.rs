@@ -0,0 +1,22 @@
+use bytes::{Buf, BufMut};
+
+use crate::{Spaceflake, SpaceflakeError};
+
+/// The `bytes` implementation of a Spaceflake.
+impl Spaceflake {
+    /// Write the ID of the Spaceflake to the buffer as 8 big-endian bytes.
+    pub fn put<B: BufMut>(&self, buf: &mut B) {
+        buf.put_u64(self.id);
+    }
+
+    /// Read a Spaceflake from 8 big-endian bytes of the buffer, for the given base epoch.
+    pub fn get<B: Buf>(buf: &mut B, base_epoch: u64) -> Result<Spaceflake, SpaceflakeError> {
+        if buf.remaining() < 8 {
+            return Err(SpaceflakeError::InvalidEncoding(format!(
+                "Buffer must contain at least 8 bytes, got {}",
+                buf.remaining()
+            )));
+        }
+        Ok(Spaceflake::new(buf.get_u64(), base_epoch))
+    }
+}
@@ -0,0 +1,276 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, Weak};
+use std::time::{Duration, Instant};
+use std::{fmt, thread};
+
+use crate::{current_time, SpaceflakeError};
+
+/// A source of the current time, see [`crate::Worker::set_clock`] and [`crate::GeneratorSettings::clock`].
+pub trait Clock: Send + Sync {
+    /// Returns the current time, in milliseconds since the Unix epoch.
+    fn now_ms(&self) -> u64;
+}
+
+/// The clock implementation of a reference to a clock, so static clocks can be shared.
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now_ms(&self) -> u64 {
+        (**self).now_ms()
+    }
+}
+
+/// The debug implementation of a clock.
+impl fmt::Debug for dyn Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Clock({})", self.now_ms())
+    }
+}
+
+/// The system clock, which is the default one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+/// The clock implementation of the system clock.
+impl Clock for SystemClock {
+    fn now_ms(&self) -> u64 {
+        current_time()
+    }
+}
+
+/// A clock that only moves when told to, for deterministic tests.
+///
+/// Clones share the same time. Workers waiting for the next millisecond wait until the clock is advanced.
+#[derive(Debug, Clone, Default)]
+pub struct MockClock {
+    /// The current time, in milliseconds.
+    now: Arc<AtomicU64>,
+}
+
+/// The default implementation of a mock clock.
+impl MockClock {
+    /// Create a new mock clock at the given time, in milliseconds since the Unix epoch.
+    pub fn new(now: u64) -> Self {
+        MockClock {
+            now: Arc::new(AtomicU64::new(now)),
+        }
+    }
+
+    /// Set the time of the clock, in milliseconds since the Unix epoch, which can go backwards.
+    pub fn set(&self, now: u64) {
+        self.now.store(now, Ordering::SeqCst);
+    }
+
+    /// Advance the clock by the given duration.
+    pub fn advance(&self, duration: Duration) {
+        self.now
+            .fetch_add(duration.as_millis() as u64, Ordering::SeqCst);
+    }
+}
+
+/// The clock implementation of a mock clock.
+impl Clock for MockClock {
+    fn now_ms(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
+
+/// An external authority the time of the Spaceflakes can come from instead of the local clock, for example a
+/// TrueTime-like API, the `now()` of a database or a PTP-disciplined clock, see [`crate::Worker::set_commit_timestamp`].
+pub trait CommitTimestamp: Send + Sync {
+    /// Returns the current commit timestamp, in milliseconds since the Unix epoch.
+    fn now(&self) -> Result<u64, SpaceflakeError>;
+}
+
+/// The interval at which the ticker thread of a [`TickerClock`] reads the clock by default.
+const TICK_INTERVAL: Duration = Duration::from_micros(250);
+
+/// The process-wide ticker clock, see [`TickerClock::shared`].
+static SHARED_TICKER_CLOCK: OnceLock<TickerClock> = OnceLock::new();
+
+/// A clock whose time is updated by a dedicated thread, so the time can be read with a single atomic load instead of a
+/// system call, see [`crate::Worker::set_clock`].
+///
+/// This is useful when thousands of concurrent generators would otherwise all read the system clock. Clones share the
+/// same ticker thread, which stops once all of them are dropped. The time may lag behind the system clock by up to the
+/// interval of the ticker thread.
+#[derive(Debug, Clone)]
+pub struct TickerClock {
+    /// The last time, in milliseconds, read by the ticker thread.
+    now: Arc<AtomicU64>,
+}
+
+/// A coarse clock updated every millisecond by a background thread, which is a [`TickerClock`], usually the
+/// process-wide one returned by [`TickerClock::shared`].
+pub type CachedClock = TickerClock;
+
+/// The default implementation of a ticker clock.
+impl TickerClock {
+    /// Create a new ticker clock and start its ticker thread.
+    ///
+    /// Returns an error if the ticker thread could not be spawned.
+    pub fn new() -> Result<Self, SpaceflakeError> {
+        Self::with_interval(TICK_INTERVAL)
+    }
+
+    /// Create a new ticker clock whose ticker thread reads the clock at the given interval, and start it.
+    ///
+    /// Returns an error if the ticker thread could not be spawned.
+    pub fn with_interval(interval: Duration) -> Result<Self, SpaceflakeError> {
+        let now = Arc::new(AtomicU64::new(current_time()));
+        let weak: Weak<AtomicU64> = Arc::downgrade(&now);
+        thread::Builder::new()
+            .name(String::from("spaceflake-ticker"))
+            .spawn(move || {
+                while let Some(now) = weak.upgrade() {
+                    now.fetch_max(current_time(), Ordering::Relaxed);
+                    drop(now);
+                    thread::sleep(interval);
+                }
+            })?;
+        Ok(TickerClock { now })
+    }
+
+    /// Returns the process-wide ticker clock, updated every millisecond, starting its ticker thread on first use.
+    ///
+    /// Every worker of the process can share it, so a single thread reads the system clock. Returns an error if the
+    /// ticker thread could not be spawned, in which case the next call tries again.
+    pub fn shared() -> Result<Self, SpaceflakeError> {
+        if let Some(clock) = SHARED_TICKER_CLOCK.get() {
+            return Ok(clock.clone());
+        }
+        let clock = TickerClock::with_interval(Duration::from_millis(1))?;
+        Ok(SHARED_TICKER_CLOCK.get_or_init(|| clock).clone())
+    }
+
+    /// Returns the last time, in milliseconds since the Unix epoch, read by the ticker thread.
+    pub fn now(&self) -> u64 {
+        self.now.load(Ordering::Relaxed)
+    }
+}
+
+/// The clock implementation of a ticker clock.
+impl Clock for TickerClock {
+    fn now_ms(&self) -> u64 {
+        self.now()
+    }
+}
+
+/// A clock anchored to the system clock once, then advancing with the monotonic clock, so its time never goes
+/// backwards even if the system clock is stepped back, for example by NTP.
+///
+/// It is re-anchored to the system clock periodically, but only when the system clock is ahead, so forward steps are
+/// caught up with while backward steps are ignored. Clones share the same anchor.
+#[derive(Debug, Clone)]
+pub struct MonotonicClock {
+    /// The anchor of the clock, shared with its clones.
+    anchor: Arc<MonotonicAnchor>,
+}
+
+/// The anchor of a [`MonotonicClock`].
+#[derive(Debug)]
+struct MonotonicAnchor {
+    /// The instant the clock was created at.
+    start: Instant,
+    /// The time, in milliseconds since the Unix epoch, of the start instant, which only grows when re-anchoring.
+    offset: AtomicU64,
+    /// The milliseconds elapsed since the start instant at the last re-anchoring.
+    anchored_at: AtomicU64,
+    /// The minimum amount of milliseconds between two re-anchorings.
+    interval: u64,
+}
+
+/// The default implementation of a monotonic clock.
+impl MonotonicClock {
+    /// Create a new monotonic clock anchored to the system clock, re-anchored at most every second.
+    pub fn new() -> Self {
+        Self::with_reanchor_interval(Duration::from_secs(1))
+    }
+
+    /// Create a new monotonic clock anchored to the system clock, re-anchored at most at the given interval.
+    pub fn with_reanchor_interval(interval: Duration) -> Self {
+        MonotonicClock {
+            anchor: Arc::new(MonotonicAnchor {
+                start: Instant::now(),
+                offset: AtomicU64::new(current_time()),
+                anchored_at: AtomicU64::new(0),
+                interval: interval.as_millis() as u64,
+            }),
+        }
+    }
+}
+
+/// The default implementation of a monotonic clock.
+impl Default for MonotonicClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The clock implementation of a monotonic clock.
+impl Clock for MonotonicClock {
+    fn now_ms(&self) -> u64 {
+        let anchor = &self.anchor;
+        let elapsed = anchor.start.elapsed().as_millis() as u64;
+        let now = anchor.offset.load(Ordering::Acquire) + elapsed;
+        let anchored_at = anchor.anchored_at.load(Ordering::Relaxed);
+        if elapsed.saturating_sub(anchored_at) >= anchor.interval
+            && anchor
+                .anchored_at
+                .compare_exchange(anchored_at, elapsed, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            let wall = current_time();
+            if wall > now {
+                anchor.offset.fetch_max(wall - elapsed, Ordering::AcqRel);
+                return wall;
+            }
+        }
+        now
+    }
+}
+
+/// A leap second smeared by the time servers, such as the ones of Google and Amazon, over a window centered on it.
+///
+/// During the window, a smeared clock runs slightly slower, or faster for a negative leap second, so it never steps.
+/// This is compensated by [`LeapSmear::to_utc`] so the time matches the one of a clock stepping at the leap second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LeapSmear {
+    /// The time, in milliseconds since the Unix epoch, of the end of the leap second, usually midnight UTC.
+    pub leap_at: u64,
+    /// The duration, in milliseconds, of the window the leap second is smeared over, default is 24 hours.
+    pub window: u64,
+    /// Whether the leap second is positive, which is the case for all leap seconds so far.
+    pub positive: bool,
+}
+
+/// The default implementation of a leap smear.
+impl LeapSmear {
+    /// Create a new linear 24 hours smear, from noon to noon UTC, of the positive leap second ending at the given time.
+    pub fn new(leap_at: u64) -> Self {
+        LeapSmear {
+            leap_at,
+            window: 86_400_000,
+            positive: true,
+        }
+    }
+
+    /// Convert a time, in milliseconds since the Unix epoch, read from a smeared clock to the time of a clock stepping
+    /// at the leap second.
+    ///
+    /// Times outside the window are returned unchanged. Just like the stepping clock, the converted time goes back one
+    /// second at a positive leap second.
+    pub fn to_utc(&self, smeared: u64) -> u64 {
+        let start = self.leap_at.saturating_sub(self.window / 2);
+        let end = self.leap_at + self.window / 2;
+        if self.window == 0 || smeared <= start || smeared >= end {
+            return smeared;
+        }
+
+        let smeared_offset = (smeared - start) * 1000 / self.window;
+        match (self.positive, smeared < self.leap_at) {
+            (true, true) => smeared + smeared_offset,
+            (true, false) => smeared + smeared_offset - 1000,
+            (false, true) => smeared - smeared_offset,
+            (false, false) => smeared + 1000 - smeared_offset,
+        }
+    }
+}
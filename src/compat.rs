@@ -0,0 +1,102 @@
+use crate::{
+    BitLayout, Spaceflake, SpaceflakeError, Worker, EPOCH_DISCORD, EPOCH_INSTAGRAM,
+    EPOCH_SONYFLAKE, EPOCH_TWITTER,
+};
+
+/// A Discord Snowflake decomposed into its parts, named as in Discord's documentation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct DiscordSnowflake {
+    /// The ID of the Snowflake.
+    pub id: u64,
+    /// The timestamp of the Snowflake, in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The internal worker ID of the Snowflake.
+    pub worker_id: u64,
+    /// The internal process ID of the Snowflake.
+    pub process_id: u64,
+    /// The increment of the Snowflake, for every ID generated on that process.
+    pub increment: u64,
+}
+
+/// An Instagram sharded ID decomposed into its parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct InstagramId {
+    /// The ID itself.
+    pub id: u64,
+    /// The time of the ID, in milliseconds since the Unix epoch.
+    pub time: u64,
+    /// The logical shard ID of the ID.
+    pub shard_id: u64,
+    /// The sequence of the ID, within its shard and millisecond.
+    pub sequence: u64,
+}
+
+/// Create a worker generating Twitter's Snowflakes for the given 10-bit machine ID, with [`BitLayout::twitter`] and
+/// [`EPOCH_TWITTER`].
+pub fn twitter_worker(machine_id: u64) -> Result<Worker, SpaceflakeError> {
+    preset_worker(machine_id, BitLayout::twitter(), EPOCH_TWITTER)
+}
+
+/// Create a worker generating Instagram's sharded IDs for the given 13-bit shard ID, with [`BitLayout::instagram`]
+/// and [`EPOCH_INSTAGRAM`].
+pub fn instagram_worker(shard_id: u64) -> Result<Worker, SpaceflakeError> {
+    preset_worker(shard_id, BitLayout::instagram(), EPOCH_INSTAGRAM)
+}
+
+/// Create a worker generating Sonyflakes for the given 16-bit machine ID, with [`BitLayout::sonyflake`] and
+/// [`EPOCH_SONYFLAKE`].
+pub fn sonyflake_worker(machine_id: u64) -> Result<Worker, SpaceflakeError> {
+    preset_worker(machine_id, BitLayout::sonyflake(), EPOCH_SONYFLAKE)
+}
+
+/// Parse a Twitter Snowflake ID, whose machine ID is returned by [`Spaceflake::machine_id`].
+pub fn parse_twitter(id: u64) -> Spaceflake {
+    Spaceflake::from_id(id, EPOCH_TWITTER).with_layout(BitLayout::twitter())
+}
+
+/// Parse a Sonyflake ID, whose machine ID is returned by [`Spaceflake::machine_id`].
+pub fn parse_sonyflake(id: u64) -> Spaceflake {
+    Spaceflake::from_id(id, EPOCH_SONYFLAKE).with_layout(BitLayout::sonyflake())
+}
+
+/// Parse a Discord Snowflake ID, such as a user, channel or message ID.
+pub fn parse_discord(id: u64) -> DiscordSnowflake {
+    let layout = BitLayout::discord();
+    DiscordSnowflake {
+        id,
+        timestamp: layout.parse_time(id, EPOCH_DISCORD),
+        worker_id: layout.parse_node_id(id),
+        process_id: layout.parse_worker_id(id),
+        increment: layout.parse_sequence(id),
+    }
+}
+
+/// Parse an Instagram sharded ID.
+pub fn parse_instagram(id: u64) -> InstagramId {
+    let layout = BitLayout::instagram();
+    InstagramId {
+        id,
+        time: layout.parse_time(id, EPOCH_INSTAGRAM),
+        shard_id: layout.parse_node_id(id),
+        sequence: layout.parse_sequence(id),
+    }
+}
+
+/// Create a worker whose node ID is the machine ID, for layouts without worker bits.
+fn preset_worker(
+    machine_id: u64,
+    layout: BitLayout,
+    base_epoch: u64,
+) -> Result<Worker, SpaceflakeError> {
+    if machine_id > layout.max_node_id() {
+        return Err(SpaceflakeError::InvalidNodeId {
+            max: layout.max_node_id(),
+        });
+    }
+    let mut worker = Worker::new(0, machine_id);
+    worker.base_epoch = base_epoch;
+    worker.layout = layout;
+    Ok(worker)
+}
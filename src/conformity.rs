@@ -0,0 +1,115 @@
+use std::ops::RangeInclusive;
+
+use crate::bloom::RollingBloom;
+use crate::{current_time, BitLayout, EPOCH};
+
+/// What the stored Spaceflake IDs scanned by [`audit_ids`] are expected to conform to.
+#[derive(Debug, Clone)]
+pub struct AuditExpectations {
+    /// The base epoch the Spaceflakes were generated with, default is [`EPOCH`].
+    pub base_epoch: u64,
+    /// The layout of the bits of the Spaceflakes, default is [`BitLayout::SPACEFLAKE`].
+    pub layout: BitLayout,
+    /// The node IDs the Spaceflakes may have been generated on, default is all of them.
+    pub node_ids: RangeInclusive<u64>,
+    /// The worker IDs the Spaceflakes may have been generated on, default is all of them.
+    pub worker_ids: RangeInclusive<u64>,
+    /// The time, in milliseconds, after which Spaceflakes are considered to be in the future, default is the time at
+    /// which the expectations were created.
+    pub now: u64,
+    /// The amount of IDs each of the two rolling bloom filters detecting the duplicates holds before they are rotated,
+    /// which bounds the memory of the audit, or `None` to skip the detection of duplicates. Default is 10 million.
+    pub dedupe_capacity: Option<usize>,
+}
+
+/// The default implementation of audit expectations.
+impl AuditExpectations {
+    /// Create new audit expectations for Spaceflakes with the given bit layout, allowing all of its node and worker
+    /// IDs.
+    pub fn with_layout(layout: BitLayout) -> Self {
+        AuditExpectations {
+            base_epoch: EPOCH,
+            layout,
+            node_ids: 0..=layout.max_node_id(),
+            worker_ids: 0..=layout.max_worker_id(),
+            now: current_time(),
+            dedupe_capacity: Some(10_000_000),
+        }
+    }
+}
+
+/// The default implementation of audit expectations.
+impl Default for AuditExpectations {
+    fn default() -> Self {
+        Self::with_layout(BitLayout::SPACEFLAKE)
+    }
+}
+
+/// The report of [`audit_ids`], listing the IDs violating the expectations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditReport {
+    /// The amount of IDs scanned.
+    pub scanned: usize,
+    /// The IDs with the sign bit set, which would be negative as a signed 64-bit integer.
+    pub sign_bit_set: Vec<u64>,
+    /// The IDs with a time in the future.
+    pub future: Vec<u64>,
+    /// The IDs with a node ID outside of the expected ones.
+    pub unexpected_node: Vec<u64>,
+    /// The IDs with a worker ID outside of the expected ones.
+    pub unexpected_worker: Vec<u64>,
+    /// The IDs that may have been seen before, listed once per additional occurrence, false positives of the bloom
+    /// filters included.
+    ///
+    /// They are not taken into account by [`AuditReport::is_conforming`], as some of them may be unique, and should
+    /// be confirmed with an exact lookup, for example in the database they were read from.
+    pub possible_duplicates: Vec<u64>,
+}
+
+/// The default implementation of an audit report.
+impl AuditReport {
+    /// Returns whether all the scanned IDs conform to the expectations, not taking the possible duplicates into
+    /// account, see [`AuditReport::possible_duplicates`].
+    pub fn is_conforming(&self) -> bool {
+        self.sign_bit_set.is_empty()
+            && self.future.is_empty()
+            && self.unexpected_node.is_empty()
+            && self.unexpected_worker.is_empty()
+    }
+}
+
+/// Scan stored Spaceflake IDs and report the ones violating the expectations, for example to verify data after a
+/// migration or a suspected misconfiguration of a generator.
+///
+/// The duplicates are detected with rolling bloom filters, so the memory stays bounded however many IDs are scanned,
+/// see [`AuditExpectations::dedupe_capacity`].
+pub fn audit_ids<I>(ids: I, expectations: &AuditExpectations) -> AuditReport
+where
+    I: IntoIterator<Item = u64>,
+{
+    let mut report = AuditReport::default();
+    let mut seen = expectations.dedupe_capacity.map(RollingBloom::new);
+    for id in ids {
+        report.scanned += 1;
+        let layout = &expectations.layout;
+        if !layout.is_sign_safe(id) {
+            report.sign_bit_set.push(id);
+        }
+        if layout.parse_time(id, expectations.base_epoch) > expectations.now {
+            report.future.push(id);
+        }
+        if !expectations.node_ids.contains(&layout.parse_node_id(id)) {
+            report.unexpected_node.push(id);
+        }
+        if !expectations
+            .worker_ids
+            .contains(&layout.parse_worker_id(id))
+        {
+            report.unexpected_worker.push(id);
+        }
+        if seen.as_mut().is_some_and(|seen| seen.check_and_insert(id)) {
+            report.possible_duplicates.push(id);
+        }
+    }
+    report
+}
@@ -0,0 +1,22 @@
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+#[cfg(feature = "time")]
+use time::OffsetDateTime;
+
+use crate::Spaceflake;
+
+/// The `chrono` and `time` implementation of a Spaceflake.
+impl Spaceflake {
+    /// Returns the time at which the Spaceflake has been generated as a `chrono` date time.
+    #[cfg(feature = "chrono")]
+    pub fn created_at_chrono(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(self.time() as i64).unwrap_or_default()
+    }
+
+    /// Returns the time at which the Spaceflake has been generated as a `time` date time.
+    #[cfg(feature = "time")]
+    pub fn created_at_time(&self) -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp_nanos(self.time() as i128 * 1_000_000)
+            .unwrap_or(OffsetDateTime::UNIX_EPOCH)
+    }
+}
@@ -0,0 +1,31 @@
+use diesel::backend::Backend;
+use diesel::deserialize::{self, FromSql};
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::BigInt;
+
+use crate::{Spaceflake, SpaceflakeError, EPOCH};
+
+/// The `diesel` serialization of a Spaceflake, as its ID in a `BIGINT`.
+impl<DB: Backend> ToSql<BigInt, DB> for Spaceflake
+where
+    i64: ToSql<BigInt, DB>,
+{
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, DB>) -> serialize::Result {
+        // SAFETY: `u64` and `i64` have the same size and alignment, and every bit pattern is valid for both.
+        let id = unsafe { &*(&self.id as *const u64 as *const i64) };
+        id.to_sql(out)
+    }
+}
+
+/// The `diesel` deserialization of a Spaceflake generated with the default [`EPOCH`], from its ID in a `BIGINT`.
+///
+/// Returns an error if the ID is negative.
+impl<DB: Backend> FromSql<BigInt, DB> for Spaceflake
+where
+    i64: FromSql<BigInt, DB>,
+{
+    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+        Spaceflake::try_from_i64(i64::from_sql(bytes)?, EPOCH)
+            .map_err(|error: SpaceflakeError| error.into())
+    }
+}
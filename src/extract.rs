@@ -0,0 +1,73 @@
+use std::fmt;
+
+#[cfg(feature = "axum")]
+use axum::extract::{FromRequestParts, Path};
+#[cfg(feature = "axum")]
+use axum::http::request::Parts;
+#[cfg(feature = "axum")]
+use axum::http::StatusCode;
+#[cfg(feature = "axum")]
+use axum::response::{IntoResponse, Response};
+#[cfg(feature = "axum")]
+use axum::Json;
+
+use crate::{Spaceflake, SpaceflakeError};
+
+/// An `axum` or `actix-web` extractor of a Spaceflake generated with the default [`crate::EPOCH`] from the single
+/// parameter of the path, such as `/users/{id}`, rejecting malformed IDs with a [`SpaceflakeRejection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpaceflakePath(pub Spaceflake);
+
+/// The rejection of a malformed Spaceflake by an extractor, responding with a `400 Bad Request` and the code and
+/// message of the error as JSON, such as `{"code":12,"message":"Invalid Spaceflake ID: abc"}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpaceflakeRejection(pub SpaceflakeError);
+
+/// The default implementation of a Spaceflake rejection.
+impl SpaceflakeRejection {
+    /// Returns the JSON body of the response of the rejection.
+    pub(crate) fn body(&self) -> serde_json::Value {
+        serde_json::json!({
+            "code": self.0.code().as_code(),
+            "message": self.0.to_string(),
+        })
+    }
+}
+
+/// The display implementation of a Spaceflake rejection. Will just return its error.
+impl fmt::Display for SpaceflakeRejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The `axum` extraction of a Spaceflake from the path.
+#[cfg(feature = "axum")]
+impl<S: Send + Sync> FromRequestParts<S> for SpaceflakePath {
+    type Rejection = SpaceflakeRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Path(id) = Path::<String>::from_request_parts(parts, state)
+            .await
+            .map_err(|rejection| {
+                SpaceflakeRejection(SpaceflakeError::InvalidArgument(rejection.body_text()))
+            })?;
+        id.parse().map(SpaceflakePath).map_err(SpaceflakeRejection)
+    }
+}
+
+/// The `axum` response of a rejected Spaceflake.
+#[cfg(feature = "axum")]
+impl IntoResponse for SpaceflakeRejection {
+    fn into_response(self) -> Response {
+        (StatusCode::BAD_REQUEST, Json(self.body())).into_response()
+    }
+}
+
+/// The `axum` response of a Spaceflake, its ID as plain text.
+#[cfg(feature = "axum")]
+impl IntoResponse for Spaceflake {
+    fn into_response(self) -> Response {
+        self.to_string().into_response()
+    }
+}
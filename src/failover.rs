@@ -0,0 +1,113 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use crate::{IdGenerator, Spaceflake, SpaceflakeError};
+
+/// A hook called with the last error of the primary generator when failing over.
+type FailoverHook = Box<dyn Fn(&SpaceflakeError) + Send + Sync>;
+
+/// A generator combining a primary and a backup [`IdGenerator`], so the issuance of Spaceflakes never depends on a
+/// single generator.
+///
+/// A Spaceflake the primary fails to generate is generated by the backup instead. Once the primary fails a given
+/// amount of times in a row, the generator fails over to the backup until [`FailoverGenerator::recover`] is called.
+pub struct FailoverGenerator<P: IdGenerator, B: IdGenerator> {
+    /// The generator used until it fails too many times.
+    primary: P,
+    /// The generator used when the primary fails.
+    backup: B,
+    /// The amount of failures in a row of the primary after which the generator fails over.
+    threshold: u32,
+    /// The amount of failures in a row of the primary.
+    failures: AtomicU32,
+    /// Whether the generator failed over to the backup.
+    failed_over: AtomicBool,
+    /// The hook called with the last error of the primary when failing over.
+    on_failover: Option<FailoverHook>,
+}
+
+/// The default implementation of a failover generator.
+impl<P: IdGenerator, B: IdGenerator> FailoverGenerator<P, B> {
+    /// Create a new failover generator that fails over to the backup after the given amount of failures in a row of
+    /// the primary.
+    ///
+    /// Returns an error if the threshold is `0`.
+    pub fn try_new(primary: P, backup: B, threshold: u32) -> Result<Self, SpaceflakeError> {
+        if threshold == 0 {
+            return Err(SpaceflakeError::InvalidArgument(String::from(
+                "Failover threshold must be greater than 0",
+            )));
+        }
+
+        Ok(FailoverGenerator {
+            primary,
+            backup,
+            threshold,
+            failures: AtomicU32::new(0),
+            failed_over: AtomicBool::new(false),
+            on_failover: None,
+        })
+    }
+
+    /// Set the hook called with the last error of the primary when failing over, for example to raise an alert.
+    pub fn on_failover<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&SpaceflakeError) + Send + Sync + 'static,
+    {
+        self.on_failover = Some(Box::new(hook));
+        self
+    }
+
+    /// Returns whether the generator failed over to the backup.
+    pub fn is_failed_over(&self) -> bool {
+        self.failed_over.load(Ordering::SeqCst)
+    }
+
+    /// Switch back to the primary generator.
+    pub fn recover(&self) {
+        self.failures.store(0, Ordering::SeqCst);
+        self.failed_over.store(false, Ordering::SeqCst);
+    }
+}
+
+/// The generator implementation of a failover generator.
+impl<P: IdGenerator, B: IdGenerator> IdGenerator for FailoverGenerator<P, B> {
+    fn generate(&self) -> Result<Spaceflake, SpaceflakeError> {
+        if self.is_failed_over() {
+            return self.backup.generate();
+        }
+
+        match self.primary.generate() {
+            Ok(spaceflake) => {
+                self.failures.store(0, Ordering::SeqCst);
+                Ok(spaceflake)
+            }
+            Err(error) => {
+                let failures = self.failures.fetch_add(1, Ordering::SeqCst) + 1;
+                if failures >= self.threshold && !self.failed_over.swap(true, Ordering::SeqCst) {
+                    if let Some(hook) = &self.on_failover {
+                        hook(&error);
+                    }
+                }
+                self.backup.generate()
+            }
+        }
+    }
+}
+
+/// The debug implementation of a failover generator.
+impl<P, B> fmt::Debug for FailoverGenerator<P, B>
+where
+    P: IdGenerator + fmt::Debug,
+    B: IdGenerator + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FailoverGenerator")
+            .field("primary", &self.primary)
+            .field("backup", &self.backup)
+            .field("threshold", &self.threshold)
+            .field("failures", &self.failures)
+            .field("failed_over", &self.failed_over)
+            .finish()
+    }
+}
@@ -0,0 +1,65 @@
+use std::sync::mpsc::{channel, sync_channel, Sender};
+use std::thread;
+
+use crate::{IdGenerator, Spaceflake, SpaceflakeError, Worker};
+
+/// The callback receiving the response to a request for a Spaceflake.
+pub(crate) type Reply = Box<dyn FnOnce(Result<Spaceflake, SpaceflakeError>) + Send>;
+
+/// A service running a worker in its own thread, handing out Spaceflakes through a channel of requests, so many
+/// threads can share one worker without contending on it.
+///
+/// The service can be cloned to be shared, and the thread stops once all the clones are dropped.
+#[derive(Debug, Clone)]
+pub struct GeneratorService {
+    /// The sending end of the channel of requests, answered by the thread of the service.
+    sender: Sender<Reply>,
+}
+
+/// The default implementation of a generator service.
+impl GeneratorService {
+    /// Spawn a new thread generating Spaceflakes on the worker for the requests of the service.
+    ///
+    /// Returns an error if the thread could not be spawned.
+    pub fn spawn(worker: Worker) -> Result<Self, SpaceflakeError> {
+        let (sender, receiver) = channel::<Reply>();
+        thread::Builder::new()
+            .name(String::from("spaceflake-generator"))
+            .spawn(move || {
+                for reply in receiver {
+                    reply(worker.generate());
+                }
+            })
+            .map_err(|error| SpaceflakeError::Io(error.to_string()))?;
+
+        Ok(GeneratorService { sender })
+    }
+
+    /// Request a new Spaceflake from the thread of the service, blocking until the response.
+    pub fn generate(&self) -> Result<Spaceflake, SpaceflakeError> {
+        let (sender, receiver) = sync_channel(1);
+        self.request(Box::new(move |result| {
+            let _ = sender.send(result);
+        }))?;
+        receiver.recv().map_err(|_| GeneratorService::stopped())?
+    }
+
+    /// Send a request to the thread of the service, answered with the reply.
+    pub(crate) fn request(&self, reply: Reply) -> Result<(), SpaceflakeError> {
+        self.sender
+            .send(reply)
+            .map_err(|_| GeneratorService::stopped())
+    }
+
+    /// Returns the error raised when the thread of the service is stopped.
+    pub(crate) fn stopped() -> SpaceflakeError {
+        SpaceflakeError::Source(String::from("The generator service is stopped"))
+    }
+}
+
+/// The generator implementation of a generator service.
+impl IdGenerator for GeneratorService {
+    fn generate(&self) -> Result<Spaceflake, SpaceflakeError> {
+        GeneratorService::generate(self)
+    }
+}
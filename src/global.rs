@@ -0,0 +1,27 @@
+use std::sync::OnceLock;
+
+use crate::{GeneratorSettings, SpaceflakeError, Worker};
+
+/// The process-wide worker, see [`global`].
+static GLOBAL: OnceLock<Worker> = OnceLock::new();
+
+/// Configure the process-wide worker returned by [`global`], once and before its first use.
+///
+/// Returns an error if the node or worker ID does not fit in the bits of the layout, or if the process-wide worker was
+/// already configured or used.
+pub fn init(settings: GeneratorSettings) -> Result<(), SpaceflakeError> {
+    GLOBAL.set(Worker::from_settings(settings)?).map_err(|_| {
+        SpaceflakeError::InvalidArgument("The global worker is already initialized".to_string())
+    })
+}
+
+/// Returns the process-wide worker, configured with [`init`] or else with the default generator settings.
+///
+/// The worker is shared by every thread, so its Spaceflakes are unique within the process, without passing generator
+/// settings around like with [`crate::generate`].
+pub fn global() -> &'static Worker {
+    GLOBAL.get_or_init(|| {
+        Worker::from_settings(GeneratorSettings::default())
+            .expect("The default generator settings are valid")
+    })
+}
@@ -0,0 +1,52 @@
+#[cfg(feature = "async-graphql")]
+use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType};
+#[cfg(feature = "juniper")]
+use juniper::{graphql_scalar, InputValue, ScalarValue};
+
+use crate::Spaceflake;
+
+/// The `async-graphql` scalar of a Spaceflake, named `Snowflake` and serialized as its ID in a string, parsed from a
+/// string or a number as generated with the default [`crate::EPOCH`].
+#[cfg(feature = "async-graphql")]
+#[Scalar(name = "Snowflake")]
+impl ScalarType for Spaceflake {
+    fn parse(value: async_graphql::Value) -> InputValueResult<Self> {
+        match &value {
+            async_graphql::Value::String(id) => Ok(id.parse()?),
+            async_graphql::Value::Number(id) => match id.as_u64() {
+                Some(id) => Ok(Spaceflake::try_from(id)?),
+                None => Err(InputValueError::expected_type(value)),
+            },
+            _ => Err(InputValueError::expected_type(value)),
+        }
+    }
+
+    fn to_value(&self) -> async_graphql::Value {
+        async_graphql::Value::String(self.to_string())
+    }
+}
+
+/// The `juniper` scalar of a Spaceflake, serialized as its ID in a string, parsed as generated with the default
+/// [`crate::EPOCH`].
+#[cfg(feature = "juniper")]
+#[graphql_scalar(with = snowflake_scalar, parse_token(String))]
+type Snowflake = Spaceflake;
+
+/// The conversions of the `juniper` scalar of a Spaceflake.
+#[cfg(feature = "juniper")]
+mod snowflake_scalar {
+    use super::*;
+
+    pub(super) fn to_output<S: ScalarValue>(v: &Snowflake) -> juniper::Value<S> {
+        juniper::Value::scalar(v.to_string())
+    }
+
+    pub(super) fn from_input<S: ScalarValue>(v: &InputValue<S>) -> Result<Snowflake, String> {
+        v.as_string_value()
+            .ok_or_else(|| format!("Expected `String`, found: {v}"))
+            .and_then(|id| {
+                id.parse()
+                    .map_err(|error| format!("Failed to parse `Snowflake`: {error}"))
+            })
+    }
+}
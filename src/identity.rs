@@ -0,0 +1,146 @@
+use std::env;
+use std::fs;
+use std::net::{IpAddr, UdpSocket};
+
+use crate::{BitLayout, Node, SpaceflakeError, MAX_5_BITS};
+
+/// How the ID of a node is derived from the identity of the host it runs on, see [`Node::auto`].
+///
+/// Apart from the fixed and environment IDs, the identity is hashed into the 5 bits of the node ID, so distinct hosts
+/// may still collide and should be checked for it when the fleet is large.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeIdStrategy {
+    /// A fixed node ID.
+    Fixed(u64),
+    /// A hash of the hostname.
+    FromHostname,
+    /// A hash of the MAC address of the first network interface, by name, that is not a loopback one.
+    FromMacAddress,
+    /// The low bits of the IP address used to reach other hosts.
+    FromIpLowBits,
+    /// The node ID read from the given environment variable.
+    FromEnv(&'static str),
+    /// The ordinal of the Kubernetes StatefulSet pod, see [`statefulset_ordinal`].
+    FromStatefulSetOrdinal,
+}
+
+/// The default implementation of a node ID strategy.
+impl NodeIdStrategy {
+    /// Returns the node ID derived with the strategy.
+    pub fn node_id(&self) -> Result<u64, SpaceflakeError> {
+        let node_id = match self {
+            NodeIdStrategy::Fixed(node_id) => *node_id,
+            NodeIdStrategy::FromHostname => fnv1a(hostname()?.as_bytes()) % (MAX_5_BITS + 1),
+            NodeIdStrategy::FromMacAddress => fnv1a(&mac_address()?) % (MAX_5_BITS + 1),
+            NodeIdStrategy::FromIpLowBits => match local_ip()? {
+                IpAddr::V4(ip) => u64::from(ip.octets()[3]) & MAX_5_BITS,
+                IpAddr::V6(ip) => u64::from(ip.segments()[7]) & MAX_5_BITS,
+            },
+            NodeIdStrategy::FromEnv(name) => {
+                let value = env::var(name).map_err(|_| {
+                    SpaceflakeError::InvalidArgument(format!(
+                        "Environment variable {} is not set",
+                        name
+                    ))
+                })?;
+                value.trim().parse().map_err(|_| {
+                    SpaceflakeError::InvalidArgument(format!(
+                        "Environment variable {} is not a node ID: {}",
+                        name, value
+                    ))
+                })?
+            }
+            NodeIdStrategy::FromStatefulSetOrdinal => statefulset_ordinal(&BitLayout::SPACEFLAKE)?,
+        };
+        if node_id > MAX_5_BITS {
+            return Err(SpaceflakeError::InvalidNodeId { max: MAX_5_BITS });
+        }
+        Ok(node_id)
+    }
+}
+
+/// The automatic implementation of a node.
+impl Node {
+    /// Create a new node whose ID is derived from the identity of the host with the given strategy, so fleets do not
+    /// need a manual configuration per host.
+    pub fn auto(strategy: NodeIdStrategy) -> Result<Self, SpaceflakeError> {
+        Node::try_new(strategy.node_id()?)
+    }
+}
+
+/// Returns the ordinal of the Kubernetes StatefulSet pod the process runs in, parsed from its hostname, for example `3`
+/// for `api-3`, to be used as a unique node ID.
+///
+/// Returns an error if the hostname does not end with an ordinal, or if the ordinal does not fit in the node bits of
+/// the layout.
+pub fn statefulset_ordinal(layout: &BitLayout) -> Result<u64, SpaceflakeError> {
+    parse_statefulset_ordinal(&hostname()?, layout)
+}
+
+/// Returns the ordinal of a Kubernetes StatefulSet pod parsed from its hostname, see [`statefulset_ordinal`].
+pub fn parse_statefulset_ordinal(
+    hostname: &str,
+    layout: &BitLayout,
+) -> Result<u64, SpaceflakeError> {
+    let ordinal = hostname
+        .rsplit_once('-')
+        .and_then(|(_, ordinal)| ordinal.parse::<u64>().ok())
+        .ok_or_else(|| {
+            SpaceflakeError::InvalidArgument(format!(
+                "Hostname {} does not end with a StatefulSet ordinal",
+                hostname
+            ))
+        })?;
+    if ordinal > layout.max_node_id() {
+        return Err(SpaceflakeError::InvalidNodeId {
+            max: layout.max_node_id(),
+        });
+    }
+    Ok(ordinal)
+}
+
+/// Returns the hostname of the host.
+fn hostname() -> Result<String, SpaceflakeError> {
+    let hostname = env::var("HOSTNAME")
+        .or_else(|_| env::var("COMPUTERNAME"))
+        .or_else(|_| fs::read_to_string("/proc/sys/kernel/hostname"))
+        .or_else(|_| fs::read_to_string("/etc/hostname"))
+        .map(|hostname| hostname.trim().to_string())
+        .unwrap_or_default();
+    if hostname.is_empty() {
+        return Err(SpaceflakeError::Source(String::from(
+            "The hostname could not be read",
+        )));
+    }
+    Ok(hostname)
+}
+
+/// Returns the MAC address of the first network interface, by name, that is not a loopback one.
+fn mac_address() -> Result<Vec<u8>, SpaceflakeError> {
+    let mut interfaces = fs::read_dir("/sys/class/net")?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+    interfaces.sort();
+    interfaces
+        .iter()
+        .filter_map(|interface| fs::read_to_string(interface.join("address")).ok())
+        .map(|address| address.trim().to_string())
+        .find(|address| !address.is_empty() && address != "00:00:00:00:00:00")
+        .map(|address| address.into_bytes())
+        .ok_or_else(|| SpaceflakeError::Source(String::from("No MAC address could be read")))
+}
+
+/// Returns the IP address used to reach other hosts, without sending any packet.
+fn local_ip() -> Result<IpAddr, SpaceflakeError> {
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    socket.connect("192.0.2.1:9")?;
+    Ok(socket.local_addr()?.ip())
+}
+
+/// Hash bytes with the 64-bit FNV-1a hash, which is stable across versions and platforms.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xCBF29CE484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001B3)
+    })
+}
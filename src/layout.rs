@@ -0,0 +1,286 @@
+use crate::SpaceflakeError;
+
+/// The maximum amount of bits of the sequence of a bit layout.
+const MAX_SEQUENCE_BITS: u64 = 21;
+
+/// The unit of the time of a bit layout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum TimeUnit {
+    /// The time is in milliseconds, like Spaceflakes.
+    #[default]
+    Millisecond,
+    /// The time is in units of 10 milliseconds, like Sonyflakes.
+    Centisecond,
+    /// The time is in seconds.
+    Second,
+}
+
+/// The default implementation of a time unit.
+impl TimeUnit {
+    /// Returns the amount of milliseconds in one unit.
+    pub const fn as_millis(&self) -> u64 {
+        match self {
+            TimeUnit::Millisecond => 1,
+            TimeUnit::Centisecond => 10,
+            TimeUnit::Second => 1000,
+        }
+    }
+}
+
+/// The order of the parts of an ID after the time, from the most significant to the least significant ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum PartOrder {
+    /// The node ID, the worker ID and then the sequence, like Spaceflakes.
+    #[default]
+    NodeWorkerSequence,
+    /// The sequence, the node ID and then the worker ID, like Sonyflakes.
+    SequenceNodeWorker,
+}
+
+/// The layout of the 63 usable bits of a Spaceflake, from the most significant to the least significant ones: the
+/// time, the node ID, the worker ID and the sequence.
+///
+/// The default layout is the Spaceflake one, 41/5/5/12, but node space can be traded for sequence space, for example
+/// 41/10/0/12 like Twitter's Snowflakes. The unit of the time and the order of the other parts can be changed too, for
+/// example for Sonyflakes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct BitLayout {
+    /// The amount of bits of the time.
+    pub time_bits: u64,
+    /// The amount of bits of the node ID, can be `0`.
+    pub node_bits: u64,
+    /// The amount of bits of the worker ID, can be `0`.
+    pub worker_bits: u64,
+    /// The amount of bits of the sequence, including the version tag if any, up to 21.
+    pub sequence_bits: u64,
+    /// The unit of the time, milliseconds by default.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub time_unit: TimeUnit,
+    /// The order of the parts after the time.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub order: PartOrder,
+}
+
+/// The default implementation of a bit layout.
+impl BitLayout {
+    /// The layout of Spaceflakes, 41/5/5/12.
+    pub const SPACEFLAKE: BitLayout = BitLayout {
+        time_bits: 41,
+        node_bits: 5,
+        worker_bits: 5,
+        sequence_bits: 12,
+        time_unit: TimeUnit::Millisecond,
+        order: PartOrder::NodeWorkerSequence,
+    };
+
+    /// The layout of Twitter's Snowflakes, 41/10/0/12, where the node ID is the 10-bit machine ID.
+    pub const fn twitter() -> BitLayout {
+        BitLayout {
+            time_bits: 41,
+            node_bits: 10,
+            worker_bits: 0,
+            sequence_bits: 12,
+            time_unit: TimeUnit::Millisecond,
+            order: PartOrder::NodeWorkerSequence,
+        }
+    }
+
+    /// The layout of Discord's Snowflakes, 41/5/5/12, where the node ID is the internal worker ID and the worker ID is
+    /// the internal process ID.
+    ///
+    /// Discord's timestamp has 42 bits, but its most significant bit stays unset until 2084.
+    pub const fn discord() -> BitLayout {
+        BitLayout::SPACEFLAKE
+    }
+
+    /// The layout of Sonyflakes, with 39 bits of time in units of 10 milliseconds, 8 bits of sequence and then 16 bits
+    /// of machine ID, which is the node ID.
+    pub const fn sonyflake() -> BitLayout {
+        BitLayout {
+            time_bits: 39,
+            node_bits: 16,
+            worker_bits: 0,
+            sequence_bits: 8,
+            time_unit: TimeUnit::Centisecond,
+            order: PartOrder::SequenceNodeWorker,
+        }
+    }
+
+    /// The layout of Instagram's sharded IDs, 40/13/0/10, where the node ID is the shard ID.
+    ///
+    /// Instagram's time has 41 bits, but its most significant bit stays unset until 2046.
+    pub const fn instagram() -> BitLayout {
+        BitLayout {
+            time_bits: 40,
+            node_bits: 13,
+            worker_bits: 0,
+            sequence_bits: 10,
+            time_unit: TimeUnit::Millisecond,
+            order: PartOrder::NodeWorkerSequence,
+        }
+    }
+
+    /// Create a new bit layout, with the time in milliseconds and the parts in the Spaceflake order.
+    ///
+    /// Returns an error if the bits do not sum to 63, if there is no bit for the time or for the sequence, or if
+    /// there are more than 21 bits for the sequence.
+    pub fn new(
+        time_bits: u64,
+        node_bits: u64,
+        worker_bits: u64,
+        sequence_bits: u64,
+    ) -> Result<Self, SpaceflakeError> {
+        let layout = BitLayout {
+            time_bits,
+            node_bits,
+            worker_bits,
+            sequence_bits,
+            time_unit: TimeUnit::Millisecond,
+            order: PartOrder::NodeWorkerSequence,
+        };
+        layout.validate()?;
+        Ok(layout)
+    }
+
+    /// Returns the bit layout with the time in the given unit.
+    pub const fn with_time_unit(mut self, time_unit: TimeUnit) -> Self {
+        self.time_unit = time_unit;
+        self
+    }
+
+    /// Returns the bit layout with the parts after the time in the given order.
+    pub const fn with_order(mut self, order: PartOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Validate the bit layout, see [`BitLayout::new`].
+    pub fn validate(&self) -> Result<(), SpaceflakeError> {
+        let total = self
+            .time_bits
+            .checked_add(self.node_bits)
+            .and_then(|total| total.checked_add(self.worker_bits))
+            .and_then(|total| total.checked_add(self.sequence_bits));
+        if total != Some(63)
+            || self.time_bits == 0
+            || self.sequence_bits == 0
+            || self.sequence_bits > MAX_SEQUENCE_BITS
+        {
+            return Err(SpaceflakeError::InvalidBitLayout);
+        }
+        Ok(())
+    }
+
+    /// Returns the maximum time, relative to the base epoch and in the unit of the layout, that fits in the layout.
+    pub fn max_time(&self) -> u64 {
+        mask(self.time_bits)
+    }
+
+    /// Returns the maximum node ID that fits in the layout.
+    pub fn max_node_id(&self) -> u64 {
+        mask(self.node_bits)
+    }
+
+    /// Returns the maximum worker ID that fits in the layout.
+    pub fn max_worker_id(&self) -> u64 {
+        mask(self.worker_bits)
+    }
+
+    /// Returns the maximum sequence, including the version tag if any, that fits in the layout.
+    pub fn max_sequence(&self) -> u64 {
+        mask(self.sequence_bits)
+    }
+
+    /// Compose an ID from its parts, which must fit in their bits, with the time in the unit of the layout.
+    pub fn compose(&self, time: u64, node_id: u64, worker_id: u64, sequence: u64) -> u64 {
+        (time << self.time_shift())
+            | (node_id << self.node_shift())
+            | (worker_id << self.worker_shift())
+            | (sequence << self.sequence_shift())
+    }
+
+    /// Returns whether the most significant bit of an ID is unset, so the ID round-trips through signed 64-bit
+    /// integers, such as SQL `BIGINT` columns, without being read as negative.
+    pub fn is_sign_safe(&self, id: u64) -> bool {
+        id >> 63 == 0
+    }
+
+    /// Returns the time, in milliseconds since the Unix epoch, of an ID for the given base epoch.
+    pub fn parse_time(&self, id: u64, base_epoch: u64) -> u64 {
+        id.checked_shr(self.time_shift() as u32)
+            .unwrap_or(0)
+            .saturating_mul(self.time_unit.as_millis())
+            + base_epoch
+    }
+
+    /// Returns the node ID of an ID.
+    pub fn parse_node_id(&self, id: u64) -> u64 {
+        id.checked_shr(self.node_shift() as u32).unwrap_or(0) & self.max_node_id()
+    }
+
+    /// Returns the worker ID of an ID.
+    pub fn parse_worker_id(&self, id: u64) -> u64 {
+        id.checked_shr(self.worker_shift() as u32).unwrap_or(0) & self.max_worker_id()
+    }
+
+    /// Returns the machine ID of an ID, which is its node and worker IDs merged.
+    pub fn parse_machine_id(&self, id: u64) -> u64 {
+        id.checked_shr(self.worker_shift() as u32).unwrap_or(0)
+            & mask(self.node_bits.saturating_add(self.worker_bits))
+    }
+
+    /// Returns the sequence of an ID, including the version tag if any.
+    pub fn parse_sequence(&self, id: u64) -> u64 {
+        id.checked_shr(self.sequence_shift() as u32).unwrap_or(0) & self.max_sequence()
+    }
+
+    /// Returns the offset of the time in an ID.
+    pub(crate) fn time_shift(&self) -> u64 {
+        self.node_bits
+            .saturating_add(self.worker_bits)
+            .saturating_add(self.sequence_bits)
+    }
+
+    /// Returns the offset of the node ID in an ID.
+    pub(crate) fn node_shift(&self) -> u64 {
+        match self.order {
+            PartOrder::NodeWorkerSequence => self.worker_bits.saturating_add(self.sequence_bits),
+            PartOrder::SequenceNodeWorker => self.worker_bits,
+        }
+    }
+
+    /// Returns the offset of the worker ID in an ID.
+    pub(crate) fn worker_shift(&self) -> u64 {
+        match self.order {
+            PartOrder::NodeWorkerSequence => self.sequence_bits,
+            PartOrder::SequenceNodeWorker => 0,
+        }
+    }
+
+    /// Returns the offset of the sequence in an ID.
+    pub(crate) fn sequence_shift(&self) -> u64 {
+        match self.order {
+            PartOrder::NodeWorkerSequence => 0,
+            PartOrder::SequenceNodeWorker => self.node_bits.saturating_add(self.worker_bits),
+        }
+    }
+}
+
+/// The default implementation of a bit layout, which is [`BitLayout::SPACEFLAKE`].
+impl Default for BitLayout {
+    fn default() -> Self {
+        BitLayout::SPACEFLAKE
+    }
+}
+
+/// Returns the maximum number that can be set with the given amount of bits.
+fn mask(bits: u64) -> u64 {
+    if bits >= 64 {
+        u64::MAX
+    } else {
+        (1 << bits) - 1
+    }
+}
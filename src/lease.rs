@@ -0,0 +1,395 @@
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use crate::{current_time, SpaceflakeError, Worker, MAX_5_BITS};
+
+/// A lease on a node and worker ID pair, claimed from an [`IdAllocator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdLease {
+    /// The leased node ID.
+    pub node_id: u64,
+    /// The leased worker ID.
+    pub worker_id: u64,
+    /// The time, in milliseconds since the Unix epoch, at which the lease expires unless it is renewed.
+    pub expires_at: u64,
+    /// The random token proving the ownership of the lease.
+    pub token: u64,
+}
+
+/// The default implementation of an ID lease.
+impl IdLease {
+    /// Create a new standalone worker for the leased node and worker IDs.
+    pub fn worker(&self) -> Result<Worker, SpaceflakeError> {
+        Worker::standalone(self.node_id, self.worker_id)
+    }
+}
+
+/// A coordinator assigning unique node and worker ID pairs across processes and machines, with leases claimed at
+/// startup, renewed while running and released at shutdown.
+pub trait IdAllocator: Send + Sync {
+    /// Claim a lease on a node and worker ID pair that is not leased by anyone else, for the given time to live.
+    fn acquire(&self, ttl: Duration) -> Result<IdLease, SpaceflakeError>;
+
+    /// Extend a lease for the given time to live, returning the renewed lease.
+    ///
+    /// Returns an error if the lease expired or is no longer owned.
+    fn renew(&self, lease: &IdLease, ttl: Duration) -> Result<IdLease, SpaceflakeError>;
+
+    /// Release a lease, so its node and worker ID pair can be claimed again.
+    fn release(&self, lease: IdLease) -> Result<(), SpaceflakeError>;
+}
+
+/// Returns the error of a lease that is not owned anymore.
+fn lost_lease(lease: &IdLease) -> SpaceflakeError {
+    SpaceflakeError::Source(format!(
+        "The lease of node {} and worker {} is not owned anymore",
+        lease.node_id, lease.worker_id
+    ))
+}
+
+/// Returns the error of an allocator without any available node and worker ID pair.
+fn exhausted_leases() -> SpaceflakeError {
+    SpaceflakeError::Source(String::from("No node and worker ID pair is available"))
+}
+
+/// An ID allocator for the processes of a single machine, holding an exclusive lock on a file per leased node and
+/// worker ID pair in a directory.
+///
+/// The locks are released by the operating system when the process exits, even if it crashes, so the time to live of
+/// the leases is only informative.
+#[derive(Debug)]
+pub struct FileIdAllocator {
+    /// The directory of the lock files.
+    directory: PathBuf,
+    /// The locked files of the leases held by this allocator, by node and worker IDs.
+    held: Mutex<HashMap<(u64, u64), (u64, File)>>,
+}
+
+/// The default implementation of a file ID allocator.
+impl FileIdAllocator {
+    /// Create a new file ID allocator locking files in the given directory, which is created if needed.
+    pub fn new<P: AsRef<Path>>(directory: P) -> Result<Self, SpaceflakeError> {
+        let directory = directory.as_ref().to_path_buf();
+        fs::create_dir_all(&directory)?;
+        Ok(FileIdAllocator {
+            directory,
+            held: Mutex::new(HashMap::new()),
+        })
+    }
+}
+
+/// The ID allocator implementation of a file ID allocator.
+impl IdAllocator for FileIdAllocator {
+    fn acquire(&self, ttl: Duration) -> Result<IdLease, SpaceflakeError> {
+        let mut held = self.held.lock().unwrap_or_else(PoisonError::into_inner);
+        for node_id in 0..=MAX_5_BITS {
+            for worker_id in 0..=MAX_5_BITS {
+                if held.contains_key(&(node_id, worker_id)) {
+                    continue;
+                }
+                let file = OpenOptions::new()
+                    .create(true)
+                    .truncate(false)
+                    .write(true)
+                    .open(
+                        self.directory
+                            .join(format!("{}-{}.lock", node_id, worker_id)),
+                    )?;
+                match file.try_lock() {
+                    Ok(()) => {
+                        let token = rand::random();
+                        held.insert((node_id, worker_id), (token, file));
+                        return Ok(IdLease {
+                            node_id,
+                            worker_id,
+                            expires_at: current_time() + ttl.as_millis() as u64,
+                            token,
+                        });
+                    }
+                    Err(TryLockError::WouldBlock) => continue,
+                    Err(TryLockError::Error(error)) => return Err(error.into()),
+                }
+            }
+        }
+        Err(exhausted_leases())
+    }
+
+    fn renew(&self, lease: &IdLease, ttl: Duration) -> Result<IdLease, SpaceflakeError> {
+        let held = self.held.lock().unwrap_or_else(PoisonError::into_inner);
+        match held.get(&(lease.node_id, lease.worker_id)) {
+            Some((token, _)) if *token == lease.token => Ok(IdLease {
+                expires_at: current_time() + ttl.as_millis() as u64,
+                ..lease.clone()
+            }),
+            _ => Err(lost_lease(lease)),
+        }
+    }
+
+    fn release(&self, lease: IdLease) -> Result<(), SpaceflakeError> {
+        let mut held = self.held.lock().unwrap_or_else(PoisonError::into_inner);
+        match held.get(&(lease.node_id, lease.worker_id)) {
+            Some((token, _)) if *token == lease.token => {
+                // Dropping the file releases its lock.
+                held.remove(&(lease.node_id, lease.worker_id));
+                Ok(())
+            }
+            _ => Err(lost_lease(&lease)),
+        }
+    }
+}
+
+/// The script renewing a lease in Redis, only if it is still owned.
+#[cfg(feature = "redis")]
+const REDIS_RENEW_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("PEXPIRE", KEYS[1], ARGV[2])
+end
+return 0
+"#;
+
+/// The script releasing a lease in Redis, only if it is still owned.
+#[cfg(feature = "redis")]
+const REDIS_RELEASE_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+end
+return 0
+"#;
+
+/// An ID allocator for multiple machines, storing the leases as expiring keys in Redis.
+///
+/// Leases that are not renewed expire on their own, so the pairs of crashed processes are claimed again.
+#[cfg(feature = "redis")]
+#[derive(Debug, Clone)]
+pub struct RedisIdAllocator {
+    /// The client of the Redis server.
+    client: redis::Client,
+    /// The prefix of the keys of the leases.
+    prefix: String,
+}
+
+/// The default implementation of a Redis ID allocator.
+#[cfg(feature = "redis")]
+impl RedisIdAllocator {
+    /// Create a new Redis ID allocator for the server at the given URL, such as `redis://127.0.0.1/`, storing the
+    /// leases under keys starting with the given prefix.
+    pub fn new(url: &str, prefix: &str) -> Result<Self, SpaceflakeError> {
+        let client = redis::Client::open(url).map_err(redis_error)?;
+        Ok(RedisIdAllocator {
+            client,
+            prefix: prefix.to_string(),
+        })
+    }
+
+    /// Returns the key of the lease of a node and worker ID pair.
+    fn key(&self, node_id: u64, worker_id: u64) -> String {
+        format!("{}{}-{}", self.prefix, node_id, worker_id)
+    }
+
+    /// Run a script on the key of a lease, returning whether it was still owned.
+    fn run_owned(
+        &self,
+        script: &str,
+        lease: &IdLease,
+        args: &[u64],
+    ) -> Result<bool, SpaceflakeError> {
+        let mut connection = self.client.get_connection().map_err(redis_error)?;
+        let script = redis::Script::new(script);
+        let mut invocation = script.key(self.key(lease.node_id, lease.worker_id));
+        invocation.arg(lease.token.to_string());
+        for arg in args {
+            invocation.arg(*arg);
+        }
+        let owned: i64 = invocation.invoke(&mut connection).map_err(redis_error)?;
+        Ok(owned == 1)
+    }
+}
+
+/// The ID allocator implementation of a Redis ID allocator.
+#[cfg(feature = "redis")]
+impl IdAllocator for RedisIdAllocator {
+    fn acquire(&self, ttl: Duration) -> Result<IdLease, SpaceflakeError> {
+        let mut connection = self.client.get_connection().map_err(redis_error)?;
+        let token: u64 = rand::random();
+        let ttl_ms = ttl.as_millis() as u64;
+        for node_id in 0..=MAX_5_BITS {
+            for worker_id in 0..=MAX_5_BITS {
+                let claimed: Option<String> = redis::cmd("SET")
+                    .arg(self.key(node_id, worker_id))
+                    .arg(token.to_string())
+                    .arg("NX")
+                    .arg("PX")
+                    .arg(ttl_ms)
+                    .query(&mut connection)
+                    .map_err(redis_error)?;
+                if claimed.is_some() {
+                    return Ok(IdLease {
+                        node_id,
+                        worker_id,
+                        expires_at: current_time() + ttl_ms,
+                        token,
+                    });
+                }
+            }
+        }
+        Err(exhausted_leases())
+    }
+
+    fn renew(&self, lease: &IdLease, ttl: Duration) -> Result<IdLease, SpaceflakeError> {
+        let ttl_ms = ttl.as_millis() as u64;
+        if !self.run_owned(REDIS_RENEW_SCRIPT, lease, &[ttl_ms])? {
+            return Err(lost_lease(lease));
+        }
+        Ok(IdLease {
+            expires_at: current_time() + ttl_ms,
+            ..lease.clone()
+        })
+    }
+
+    fn release(&self, lease: IdLease) -> Result<(), SpaceflakeError> {
+        if !self.run_owned(REDIS_RELEASE_SCRIPT, &lease, &[])? {
+            return Err(lost_lease(&lease));
+        }
+        Ok(())
+    }
+}
+
+/// Convert a Redis error into a Spaceflake error.
+#[cfg(feature = "redis")]
+fn redis_error(error: redis::RedisError) -> SpaceflakeError {
+    SpaceflakeError::Source(format!("Redis: {}", error))
+}
+
+/// An ID allocator for Kubernetes deployments and other clusters, storing the leases as keys attached to etcd leases,
+/// through the JSON gateway of the etcd v3 API.
+///
+/// The keys are deleted by etcd once their lease is not kept alive anymore, for example when the pod died. As etcd
+/// leases keep the time to live they were granted with, renewals extend the lease by the time to live of the
+/// acquisition.
+#[cfg(feature = "etcd")]
+#[derive(Debug, Clone)]
+pub struct EtcdIdAllocator {
+    /// The HTTP agent calling the gateway.
+    agent: ureq::Agent,
+    /// The URL of the etcd endpoint, such as `http://127.0.0.1:2379`.
+    endpoint: String,
+    /// The prefix of the keys of the leases.
+    prefix: String,
+}
+
+/// The default implementation of an etcd ID allocator.
+#[cfg(feature = "etcd")]
+impl EtcdIdAllocator {
+    /// Create a new etcd ID allocator for the endpoint at the given URL, such as `http://127.0.0.1:2379`, storing the
+    /// leases under keys starting with the given prefix.
+    pub fn new(endpoint: &str, prefix: &str) -> Self {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(5)))
+            .build()
+            .into();
+        EtcdIdAllocator {
+            agent,
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            prefix: prefix.to_string(),
+        }
+    }
+
+    /// Call a method of the gateway and returns its response.
+    fn call(
+        &self,
+        method: &str,
+        body: serde_json::Value,
+    ) -> Result<serde_json::Value, SpaceflakeError> {
+        self.agent
+            .post(&format!("{}/v3/{}", self.endpoint, method))
+            .send_json(&body)
+            .and_then(|mut response| response.body_mut().read_json())
+            .map_err(|error| SpaceflakeError::Source(format!("etcd: {}", error)))
+    }
+
+    /// Returns the base64 encoded key of the lease of a node and worker ID pair.
+    fn key(&self, node_id: u64, worker_id: u64) -> String {
+        use base64::Engine;
+
+        base64::engine::general_purpose::STANDARD
+            .encode(format!("{}{}-{}", self.prefix, node_id, worker_id))
+    }
+}
+
+/// Read a 64-bit integer of an etcd response, which the gateway encodes as a string.
+#[cfg(feature = "etcd")]
+fn etcd_integer(value: &serde_json::Value) -> Option<u64> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|value| value.parse().ok()))
+}
+
+/// The ID allocator implementation of an etcd ID allocator.
+#[cfg(feature = "etcd")]
+impl IdAllocator for EtcdIdAllocator {
+    fn acquire(&self, ttl: Duration) -> Result<IdLease, SpaceflakeError> {
+        let granted = self.call(
+            "lease/grant",
+            serde_json::json!({ "TTL": ttl.as_secs().max(1) }),
+        )?;
+        let lease_id = etcd_integer(&granted["ID"])
+            .ok_or_else(|| SpaceflakeError::Source(String::from("etcd: No lease was granted")))?;
+        for node_id in 0..=MAX_5_BITS {
+            for worker_id in 0..=MAX_5_BITS {
+                let key = self.key(node_id, worker_id);
+                let transaction = self.call(
+                    "kv/txn",
+                    serde_json::json!({
+                        "compare": [{
+                            "key": key,
+                            "result": "EQUAL",
+                            "target": "CREATE",
+                            "create_revision": "0",
+                        }],
+                        "success": [{
+                            "request_put": { "key": key, "value": "", "lease": lease_id.to_string() },
+                        }],
+                    }),
+                )?;
+                if transaction["succeeded"].as_bool() == Some(true) {
+                    return Ok(IdLease {
+                        node_id,
+                        worker_id,
+                        expires_at: current_time() + ttl.as_millis() as u64,
+                        token: lease_id,
+                    });
+                }
+            }
+        }
+        self.call(
+            "lease/revoke",
+            serde_json::json!({ "ID": lease_id.to_string() }),
+        )?;
+        Err(exhausted_leases())
+    }
+
+    fn renew(&self, lease: &IdLease, _ttl: Duration) -> Result<IdLease, SpaceflakeError> {
+        let kept = self.call(
+            "lease/keepalive",
+            serde_json::json!({ "ID": lease.token.to_string() }),
+        )?;
+        match etcd_integer(&kept["result"]["TTL"]) {
+            Some(granted) if granted > 0 => Ok(IdLease {
+                expires_at: current_time() + granted * 1000,
+                ..lease.clone()
+            }),
+            _ => Err(lost_lease(lease)),
+        }
+    }
+
+    fn release(&self, lease: IdLease) -> Result<(), SpaceflakeError> {
+        self.call(
+            "lease/revoke",
+            serde_json::json!({ "ID": lease.token.to_string() }),
+        )?;
+        Ok(())
+    }
+}
@@ -1,31 +1,242 @@
 #![allow(clippy::needless_doctest_main)]
 
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::ops::{Range, RangeInclusive};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError, RwLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::{fmt, thread};
 
 use rand::Rng;
 
+#[cfg(feature = "actix")]
+pub use actix::{SpaceflakeRequestId, SpaceflakeRequestIdMiddleware};
+#[cfg(feature = "async")]
+pub use asynchronous::{bulk_generate_async, SpaceflakeStream};
+pub use audit::{AuditRecord, AuditSink, AuditedGenerator, FileAuditSink};
+pub use base62::{decode_base62, encode_base62};
+pub use clock::{
+    CachedClock, Clock, CommitTimestamp, LeapSmear, MockClock, MonotonicClock, SystemClock,
+    TickerClock,
+};
+pub use compat::{
+    instagram_worker, parse_discord, parse_instagram, parse_sonyflake, parse_twitter,
+    sonyflake_worker, twitter_worker, DiscordSnowflake, InstagramId,
+};
+pub use conformity::{audit_ids, AuditExpectations, AuditReport};
+#[cfg(any(feature = "actix", feature = "axum"))]
+pub use extract::{SpaceflakePath, SpaceflakeRejection};
+pub use failover::FailoverGenerator;
+pub use generator::GeneratorService;
+pub use global::{global, init};
+pub use identity::{parse_statefulset_ordinal, statefulset_ordinal, NodeIdStrategy};
+pub use layout::{BitLayout, PartOrder, TimeUnit};
+#[cfg(feature = "etcd")]
+pub use lease::EtcdIdAllocator;
+#[cfg(feature = "redis")]
+pub use lease::RedisIdAllocator;
+pub use lease::{FileIdAllocator, IdAllocator, IdLease};
+pub use machine::{
+    generate_on_machine, machine_worker, parse_machine_id, split_machine_id, MAX_MACHINE_ID,
+};
+#[cfg(feature = "mmap")]
+pub use mmap::bulk_generate_to_mmap;
+pub use persistence::{FileStatePersistence, StatePersistence, PERSISTENCE_WINDOW_MS};
+pub use pool::IdPool;
+pub use range::SpaceflakeRange;
+pub use region::{
+    generate_in_region, region_code, region_name, region_node_id, register_regions, REGION_BITS,
+};
+pub use remote::{RemoteGenerator, RemoteSource};
+#[cfg(feature = "tower")]
+pub use service::{
+    BulkGenerateRequest, GenerateFuture, GenerateRequest, SetSpaceflakeRequestId,
+    SetSpaceflakeRequestIdLayer,
+};
+pub use shard::{ShardMap, ShardMapVersion};
+
+#[cfg(feature = "actix")]
+mod actix;
+#[cfg(feature = "async")]
+mod asynchronous;
+mod audit;
+#[cfg(feature = "avro")]
+pub mod avro;
+mod base62;
+mod bloom;
+#[cfg(feature = "bytes")]
+mod buf;
+mod clock;
+mod compat;
+mod conformity;
+#[cfg(any(feature = "chrono", feature = "time"))]
+mod datetime;
+#[cfg(feature = "diesel")]
+mod diesel_types;
+#[cfg(any(feature = "actix", feature = "axum"))]
+mod extract;
+mod failover;
+mod generator;
+mod global;
+#[cfg(any(feature = "async-graphql", feature = "juniper"))]
+mod graphql;
+mod identity;
+mod layout;
+mod lease;
+mod machine;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(feature = "utoipa")]
+mod openapi;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod persistence;
+mod pool;
+mod range;
+mod region;
+mod remote;
+#[cfg(feature = "sea-orm")]
+mod sea_orm_types;
+#[cfg(feature = "serde")]
+pub mod serde;
+#[cfg(feature = "tower")]
+mod service;
+mod shard;
+pub mod sql;
+#[cfg(feature = "sqlx")]
+mod sqlx_types;
+#[cfg(feature = "stress")]
+pub mod stress;
+pub mod synth;
+#[cfg(feature = "chrono-tz")]
+mod tz;
+#[cfg(feature = "ulid")]
+mod ulids;
+#[cfg(feature = "uuid")]
+mod uuids;
+
 /// The default epoch used **with milliseconds**, which is the 1st of January 2015 at 12:00:00 AM GMT.
 pub const EPOCH: u64 = 1420070400000;
 
+/// The epoch of Twitter's Snowflakes **with milliseconds**, which is the 4th of November 2010 at 01:42:54.657 AM GMT.
+pub const EPOCH_TWITTER: u64 = 1288834974657;
+
+/// The epoch of Discord's Snowflakes **with milliseconds**, which is the 1st of January 2015 at 00:00:00 AM GMT.
+pub const EPOCH_DISCORD: u64 = 1420070400000;
+
+/// The epoch of Sonyflakes **with milliseconds**, which is the 1st of September 2014 at 00:00:00 AM GMT.
+pub const EPOCH_SONYFLAKE: u64 = 1409529600000;
+
+/// The epoch of Instagram's sharded IDs **with milliseconds**, which is the 24th of August 2011 at 09:07:01.721 PM GMT.
+pub const EPOCH_INSTAGRAM: u64 = 1314220021721;
+
+/// The name of the header the request ID middlewares inject the Spaceflake of each request in.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// The amount of 100 nanoseconds between the Gregorian epoch of UUIDs, the 15th of October 1582, and the Unix epoch.
+const GREGORIAN_OFFSET: u64 = 0x01B21DD213814000;
+
 /// The maximum number that can be set with 5 bits.
 const MAX_5_BITS: u64 = 31;
 
 /// The maximum number that can be set with 12 bits.
 const MAX_12_BITS: u64 = 4095;
 
+/// The amount of low bits of the stamp of a worker holding the amount of Spaceflakes generated in a millisecond, which
+/// must be greater than the maximum amount of bits of the sequence of a [`BitLayout`].
+const STAMP_COUNT_BITS: u64 = 22;
+
+/// The mask of the low bits of the stamp of a worker holding the amount of Spaceflakes generated in a millisecond.
+const STAMP_COUNT_MASK: u64 = (1 << STAMP_COUNT_BITS) - 1;
+
+/// The maximum amount of bits of the sequence that can be reserved for the version tag.
+pub const MAX_VERSION_BITS: u64 = 2;
+
 /// A Spaceflake is the internal name for a Snowflake ID.
 ///
 /// Apart from being a crystal of snow, a snowflake is a form of unique identifier which is being used in distributed computing. It has specific parts and is 64 bits long in binary.
 /// ![A Spaceflake structure](https://raw.githubusercontent.com/kkrypt0nn/spaceflake.rs/main/assets/spaceflake_structure.png)
-#[derive(Debug)]
+///
+/// Spaceflakes are compared, hashed and ordered by their ID only, which is chronological for Spaceflakes generated with
+/// the same base epoch.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow)
+)]
+#[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
 pub struct Spaceflake {
+    /// The decimal representation of the Spaceflake.
+    pub id: u64,
     /// The  base epoch that was used to generate the Spaceflake, default is [`EPOCH`].
     base_epoch: u64,
+    /// The amount of bits of the sequence that are reserved for the version tag, default is `0`.
+    version_bits: u64,
+    /// The layout of the bits of the Spaceflake, default is [`BitLayout::SPACEFLAKE`].
+    layout: BitLayout,
+}
+
+/// The parts of a Spaceflake, see [`Spaceflake::parts`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct DecomposedSpaceflake {
     /// The decimal representation of the Spaceflake.
     pub id: u64,
+    /// The time, in milliseconds since the Unix epoch, at which the Spaceflake was generated.
+    pub time: u64,
+    /// The node ID the Spaceflake was generated on.
+    pub node_id: u64,
+    /// The worker ID the Spaceflake was generated on.
+    pub worker_id: u64,
+    /// The sequence of the Spaceflake.
+    pub sequence: u64,
+    /// The version tag of the Spaceflake, `0` if no bits are reserved for it.
+    pub version: u64,
+}
+
+/// Implements a formatting trait of a Spaceflake by formatting its ID, honoring the flags, such as `{:016x}`.
+macro_rules! impl_fmt {
+    ($($trait:ident),*) => {
+        $(
+            impl fmt::$trait for Spaceflake {
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    fmt::$trait::fmt(&self.id, f)
+                }
+            }
+        )*
+    };
+}
+
+impl_fmt!(LowerHex, UpperHex, Binary, Octal);
+
+/// The equality implementation of a Spaceflake, which compares their IDs only.
+impl PartialEq for Spaceflake {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Spaceflake {}
+
+/// The hash implementation of a Spaceflake, which hashes its ID only.
+impl std::hash::Hash for Spaceflake {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+/// The ordering implementation of a Spaceflake, which orders them by their IDs only.
+impl PartialOrd for Spaceflake {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Spaceflake {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.id.cmp(&other.id)
+    }
 }
 
 /// The display implementation of a Spaceflake. Will just return its ID.
@@ -35,30 +246,161 @@ impl fmt::Display for Spaceflake {
     }
 }
 
+/// The parsing of a Spaceflake from its ID as a decimal string, generated with the default [`EPOCH`].
+impl std::str::FromStr for Spaceflake {
+    type Err = SpaceflakeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse::<u64>() {
+            Ok(id) => Spaceflake::try_from(id),
+            Err(_) => Err(SpaceflakeError::InvalidEncoding(format!(
+                "Invalid Spaceflake ID: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// The conversion of an ID into a Spaceflake generated with the default [`EPOCH`].
+///
+/// Returns an error if the most significant bit, which is never set on Spaceflakes, is set.
+impl TryFrom<u64> for Spaceflake {
+    type Error = SpaceflakeError;
+
+    fn try_from(id: u64) -> Result<Self, Self::Error> {
+        if id >> 63 != 0 {
+            return Err(SpaceflakeError::InvalidEncoding(format!(
+                "Invalid Spaceflake ID: {}",
+                id
+            )));
+        }
+        Ok(Spaceflake::new(id, EPOCH))
+    }
+}
+
 /// The default implementation of a Spaceflake.
 impl Spaceflake {
     fn new(id: u64, base_epoch: u64) -> Self {
-        Spaceflake { base_epoch, id }
+        Spaceflake {
+            base_epoch,
+            version_bits: 0,
+            layout: BitLayout::SPACEFLAKE,
+            id,
+        }
+    }
+
+    /// Returns the Spaceflake with its version tag read from the given amount of high bits of the sequence, up to
+    /// [`MAX_VERSION_BITS`], for IDs generated with a version tag such as IDs parsed from a string or read from a
+    /// database.
+    pub fn with_version_bits(mut self, version_bits: u64) -> Self {
+        self.version_bits = version_bits.min(MAX_VERSION_BITS);
+        self
+    }
+
+    /// Returns the Spaceflake with its parts read with the given bit layout, for IDs generated with that layout.
+    pub fn with_layout(mut self, layout: BitLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Returns the layout of the bits of the Spaceflake.
+    pub fn layout(&self) -> BitLayout {
+        self.layout
+    }
+
+    /// Create a Spaceflake from its ID, for the given base epoch, such as an ID read from a database.
+    pub fn from_id(id: u64, base_epoch: u64) -> Self {
+        Spaceflake::new(id, base_epoch)
+    }
+
+    /// Returns the smallest Spaceflake of the given timestamp, in milliseconds since the Unix epoch, with all the bits
+    /// other than the time set to 0, for example as the lower bound of a query over the time of Spaceflake keys.
+    ///
+    /// Returns an error if the timestamp is before the base epoch or does not fit in the bits of the time.
+    pub fn first_at(timestamp: u64, base_epoch: u64) -> Result<Self, SpaceflakeError> {
+        Spaceflake::at_timestamp(timestamp, base_epoch, false)
+    }
+
+    /// Returns the largest Spaceflake of the given timestamp, in milliseconds since the Unix epoch, with all the bits
+    /// other than the time set to 1, for example as the upper bound of a query over the time of Spaceflake keys.
+    ///
+    /// Returns an error if the timestamp is before the base epoch or does not fit in the bits of the time.
+    pub fn last_at(timestamp: u64, base_epoch: u64) -> Result<Self, SpaceflakeError> {
+        Spaceflake::at_timestamp(timestamp, base_epoch, true)
+    }
+
+    /// Returns the inclusive bounds of the IDs of the Spaceflakes generated in the range of timestamps, in milliseconds
+    /// since the Unix epoch, for example to find all rows created between two times with
+    /// `WHERE id BETWEEN start AND end`, see [`SpaceflakeRange`].
+    ///
+    /// Returns an error if the range is empty, or if a timestamp is before the base epoch or does not fit in the bits
+    /// of the time.
+    pub fn range_for(
+        range: Range<u64>,
+        base_epoch: u64,
+    ) -> Result<RangeInclusive<u64>, SpaceflakeError> {
+        SpaceflakeRange::new(range, base_epoch).map(Into::into)
+    }
+
+    /// Returns the Spaceflake of the given timestamp with all the bits other than the time set to 0, or to 1.
+    fn at_timestamp(
+        timestamp: u64,
+        base_epoch: u64,
+        filled: bool,
+    ) -> Result<Self, SpaceflakeError> {
+        let milliseconds = timestamp
+            .checked_sub(base_epoch)
+            .ok_or(SpaceflakeError::EpochAfterGenerationTime)?;
+        let low_bits = if filled { u64::MAX } else { 0 };
+        Spaceflake::new(low_bits, base_epoch).with_milliseconds(Some(milliseconds))
     }
 
     /// Returns the time at which the Spaceflake has been generated.
+    #[cfg_attr(all(feature = "no-panic", not(debug_assertions)), no_panic::no_panic)]
     pub fn time(&self) -> u64 {
-        (self.id >> 22) + self.base_epoch
+        self.layout.parse_time(self.id, self.base_epoch)
+    }
+
+    /// Returns the time at which the Spaceflake has been generated as a system time.
+    pub fn system_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.time())
+    }
+
+    /// Returns how long ago the Spaceflake has been generated, or zero if it has been generated in the future.
+    pub fn age(&self) -> Duration {
+        Duration::from_millis(current_time().saturating_sub(self.time()))
+    }
+
+    /// Returns whether the Spaceflake has been generated longer ago than the duration, for example to expire it.
+    pub fn is_older_than(&self, duration: Duration) -> bool {
+        self.age() > duration
     }
 
     /// Returns the node ID of the Spaceflake.
+    #[cfg_attr(all(feature = "no-panic", not(debug_assertions)), no_panic::no_panic)]
     pub fn node_id(&self) -> u64 {
-        (self.id & 0x3E0000) >> 17
+        self.layout.parse_node_id(self.id)
     }
 
     /// Returns the worker ID of the Spaceflake.
+    #[cfg_attr(all(feature = "no-panic", not(debug_assertions)), no_panic::no_panic)]
     pub fn worker_id(&self) -> u64 {
-        (self.id & 0x1F000) >> 12
+        self.layout.parse_worker_id(self.id)
     }
 
-    /// Returns the sequence of the Spaceflake.
+    /// Returns the sequence of the Spaceflake, without the version tag.
+    #[cfg_attr(all(feature = "no-panic", not(debug_assertions)), no_panic::no_panic)]
     pub fn sequence(&self) -> u64 {
-        self.id & 0xFFF
+        self.layout.parse_sequence(self.id) & (self.layout.max_sequence() >> self.version_bits)
+    }
+
+    /// Returns the version tag of the Spaceflake, which is always `0` if no bits were reserved for it.
+    #[cfg_attr(all(feature = "no-panic", not(debug_assertions)), no_panic::no_panic)]
+    pub fn version(&self) -> u64 {
+        self.layout
+            .parse_sequence(self.id)
+            .checked_shr(self.layout.sequence_bits.saturating_sub(self.version_bits) as u32)
+            .unwrap_or(0)
     }
 
     /// Returns the ID of the Spaceflake as a string.
@@ -66,6 +408,68 @@ impl Spaceflake {
         self.to_string()
     }
 
+    /// Returns the ID of the Spaceflake as a signed integer, such as a SQL `BIGINT`, which is never negative as the most
+    /// significant bit of Spaceflakes is never set.
+    pub fn as_i64(&self) -> i64 {
+        self.id as i64
+    }
+
+    /// Create a Spaceflake from a signed integer returned by [`Spaceflake::as_i64`], such as a SQL `BIGINT`, for the
+    /// given base epoch.
+    ///
+    /// Returns an error if the integer is negative.
+    pub fn try_from_i64(id: i64, base_epoch: u64) -> Result<Self, SpaceflakeError> {
+        u64::try_from(id)
+            .map(|id| Spaceflake::new(id, base_epoch))
+            .map_err(|_| SpaceflakeError::InvalidEncoding(format!("Invalid Spaceflake ID: {}", id)))
+    }
+
+    /// Returns the ID of the Spaceflake encoded in base62, shorter than its decimal string, see [`encode_base62`].
+    pub fn to_base62(&self) -> String {
+        encode_base62(self.id)
+    }
+
+    /// Create a Spaceflake from a string returned by [`Spaceflake::to_base62`], for the given base epoch.
+    ///
+    /// Returns an error if the string is not a valid base62 ID.
+    pub fn from_base62(encoded: &str, base_epoch: u64) -> Result<Self, SpaceflakeError> {
+        Ok(Spaceflake::new(decode_base62(encoded)?, base_epoch))
+    }
+
+    /// Returns the ID of the Spaceflake as a string, suffixed with a Luhn check digit.
+    ///
+    /// This is meant for IDs entered by humans, so typos can be detected with [`Spaceflake::from_checked_string`].
+    pub fn to_checked_string(&self) -> String {
+        let id = self.to_string();
+        format!("{}{}", id, luhn_check_digit(&id))
+    }
+
+    /// Create a Spaceflake from a string returned by [`Spaceflake::to_checked_string`], for the given base epoch.
+    ///
+    /// Returns an error if the string is not a number or if the check digit does not match.
+    pub fn from_checked_string(checked: &str, base_epoch: u64) -> Result<Self, SpaceflakeError> {
+        if checked.len() < 2 || !checked.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(SpaceflakeError::InvalidEncoding(format!(
+                "Invalid checked Spaceflake ID: {}",
+                checked
+            )));
+        }
+        let (id, check_digit) = checked.split_at(checked.len() - 1);
+        if luhn_check_digit(id).to_string() != check_digit {
+            return Err(SpaceflakeError::InvalidEncoding(format!(
+                "Invalid check digit for Spaceflake ID: {}",
+                checked
+            )));
+        }
+        match id.parse::<u64>() {
+            Ok(id) => Ok(Spaceflake::new(id, base_epoch)),
+            Err(_) => Err(SpaceflakeError::InvalidEncoding(format!(
+                "Invalid checked Spaceflake ID: {}",
+                checked
+            ))),
+        }
+    }
+
     /// Returns the ID in binary of the Spaceflake as a string.
     #[deprecated(since = "1.1.0", note = "Use `to_binary`")]
     pub fn binary_id(&self) -> String {
@@ -74,7 +478,203 @@ impl Spaceflake {
 
     /// Returns the ID in binary of the Spaceflake as a string.
     pub fn to_binary(&self) -> String {
-        pad_left(decimal_binary(self.id), 64)
+        format!("{:064b}", self)
+    }
+
+    /// Returns the ID of the Spaceflake as big-endian bytes, so that the byte order matches the numeric order.
+    #[cfg_attr(all(feature = "no-panic", not(debug_assertions)), no_panic::no_panic)]
+    pub fn to_bytes(&self) -> [u8; 8] {
+        self.id.to_be_bytes()
+    }
+
+    /// Create a Spaceflake from big-endian bytes, as returned by [`Spaceflake::to_bytes`], for the given base epoch.
+    pub fn from_bytes(bytes: [u8; 8], base_epoch: u64) -> Self {
+        Spaceflake::new(u64::from_be_bytes(bytes), base_epoch)
+    }
+
+    /// Returns the Kafka partition a record keyed by the Spaceflake lands on, for the given amount of partitions.
+    ///
+    /// This matches the default partitioner of the Java client with the key serialized as a `Long`, which is the
+    /// murmur2 hash of the big-endian bytes of the ID.
+    ///
+    /// Returns an error if the amount of partitions is `0`.
+    pub fn kafka_partition(&self, num_partitions: u32) -> Result<u32, SpaceflakeError> {
+        (murmur2(&self.to_bytes()) & 0x7FFFFFFF)
+            .checked_rem(num_partitions)
+            .ok_or_else(|| {
+                SpaceflakeError::InvalidArgument(String::from(
+                    "Number of partitions must be greater than 0",
+                ))
+            })
+    }
+
+    /// Returns the Redis Streams entry ID of the Spaceflake, formatted as `<ms>-<seq>`.
+    ///
+    /// The milliseconds are the time of the Spaceflake, and the sequence holds all the bits of its layout after the
+    /// time, so the entry IDs are ordered just like the Spaceflakes.
+    pub fn to_redis_stream_id(&self) -> String {
+        format!("{}-{}", self.time(), self.low_bits())
+    }
+
+    /// Create a Spaceflake from a Redis Streams entry ID returned by [`Spaceflake::to_redis_stream_id`], for the given
+    /// base epoch and bit layout.
+    ///
+    /// Returns an error if the entry ID does not fit in the layout for the base epoch.
+    pub fn from_redis_stream_id(
+        entry_id: &str,
+        base_epoch: u64,
+        layout: BitLayout,
+    ) -> Result<Self, SpaceflakeError> {
+        let invalid = || {
+            SpaceflakeError::InvalidEncoding(format!(
+                "Invalid Redis Streams entry ID: {}",
+                entry_id
+            ))
+        };
+        let (milliseconds, sequence) = entry_id.split_once('-').ok_or_else(invalid)?;
+        let time = milliseconds.parse::<u64>().map_err(|_| invalid())?;
+        let sequence = sequence.parse::<u64>().map_err(|_| invalid())?;
+        if sequence >> layout.time_shift() != 0 {
+            return Err(invalid());
+        }
+        Spaceflake::new(sequence, base_epoch)
+            .with_layout(layout)
+            .with_milliseconds(time.checked_sub(base_epoch))
+            .map_err(|_| invalid())
+    }
+
+    /// Returns the Spaceflake as a version 1 time-based UUID, also known as a Cassandra `timeuuid`.
+    ///
+    /// The time of the Spaceflake is the timestamp of the UUID, so the UUIDs are ordered by time just like the
+    /// Spaceflakes, and the bits of its layout after the time are stored in the low 32 bits of the node field of the
+    /// UUID, with the multicast bit set as it is not a MAC address.
+    ///
+    /// Returns an error if the layout has more than 32 bits after the time, or if the time does not fit in the
+    /// timestamp of a UUID.
+    pub fn to_timeuuid(&self) -> Result<[u8; 16], SpaceflakeError> {
+        if self.layout.time_shift() > 32 {
+            return Err(SpaceflakeError::InvalidArgument(String::from(
+                "Only layouts with up to 32 bits after the time fit in a timeuuid",
+            )));
+        }
+        let timestamp = self
+            .time()
+            .checked_mul(10_000)
+            .and_then(|timestamp| timestamp.checked_add(GREGORIAN_OFFSET))
+            .filter(|timestamp| timestamp >> 60 == 0)
+            .ok_or(SpaceflakeError::TimeOverflow)?;
+        let mut uuid = [0u8; 16];
+        uuid[0..4].copy_from_slice(&(timestamp as u32).to_be_bytes());
+        uuid[4..6].copy_from_slice(&((timestamp >> 32) as u16).to_be_bytes());
+        uuid[6..8].copy_from_slice(&((((timestamp >> 48) as u16) & 0x0FFF) | 0x1000).to_be_bytes());
+        uuid[8] = 0x80;
+        uuid[10] = 0x01;
+        uuid[12..16].copy_from_slice(&(self.low_bits() as u32).to_be_bytes());
+        Ok(uuid)
+    }
+
+    /// Returns the Spaceflake as a version 1 time-based UUID string, see [`Spaceflake::to_timeuuid`].
+    pub fn to_timeuuid_string(&self) -> Result<String, SpaceflakeError> {
+        let uuid = self.to_timeuuid()?;
+        let hex = uuid
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        Ok(format!(
+            "{}-{}-{}-{}-{}",
+            &hex[0..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..32]
+        ))
+    }
+
+    /// Create a Spaceflake from a version 1 time-based UUID returned by [`Spaceflake::to_timeuuid`], for the given base
+    /// epoch and bit layout.
+    pub fn from_timeuuid(
+        uuid: [u8; 16],
+        base_epoch: u64,
+        layout: BitLayout,
+    ) -> Result<Self, SpaceflakeError> {
+        if uuid[6] >> 4 != 1 || uuid[8] >> 6 != 0b10 {
+            return Err(SpaceflakeError::InvalidEncoding(String::from(
+                "Invalid timeuuid: not a version 1 UUID",
+            )));
+        }
+        let time_low = u32::from_be_bytes([uuid[0], uuid[1], uuid[2], uuid[3]]) as u64;
+        let time_mid = u16::from_be_bytes([uuid[4], uuid[5]]) as u64;
+        let time_high = (u16::from_be_bytes([uuid[6], uuid[7]]) & 0x0FFF) as u64;
+        let timestamp = (time_high << 48) | (time_mid << 32) | time_low;
+        if timestamp < GREGORIAN_OFFSET {
+            return Err(SpaceflakeError::InvalidEncoding(String::from(
+                "Invalid timeuuid: time is before the Unix epoch",
+            )));
+        }
+        let time = (timestamp - GREGORIAN_OFFSET) / 10_000;
+        let low_bits = u32::from_be_bytes([uuid[12], uuid[13], uuid[14], uuid[15]]) as u64;
+        Spaceflake::new(low_bits, base_epoch)
+            .with_layout(layout)
+            .with_milliseconds(time.checked_sub(base_epoch))
+            .map_err(|_| {
+                SpaceflakeError::InvalidEncoding(String::from(
+                    "Invalid timeuuid: time does not fit in the layout for the base epoch",
+                ))
+            })
+    }
+
+    /// Returns the bits of the Spaceflake after the time, which are its node ID, worker ID and sequence.
+    fn low_bits(&self) -> u64 {
+        self.id & ((1 << self.layout.time_shift()) - 1)
+    }
+
+    /// Returns a new Spaceflake with the timestamp shifted later by the duration, and the same node ID, worker ID and
+    /// sequence, for example to compute the Spaceflake an hour later as a cursor.
+    ///
+    /// Returns an error if the shifted timestamp does not fit in the bits of the time.
+    pub fn checked_add(&self, duration: Duration) -> Result<Spaceflake, SpaceflakeError> {
+        let milliseconds = u64::try_from(duration.as_millis())
+            .ok()
+            .and_then(|milliseconds| self.layout.parse_time(self.id, 0).checked_add(milliseconds));
+        self.with_milliseconds(milliseconds)
+    }
+
+    /// Returns a new Spaceflake with the timestamp shifted earlier by the duration, and the same node ID, worker ID and
+    /// sequence, for example to compute the expiry boundary of Spaceflakes.
+    ///
+    /// Returns an error if the shifted timestamp is before the base epoch.
+    pub fn checked_sub(&self, duration: Duration) -> Result<Spaceflake, SpaceflakeError> {
+        let milliseconds = u64::try_from(duration.as_millis())
+            .ok()
+            .and_then(|milliseconds| self.layout.parse_time(self.id, 0).checked_sub(milliseconds));
+        self.with_milliseconds(milliseconds)
+    }
+
+    /// Returns a new Spaceflake with the milliseconds since the base epoch replaced, if they fit in the bits of the
+    /// time.
+    fn with_milliseconds(&self, milliseconds: Option<u64>) -> Result<Spaceflake, SpaceflakeError> {
+        let shift = self.layout.time_shift();
+        let time =
+            milliseconds.map(|milliseconds| milliseconds / self.layout.time_unit.as_millis());
+        match time {
+            Some(time) if time <= self.layout.max_time() => Ok(Spaceflake::new(
+                (time << shift) | (self.id & ((1 << shift) - 1)),
+                self.base_epoch,
+            )
+            .with_version_bits(self.version_bits)
+            .with_layout(self.layout)),
+            _ => Err(SpaceflakeError::TimeOverflow),
+        }
+    }
+
+    /// Compare the Spaceflake with another one, see [`IdDiff`].
+    pub fn diff(&self, other: &Spaceflake) -> IdDiff {
+        IdDiff {
+            time_delta: other.time() as i64 - self.time() as i64,
+            node_differs: self.node_id() != other.node_id(),
+            worker_differs: self.worker_id() != other.worker_id(),
+            sequence_distance: other.sequence() as i64 - self.sequence() as i64,
+        }
     }
 
     /// Returns a hashmap of key-values with each part of the Spaceflake.
@@ -95,17 +695,40 @@ impl Spaceflake {
     ///     "time": 1698048745164,
     ///     "sequence": 2111,
     ///     "node_id": 0,
+    ///     "version": 0,
     ///     "worker_id": 0,
     /// }
     /// ```
+    ///
+    /// Layouts without worker bits, such as [`BitLayout::twitter`], also hold the `machine_id`.
+    ///
+    /// Prefer [`Spaceflake::parts`], which does not allocate.
     pub fn decompose(&self) -> HashMap<String, u64> {
-        HashMap::<String, u64>::from([
+        let mut parts = HashMap::<String, u64>::from([
             ("id".to_string(), self.id),
             ("node_id".to_string(), self.node_id()),
             ("sequence".to_string(), self.sequence()),
             ("time".to_string(), self.time()),
+            ("version".to_string(), self.version()),
             ("worker_id".to_string(), self.worker_id()),
-        ])
+        ]);
+        if self.layout.worker_bits == 0 {
+            parts.insert("machine_id".to_string(), self.machine_id());
+        }
+        parts
+    }
+
+    /// Returns each part of the Spaceflake, without allocating.
+    #[cfg_attr(all(feature = "no-panic", not(debug_assertions)), no_panic::no_panic)]
+    pub fn parts(&self) -> DecomposedSpaceflake {
+        DecomposedSpaceflake {
+            id: self.id,
+            time: self.time(),
+            node_id: self.node_id(),
+            worker_id: self.worker_id(),
+            sequence: self.sequence(),
+            version: self.version(),
+        }
     }
 
     /// Returns a hashmap of key-values with each part of the Spaceflake as binary.
@@ -127,6 +750,7 @@ impl Spaceflake {
     ///     "id": "0001000000101110001100110011101110110011000000000000100000111111",
     ///     "worker_id": "00000",
     ///     "sequence": "100000111111",
+    ///     "version": "",
     /// }
     /// ```
     pub fn decompose_binary(&self) -> HashMap<String, String> {
@@ -134,24 +758,164 @@ impl Spaceflake {
             ("id".to_string(), pad_left(decimal_binary(self.id), 64)),
             (
                 "node_id".to_string(),
-                pad_left(decimal_binary(self.node_id()), 5),
+                pad_left(
+                    decimal_binary(self.node_id()),
+                    self.layout.node_bits as usize,
+                ),
             ),
             (
                 "sequence".to_string(),
-                pad_left(decimal_binary(self.sequence()), 12),
+                pad_left(
+                    decimal_binary(self.sequence()),
+                    self.layout.sequence_bits.saturating_sub(self.version_bits) as usize,
+                ),
             ),
             (
                 "time".to_string(),
-                pad_left(decimal_binary(self.time()), 41),
+                pad_left(decimal_binary(self.time()), self.layout.time_bits as usize),
+            ),
+            (
+                "version".to_string(),
+                pad_left(decimal_binary(self.version()), self.version_bits as usize),
             ),
             (
                 "worker_id".to_string(),
-                pad_left(decimal_binary(self.worker_id()), 5),
+                pad_left(
+                    decimal_binary(self.worker_id()),
+                    self.layout.worker_bits as usize,
+                ),
             ),
         ])
     }
 }
 
+/// The differences between two Spaceflakes, as returned by [`Spaceflake::diff`].
+///
+/// The deltas are computed from the first Spaceflake to the second one, so they are positive if the second one is
+/// greater.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdDiff {
+    /// The difference between the times of the Spaceflakes, in milliseconds.
+    pub time_delta: i64,
+    /// Whether the Spaceflakes were generated on different nodes.
+    pub node_differs: bool,
+    /// Whether the Spaceflakes were generated on different workers.
+    pub worker_differs: bool,
+    /// The difference between the sequences of the Spaceflakes.
+    pub sequence_distance: i64,
+}
+
+/// The display implementation of an ID diff. Will explain the differences in a sentence.
+impl fmt::Display for IdDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.time_delta {
+            0 => write!(f, "Generated in the same millisecond")?,
+            delta if delta > 0 => write!(f, "Generated {}ms later", delta)?,
+            delta => write!(f, "Generated {}ms earlier", -delta)?,
+        }
+        match (self.node_differs, self.worker_differs) {
+            (false, false) => write!(f, ", on the same node and worker")?,
+            (false, true) => write!(f, ", on the same node but another worker")?,
+            (true, _) => write!(f, ", on another node")?,
+        }
+        write!(
+            f,
+            ", with a sequence distance of {}",
+            self.sequence_distance
+        )
+    }
+}
+
+/// A generator of Spaceflakes, so the source of the Spaceflakes can be swapped, for example with a
+/// [`RemoteGenerator`].
+pub trait IdGenerator {
+    /// Generate a new Spaceflake.
+    fn generate(&self) -> Result<Spaceflake, SpaceflakeError>;
+}
+
+/// The generator implementation of a worker.
+impl IdGenerator for Worker {
+    fn generate(&self) -> Result<Spaceflake, SpaceflakeError> {
+        Worker::generate(self)
+    }
+}
+
+/// A handler of the lifecycle events of the generators, registered with [`Node::set_event_handler`] or
+/// [`Worker::set_event_handler`], to wire them into alerting without polling.
+///
+/// Every event does nothing by default, so only the relevant ones need to be implemented.
+pub trait EventHandler: Send + Sync {
+    /// Called when a worker is created on a node.
+    fn on_worker_created(&self, _worker: &Worker) {}
+
+    /// Called when a worker is removed from a node.
+    fn on_worker_retired(&self, _worker: &Worker) {}
+
+    /// Called when the clock of a worker went backwards, by the given amount of milliseconds.
+    ///
+    /// What happens next depends on the [`DriftPolicy`] of the worker.
+    fn on_drift(&self, _worker: &Worker, _drift: u64) {}
+
+    /// Called when a worker exhausted the sequence of a millisecond.
+    fn on_sequence_exhausted(&self, _worker: &Worker) {}
+}
+
+/// The event handler registered on a node or a worker, if any.
+#[derive(Clone, Default)]
+struct Events(Option<Arc<dyn EventHandler>>);
+
+impl Events {
+    /// Call the event on the registered handler, if any.
+    fn emit(&self, event: impl FnOnce(&dyn EventHandler)) {
+        if let Some(handler) = &self.0 {
+            event(handler.as_ref());
+        }
+    }
+}
+
+impl fmt::Debug for Events {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(..)" } else { "None" })
+    }
+}
+
+/// The commit timestamp source registered on a worker, if any.
+#[derive(Clone, Default)]
+struct CommitSource(Option<Arc<dyn CommitTimestamp>>);
+
+impl fmt::Debug for CommitSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(..)" } else { "None" })
+    }
+}
+
+/// The clock registered on a worker, if any.
+#[derive(Clone, Default)]
+struct ClockSource(Option<Arc<dyn Clock>>);
+
+impl ClockSource {
+    /// Create the clock source of a static clock, such as the one of generator settings.
+    fn from_static(clock: Option<&'static dyn Clock>) -> Self {
+        ClockSource(clock.map(|clock| Arc::new(clock) as Arc<dyn Clock>))
+    }
+}
+
+impl fmt::Debug for ClockSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(..)" } else { "None" })
+    }
+}
+
+/// The state persistence registered on a worker, if any.
+#[derive(Clone, Default)]
+struct PersistenceSource(Option<Arc<dyn StatePersistence>>);
+
+impl fmt::Debug for PersistenceSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(..)" } else { "None" })
+    }
+}
+
 /// A node holds multiple [`Worker`] structures and has a, ideally, unique ID given.
 #[derive(Debug)]
 pub struct Node {
@@ -159,33 +923,79 @@ pub struct Node {
     pub id: u64,
     /// The list of workers the node holds, which will then be responsible to generate the Spaceflakes.
     workers: Vec<Worker>,
+    /// The handler of the lifecycle events, passed on to the workers created afterwards.
+    events: Events,
 }
 
 /// The default implementation of a node.
 impl Node {
     /// Create a new node for the given ID.
+    ///
+    /// Panics if the ID is too large.
+    #[deprecated(since = "1.2.0", note = "Use `Node::try_new`")]
     pub fn new(id: u64) -> Self {
         if id > MAX_5_BITS {
             panic!("Node ID must be less than {}", MAX_5_BITS);
         }
 
+        Node::unchecked(id)
+    }
+
+    /// Create a new node for the given ID.
+    ///
+    /// Returns an error if the ID does not fit in 5 bits.
+    pub fn try_new(id: u64) -> Result<Self, SpaceflakeError> {
+        if id > MAX_5_BITS {
+            return Err(SpaceflakeError::InvalidNodeId { max: MAX_5_BITS });
+        }
+
+        Ok(Node::unchecked(id))
+    }
+
+    /// Create a new node for the given ID, without checking it.
+    fn unchecked(id: u64) -> Self {
         Node {
             id,
             workers: Vec::<Worker>::new(),
+            events: Events::default(),
         }
     }
 
+    /// Register the handler of the lifecycle events of the node and of the workers it creates afterwards.
+    pub fn set_event_handler(&mut self, handler: Arc<dyn EventHandler>) {
+        self.events = Events(Some(handler));
+    }
+
     /// Create a new worker and push it to the list of workers of the node to generate Spaceflakes.
     pub fn new_worker(&mut self) -> Worker {
         let worker = Worker::new((self.workers.len() + 1) as u64, self.id);
+        self.push_worker(worker)
+    }
+
+    /// Create a new worker whose ID is derived from the current process, see [`process_worker_id`], and push it to the
+    /// list of workers of the node to generate Spaceflakes.
+    ///
+    /// This way, multiple processes on the same host get distinct worker IDs by default.
+    pub fn new_process_worker(&mut self) -> Worker {
+        let worker = Worker::new(process_worker_id(), self.id);
+        self.push_worker(worker)
+    }
+
+    /// Push a newly created worker to the list of workers, passing it the event handler of the node.
+    fn push_worker(&mut self, mut worker: Worker) -> Worker {
+        worker.events = self.events.clone();
         self.workers.push(worker.clone());
+        self.events
+            .emit(|handler| handler.on_worker_created(&worker));
         worker
     }
 
     /// Remove a worker given its ID from the list of workers.
     pub fn remove_worker(&mut self, id: u64) {
         if let Some(index) = self.workers.iter().position(|w| w.id == id) {
-            self.workers.remove(index);
+            let worker = self.workers.remove(index);
+            self.events
+                .emit(|handler| handler.on_worker_retired(&worker));
         }
     }
 
@@ -197,21 +1007,18 @@ impl Node {
     /// Generate an amount of Spaceflakes on the node.
     ///
     /// The workers will automatically scale, so there is no need to add new workers to the node.
-    pub fn bulk_generate(&self, amount: usize) -> Result<Vec<Spaceflake>, String> {
-        let mut node = Node::new(self.id);
+    pub fn bulk_generate(&self, amount: usize) -> Result<Vec<Spaceflake>, SpaceflakeError> {
+        let mut node = Node::unchecked(self.id);
         let mut worker = node.new_worker();
         let mut spaceflakes = Vec::<Spaceflake>::with_capacity(amount);
 
         for i in 1..=amount {
-            if i % ((MAX_12_BITS as usize * MAX_5_BITS as usize) + 1) == 0 {
-                thread::sleep(Duration::from_millis(1));
-                node.workers.clear();
+            if i > 1 && (i - 1) % MAX_12_BITS as usize == 0 {
+                if node.workers.len() == MAX_5_BITS as usize {
+                    thread::sleep(Duration::from_millis(1));
+                    node.workers.clear();
+                }
                 worker = node.new_worker();
-            } else if i % MAX_12_BITS as usize == 0
-                && i % (MAX_12_BITS as usize * MAX_5_BITS as usize) != 0
-            {
-                let new_worker = node.new_worker();
-                worker = new_worker;
             }
             match generate_on_node_and_worker(node.id, worker.clone(), None) {
                 Ok(spaceflake) => spaceflakes.push(spaceflake),
@@ -223,6 +1030,126 @@ impl Node {
     }
 }
 
+/// The hook overriding the worker ID derived from the current process, see [`set_process_worker_id_hook`].
+static PROCESS_WORKER_ID_HOOK: RwLock<Option<fn() -> u64>> = RwLock::new(None);
+
+/// Returns the worker ID derived from the current process, which is its ID modulo 32 unless a hook was set with
+/// [`set_process_worker_id_hook`].
+pub fn process_worker_id() -> u64 {
+    match *PROCESS_WORKER_ID_HOOK
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+    {
+        Some(hook) => hook(),
+        None => std::process::id() as u64 % (MAX_5_BITS + 1),
+    }
+}
+
+/// Override how the worker ID is derived from the current process, or restore the default with `None`.
+pub fn set_process_worker_id_hook(hook: Option<fn() -> u64>) {
+    *PROCESS_WORKER_ID_HOOK
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = hook;
+}
+
+/// A cluster holds multiple [`Node`] structures, usually all the nodes of an application.
+#[derive(Debug, Default)]
+pub struct Cluster {
+    /// The list of nodes the cluster holds.
+    nodes: Vec<Node>,
+}
+
+/// The default implementation of a cluster.
+impl Cluster {
+    /// Create a new empty cluster.
+    pub fn new() -> Self {
+        Cluster::default()
+    }
+
+    /// Create a new node for the given ID and push it to the list of nodes of the cluster.
+    ///
+    /// Returns an error if the ID does not fit in 5 bits.
+    pub fn new_node(&mut self, id: u64) -> Result<&mut Node, SpaceflakeError> {
+        let index = self.nodes.len();
+        self.nodes.push(Node::try_new(id)?);
+        Ok(&mut self.nodes[index])
+    }
+
+    /// Push an existing node to the list of nodes of the cluster.
+    pub fn add_node(&mut self, node: Node) {
+        self.nodes.push(node);
+    }
+
+    /// Returns the list of nodes the cluster is currently holding.
+    pub fn get_nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Export the topology of the cluster as JSON, with the nodes, their workers, how many Spaceflakes each worker has
+    /// issued and the state of each worker, see [`Worker::last_generated`].
+    ///
+    /// # Example
+    ///
+    /// ```json
+    /// {"nodes":[{"id":1,"workers":[{"id":1,"base_epoch":1420070400000,"issued":1337,"last_timestamp":1698048745164,"sequence":2}]}]}
+    /// ```
+    pub fn export_topology(&self) -> String {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let workers = node
+                    .workers
+                    .iter()
+                    .map(|worker| {
+                        let (last_timestamp, sequence) = worker.last_generated();
+                        format!(
+                            "{{\"id\":{},\"base_epoch\":{},\"issued\":{},\"last_timestamp\":{},\"sequence\":{}}}",
+                            worker.id,
+                            worker.base_epoch,
+                            worker.issued(),
+                            last_timestamp,
+                            sequence
+                        )
+                    })
+                    .collect::<Vec<String>>();
+                format!("{{\"id\":{},\"workers\":[{}]}}", node.id, workers.join(","))
+            })
+            .collect::<Vec<String>>();
+        format!("{{\"nodes\":[{}]}}", nodes.join(","))
+    }
+
+    /// Export the topology of the cluster as a Graphviz DOT graph, with the nodes, their workers, how many Spaceflakes
+    /// each worker has issued and the state of each worker, see [`Worker::last_generated`].
+    pub fn export_topology_dot(&self) -> String {
+        let mut dot = String::from("digraph spaceflake {\n");
+        for node in &self.nodes {
+            dot.push_str(&format!(
+                "    \"node_{}\" [label=\"Node {}\", shape=box];\n",
+                node.id, node.id
+            ));
+            for worker in &node.workers {
+                let (last_timestamp, sequence) = worker.last_generated();
+                dot.push_str(&format!(
+                    "    \"node_{}_worker_{}\" [label=\"Worker {}\\nissued: {}\\nlast timestamp: {}\\nsequence: {}\"];\n",
+                    node.id,
+                    worker.id,
+                    worker.id,
+                    worker.issued(),
+                    last_timestamp,
+                    sequence
+                ));
+                dot.push_str(&format!(
+                    "    \"node_{}\" -> \"node_{}_worker_{}\";\n",
+                    node.id, node.id, worker.id
+                ));
+            }
+        }
+        dot.push('}');
+        dot
+    }
+}
+
 /// A worker is the a structure that is responsible to generate the Spaceflake.
 #[derive(Debug, Clone)]
 pub struct Worker {
@@ -236,44 +1163,451 @@ pub struct Worker {
     ///
     /// If set to 0, it will be the incremented number.
     pub sequence: u64,
-    /// The incremented number of the worker, used for the sequence.
-    increment: Arc<Mutex<u64>>,
+    /// The amount of high bits of the sequence reserved for the version tag, up to [`MAX_VERSION_BITS`], default is `0`.
+    pub version_bits: u64,
+    /// The version tag written into every generated Spaceflake, must fit in the reserved version bits.
+    pub version: u64,
+    /// The granularity, in milliseconds, of the embedded timestamp, default is `1`.
+    ///
+    /// When greater than `1`, the timestamp is truncated to the granularity, rounded up to the unit of the layout, so
+    /// the exact creation time of the Spaceflake is not disclosed. The low bits of the time freed by the truncation
+    /// then widen the sequence: once the sequence of the first unit of the window is exhausted, the Spaceflakes are
+    /// generated in the next unit, and so on, so as many Spaceflakes can be generated per window as without the
+    /// truncation. The time of a Spaceflake is then only accurate to the granularity.
+    pub time_granularity: u64,
+    /// Whether the incremented number of the worker starts at a random offset every millisecond, default is `false`.
+    ///
+    /// This spreads the low bits of the Spaceflakes across the whole sequence space, which reduces the modulo bias when
+    /// they are later hashed or sharded by their low bits.
+    pub random_sequence_offset: bool,
+    /// Whether the worker reuses the last time read from the clock until the sequence of that millisecond is exhausted,
+    /// default is `false`.
+    ///
+    /// This avoids reading the clock for every Spaceflake in tight loops, at the cost of the embedded timestamp lagging
+    /// behind the actual time when Spaceflakes are generated slower than the sequence wraps. Once the sequence is
+    /// exhausted, the worker waits for the clock to reach the next millisecond.
+    pub cache_time: bool,
+    /// Whether generating a Spaceflake returns [`SpaceflakeError::SequenceExhausted`] once the sequence of the current
+    /// millisecond is exhausted, instead of waiting for the next millisecond, default is `false`.
+    pub fail_on_exhaustion: bool,
+    /// How the worker handles the clock going backwards, default is to wait for it to catch up if it went backwards by
+    /// up to 10 milliseconds, see [`DriftPolicy::WaitUpTo`].
+    pub drift_policy: DriftPolicy,
+    /// The leap smear compensated when reading the time, for clocks synchronized with smearing time servers, default is
+    /// `None`.
+    pub leap_smear: Option<LeapSmear>,
+    /// The layout of the bits of the generated Spaceflakes, default is [`BitLayout::SPACEFLAKE`].
+    ///
+    /// The node and worker IDs must fit in the bits of the layout.
+    pub layout: BitLayout,
+    /// The state of the worker, shared with its clones.
+    state: Arc<WorkerState>,
+    /// The handler of the lifecycle events of the worker.
+    events: Events,
+    /// The source of the commit timestamps the worker reads the time from, see [`Worker::set_commit_timestamp`].
+    commit_timestamp: CommitSource,
+    /// The clock the worker reads the time from, see [`Worker::set_clock`].
+    clock: ClockSource,
+    /// The persistence of the last time of the worker, see [`Worker::set_state_persistence`].
+    persistence: PersistenceSource,
+    /// The generation of the configuration the fields of the worker match, see [`Worker::reconfigure`].
+    generation: u64,
 }
 
 /// The default implementation of a worker.
 impl Worker {
+    /// Create a new worker, whose IDs are only checked against its layout when generating Spaceflakes.
     fn new(id: u64, node_id: u64) -> Self {
-        if id > MAX_12_BITS {
-            panic!("Worker ID must be less than {}", MAX_12_BITS);
-        }
-
         Worker {
             id,
             base_epoch: EPOCH,
             node_id,
             sequence: 0,
-            increment: Arc::new(Mutex::new(0)),
+            version_bits: 0,
+            version: 0,
+            time_granularity: 1,
+            random_sequence_offset: false,
+            cache_time: false,
+            fail_on_exhaustion: false,
+            drift_policy: DriftPolicy::default(),
+            leap_smear: None,
+            layout: BitLayout::SPACEFLAKE,
+            state: Arc::new(WorkerState::default()),
+            events: Events::default(),
+            commit_timestamp: CommitSource::default(),
+            clock: ClockSource::default(),
+            persistence: PersistenceSource::default(),
+            generation: 0,
         }
     }
 
-    /// Generate a new Spaceflake on this worker.
-    pub fn generate(&self) -> Result<Spaceflake, String> {
-        generate_on_node_and_worker(self.node_id, self.clone(), None)
-    }
-
-    /// Generate a new Spaceflake on this worker at a specific time.
-    pub fn generate_at(&self, at: u64) -> Result<Spaceflake, String> {
-        generate_on_node_and_worker(self.node_id, self.clone(), Option::from(at))
+    /// Create a new worker for the given node and worker IDs, without a [`Node`].
+    ///
+    /// Returns an error if an ID does not fit in 5 bits.
+    pub fn standalone(node_id: u64, worker_id: u64) -> Result<Self, SpaceflakeError> {
+        if node_id > MAX_5_BITS {
+            return Err(SpaceflakeError::InvalidNodeId { max: MAX_5_BITS });
+        }
+        if worker_id > MAX_5_BITS {
+            return Err(SpaceflakeError::InvalidWorkerId { max: MAX_5_BITS });
+        }
+        Ok(Worker::new(worker_id, node_id))
+    }
+
+    /// Create a new worker for the node and worker IDs of the generator settings, configured with the rest of them.
+    ///
+    /// Returns an error if the layout is invalid, or if the node or worker ID does not fit in its bits.
+    pub fn from_settings(settings: GeneratorSettings) -> Result<Self, SpaceflakeError> {
+        settings.layout.validate()?;
+        if settings.node_id > settings.layout.max_node_id() {
+            return Err(SpaceflakeError::InvalidNodeId {
+                max: settings.layout.max_node_id(),
+            });
+        }
+        if settings.worker_id > settings.layout.max_worker_id() {
+            return Err(SpaceflakeError::InvalidWorkerId {
+                max: settings.layout.max_worker_id(),
+            });
+        }
+
+        let mut worker = Worker::new(settings.worker_id, settings.node_id);
+        worker.base_epoch = settings.base_epoch;
+        worker.sequence = settings.sequence;
+        worker.version_bits = settings.version_bits;
+        worker.version = settings.version;
+        worker.time_granularity = settings.time_granularity;
+        worker.layout = settings.layout;
+        worker.clock = ClockSource::from_static(settings.clock);
+        worker.drift_policy = settings.drift_policy;
+        Ok(worker)
+    }
+
+    /// Register the handler of the lifecycle events of the worker.
+    pub fn set_event_handler(&mut self, handler: Arc<dyn EventHandler>) {
+        self.events = Events(Some(handler));
+    }
+
+    /// Read the time from an external commit timestamp authority instead of the local clock, so the order of the
+    /// Spaceflakes matches the commit order of the transactions.
+    ///
+    /// The clock going backwards, and the sequence being exhausted, are then detected on the commit timestamps.
+    pub fn set_commit_timestamp(&mut self, source: Arc<dyn CommitTimestamp>) {
+        self.commit_timestamp = CommitSource(Some(source));
+    }
+
+    /// Read the time from the given clock instead of the system clock, for example a [`MockClock`] in tests.
+    ///
+    /// A commit timestamp source, if any, still takes precedence over the clock.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = ClockSource(Some(clock));
+    }
+
+    /// Restore the last time of the worker from the state persistence, and keep recording it there.
+    ///
+    /// To avoid a write for every millisecond, the recorded time is ahead of the last time issued by up to
+    /// [`PERSISTENCE_WINDOW_MS`]. Until the clock passes the restored time, the worker waits for it whatever its
+    /// [`DriftPolicy`], so no Spaceflake issued before the restart can be issued again, or returns
+    /// [`SpaceflakeError::ClockMovedBackwards`] if the clock is behind it by more than [`PERSISTENCE_WINDOW_MS`].
+    pub fn set_state_persistence(
+        &mut self,
+        persistence: Arc<dyn StatePersistence>,
+    ) -> Result<(), SpaceflakeError> {
+        if let Some(last_time) = persistence.load(self.node_id, self.id)? {
+            // The sequence of the restored time is marked as exhausted, as it may have been used before the restart.
+            self.state.stamp.fetch_max(
+                (last_time << STAMP_COUNT_BITS) | STAMP_COUNT_MASK,
+                Ordering::AcqRel,
+            );
+            self.state.persisted.fetch_max(last_time, Ordering::AcqRel);
+            self.state.restored.fetch_max(last_time, Ordering::AcqRel);
+        }
+        self.persistence = PersistenceSource(Some(persistence));
+        Ok(())
+    }
+
+    /// Returns the amount of Spaceflakes the worker, and its clones, have issued.
+    pub fn issued(&self) -> u64 {
+        self.state.issued.load(Ordering::Relaxed)
+    }
+
+    /// Returns the time, in milliseconds since the Unix epoch, at which the worker, or one of its clones, last
+    /// generated Spaceflakes, and the amount of Spaceflakes generated at that time, or `(0, 0)` if none were generated.
+    pub fn last_generated(&self) -> (u64, u64) {
+        let (last_timestamp, count) = self.state.stamp();
+        (last_timestamp, count.min(self.layout.max_sequence()))
+    }
+
+    /// Returns the current configuration of the worker, which is the one of its last reconfiguration, if any, even
+    /// when it was reconfigured through one of its clones.
+    pub fn config(&self) -> WorkerConfig {
+        let mut worker = self.clone();
+        match worker.sync_config(true) {
+            Ok(_) => worker.own_config(),
+            Err(_) => self.own_config(),
+        }
+    }
+
+    /// Returns the generation of the configuration of the worker, and its clones, which starts at `0` and is bumped on
+    /// every reconfiguration.
+    pub fn generation(&self) -> u64 {
+        self.state.generation.load(Ordering::SeqCst) / 2
+    }
+
+    /// Switch the worker, and all its clones, to a new configuration.
+    ///
+    /// Generating Spaceflakes is paused on the worker and its clones while the worker waits for the current millisecond
+    /// to pass, or the current window of the new time granularity, and the sequence of that millisecond is closed, so
+    /// Spaceflakes can never be generated in the same millisecond with both the old and the new configuration.
+    /// Spaceflakes being generated with the old configuration meanwhile are generated again with the new one. The
+    /// generation of the configuration is then bumped.
+    ///
+    /// Returns an error if the configuration is invalid, for example if its base epoch is after the time of the clock
+    /// of the worker, or if the node or worker ID does not fit in its layout.
+    pub fn reconfigure(&mut self, config: WorkerConfig) -> Result<(), SpaceflakeError> {
+        config.validate(self.now()?)?;
+        if self.node_id > config.layout.max_node_id() {
+            return Err(SpaceflakeError::InvalidNodeId {
+                max: config.layout.max_node_id(),
+            });
+        }
+        if self.id > config.layout.max_worker_id() {
+            return Err(SpaceflakeError::InvalidWorkerId {
+                max: config.layout.max_worker_id(),
+            });
+        }
+
+        let state = self.state.clone();
+        let mut shared = state.config.write().unwrap_or_else(PoisonError::into_inner);
+        // An odd generation pauses the generation of Spaceflakes until the reconfiguration is done.
+        state.generation.fetch_add(1, Ordering::SeqCst);
+        let mut next = self.clone();
+        next.apply_config(config);
+        if let Err(error) = self.fence(&next) {
+            state.generation.fetch_sub(1, Ordering::SeqCst);
+            return Err(error);
+        }
+        *shared = config;
+        self.apply_config(config);
+        self.generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        Ok(())
+    }
+
+    /// Wait for the current millisecond to pass, for the time truncated with the next configuration, then mark the
+    /// sequence of the last millisecond a Spaceflake could have been generated in as exhausted, so no Spaceflake can be
+    /// generated in it anymore.
+    fn fence(&self, next: &Worker) -> Result<(), SpaceflakeError> {
+        let fence = self.now()?;
+        while next.truncate_time(self.now()?) <= fence {
+            thread::sleep(Duration::from_micros(100));
+        }
+        // The last Spaceflake may be ahead of the clock, when the sequence of the next millisecond was borrowed.
+        let (last_timestamp, _) = self.state.stamp();
+        self.state.stamp.fetch_max(
+            (fence.max(last_timestamp) << STAMP_COUNT_BITS) | STAMP_COUNT_MASK,
+            Ordering::AcqRel,
+        );
+        Ok(())
+    }
+
+    /// Returns the configuration of the fields of the worker.
+    fn own_config(&self) -> WorkerConfig {
+        WorkerConfig {
+            base_epoch: self.base_epoch,
+            version_bits: self.version_bits,
+            version: self.version,
+            time_granularity: self.time_granularity,
+            layout: self.layout,
+            drift_policy: self.drift_policy,
+        }
+    }
+
+    /// Set the fields of the worker to the configuration.
+    fn apply_config(&mut self, config: WorkerConfig) {
+        self.base_epoch = config.base_epoch;
+        self.version_bits = config.version_bits;
+        self.version = config.version;
+        self.time_granularity = config.time_granularity;
+        self.layout = config.layout;
+        self.drift_policy = config.drift_policy;
+    }
+
+    /// Set the fields of the worker to the configuration shared with its clones if one of them was reconfigured since,
+    /// and returns the generation of the configuration.
+    ///
+    /// Waits for a reconfiguration in progress to be done, or returns [`SpaceflakeError::WouldBlock`] when not
+    /// blocking.
+    fn sync_config(&mut self, blocking: bool) -> Result<u64, SpaceflakeError> {
+        loop {
+            let generation = self.state.generation.load(Ordering::SeqCst);
+            if generation % 2 == 1 {
+                if !blocking {
+                    return Err(SpaceflakeError::WouldBlock { retry_after_ms: 1 });
+                }
+                thread::sleep(Duration::from_micros(100));
+                continue;
+            }
+            if generation != self.generation {
+                let config = *self
+                    .state
+                    .config
+                    .read()
+                    .unwrap_or_else(PoisonError::into_inner);
+                if self.state.generation.load(Ordering::SeqCst) != generation {
+                    continue;
+                }
+                self.apply_config(config);
+                self.generation = generation;
+            }
+            return Ok(generation);
+        }
+    }
+
+    /// Returns the current time, in milliseconds since the Unix epoch, from the commit timestamp source or the clock if
+    /// any and with the leap smear compensated if any.
+    fn now(&self) -> Result<u64, SpaceflakeError> {
+        let now = match (&self.commit_timestamp.0, &self.clock.0) {
+            (Some(source), _) => source.now()?,
+            (None, Some(clock)) => clock.now_ms(),
+            (None, None) => current_time(),
+        };
+        Ok(match &self.leap_smear {
+            Some(leap_smear) => leap_smear.to_utc(now),
+            None => now,
+        })
+    }
+
+    /// Returns how many Spaceflakes can be generated on this worker, and its clones, in the current millisecond, or the
+    /// current window of the time granularity, before the sequence is exhausted, so batches can be sized to avoid
+    /// waiting for the next millisecond.
+    pub fn available_without_wait(&self) -> usize {
+        let max_sequence = self.layout.max_sequence() >> self.version_bits.min(MAX_VERSION_BITS);
+        let clock_time = self.state.clock_time.load(Ordering::Acquire);
+        let now = if self.cache_time && clock_time != 0 {
+            clock_time
+        } else {
+            match self.now() {
+                Ok(now) => now,
+                Err(_) => return 0,
+            }
+        };
+        let (last_timestamp, count) = self.state.stamp();
+        self.available_in_window(last_timestamp, count, now, max_sequence) as usize
+    }
+
+    /// Returns how many Spaceflakes can be generated in the window of the time granularity of the given time, after the
+    /// last one generated at the given time, which may be any unit of the window, and count.
+    fn available_in_window(
+        &self,
+        last_timestamp: u64,
+        count: u64,
+        now: u64,
+        max_sequence: u64,
+    ) -> u64 {
+        let unit = self.layout.time_unit.as_millis();
+        let step = self.time_step();
+        let window = self.truncate_time(now);
+        let units = step / unit;
+        if last_timestamp < window || last_timestamp - window >= step {
+            return units.saturating_mul(max_sequence);
+        }
+        (units - (last_timestamp - window) / unit)
+            .saturating_mul(max_sequence)
+            .saturating_sub(count.min(max_sequence))
+    }
+
+    /// Returns the length, in milliseconds, of the windows the timestamps are truncated to, which is the time
+    /// granularity rounded up to the unit of the layout.
+    fn time_step(&self) -> u64 {
+        let unit = self.layout.time_unit.as_millis();
+        self.time_granularity
+            .max(1)
+            .div_ceil(unit)
+            .saturating_mul(unit)
+    }
+
+    /// Returns the time, in milliseconds since the Unix epoch, truncated to the window of the time granularity it
+    /// belongs to, so the Spaceflakes of a window do not disclose when they were generated in it.
+    fn truncate_time(&self, time: u64) -> u64 {
+        time - time.saturating_sub(self.base_epoch) % self.time_step()
+    }
+
+    /// Generate a new Spaceflake on this worker.
+    pub fn generate(&self) -> Result<Spaceflake, SpaceflakeError> {
+        generate_on_node_and_worker(self.node_id, self.clone(), None)
+    }
+
+    /// Generate a new Spaceflake on this worker without ever sleeping.
+    ///
+    /// Returns [`SpaceflakeError::WouldBlock`] when the worker would have to wait for the next millisecond, so callers
+    /// can implement their own backoff, for example in async executors.
+    pub fn try_generate(&self) -> Result<Spaceflake, SpaceflakeError> {
+        generate_on_node_and_worker_with(self.node_id, self.clone(), None, false)
+    }
+
+    /// Generate a new Spaceflake on this worker at a specific time.
+    pub fn generate_at(&self, at: u64) -> Result<Spaceflake, SpaceflakeError> {
+        generate_on_node_and_worker(self.node_id, self.clone(), Option::from(at))
+    }
+
+    /// Generate a new Spaceflake on this worker, retrying transient failures according to the retry policy.
+    ///
+    /// Transient failures are the ones caused by the clock, such as a clock regression or the sequence being exhausted.
+    /// Other failures, including a base epoch after the current time, are returned immediately, and the last failure
+    /// is returned once all the attempts are used.
+    pub fn generate_with_retry(&self, policy: RetryPolicy) -> Result<Spaceflake, SpaceflakeError> {
+        let mut backoff = policy.initial_backoff;
+        let mut attempt = 1;
+        loop {
+            match self.generate() {
+                Ok(spaceflake) => return Ok(spaceflake),
+                Err(error) if attempt < policy.max_attempts && error.is_transient() => {
+                    thread::sleep(backoff);
+                    backoff = backoff.saturating_mul(2).min(policy.max_backoff);
+                    attempt += 1;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Generate a new 64-bit span ID on this worker, compatible with the W3C trace context.
+    ///
+    /// The span ID is the big-endian Spaceflake, so it is never all zeroes and sorts by creation time.
+    pub fn generate_span_id(&self) -> Result<[u8; 8], SpaceflakeError> {
+        Ok(self.generate()?.to_bytes())
+    }
+
+    /// Generate a new 128-bit trace ID on this worker, compatible with the W3C trace context.
+    ///
+    /// The first 8 bytes are the big-endian Spaceflake, to keep trace IDs sortable, and the last 8 bytes are random.
+    pub fn generate_trace_id(&self) -> Result<[u8; 16], SpaceflakeError> {
+        let mut trace_id = [0u8; 16];
+        trace_id[..8].copy_from_slice(&self.generate()?.to_bytes());
+        rand::thread_rng().fill(&mut trace_id[8..]);
+        Ok(trace_id)
+    }
+
+    /// Lazily generate Spaceflakes on the worker, without ever ending.
+    ///
+    /// It will automatically sleep of a millisecond, only if needed, to prevent duplicated Spaceflakes to get generated.
+    /// Use [`Iterator::take`] to bound the amount of Spaceflakes.
+    pub fn iter(&self) -> impl Iterator<Item = Result<Spaceflake, SpaceflakeError>> + '_ {
+        std::iter::repeat_with(move || {
+            if self.available_without_wait() == 0 {
+                thread::sleep(Duration::from_millis(1));
+            }
+            self.generate()
+        })
     }
 
     /// Generate an amount of Spaceflakes on the worker.
     ///
     /// It will automatically sleep of a millisecond, only if needed, to prevent duplicated Spaceflakes to get generated.
-    pub fn bulk_generate(&self, amount: usize) -> Result<Vec<Spaceflake>, String> {
+    pub fn bulk_generate(&self, amount: usize) -> Result<Vec<Spaceflake>, SpaceflakeError> {
         let mut spaceflakes = Vec::<Spaceflake>::new();
 
-        for i in 1..=amount {
-            if i % (MAX_12_BITS as usize + 1) == 0 {
+        for _ in 0..amount {
+            if self.available_without_wait() == 0 {
                 thread::sleep(Duration::from_millis(1));
             }
             match generate_on_node_and_worker(self.node_id, self.clone(), None) {
@@ -286,15 +1620,511 @@ impl Worker {
 
         Ok(spaceflakes)
     }
+
+    /// Atomically reserve an amount of consecutive sequence slots on this worker, whose Spaceflakes are materialized
+    /// lazily with [`SequenceBlock::iter`], so no other Spaceflake generated on the worker, or its clones, can take
+    /// them in the meantime.
+    ///
+    /// Waits for the next millisecond when the sequence is exhausted, so a block can span several milliseconds.
+    pub fn reserve_block(&self, amount: usize) -> Result<SequenceBlock, SpaceflakeError> {
+        let mut worker = self.clone();
+        let mut runs = Vec::new();
+        let mut remaining = amount as u64;
+        while remaining > 0 {
+            let generation = worker.generation;
+            let reservation =
+                reserve_on_node_and_worker(self.node_id, &mut worker, None, true, remaining)?;
+            // The worker was reconfigured, so the slots reserved with the previous configuration are given up.
+            if worker.generation != generation {
+                runs.clear();
+                remaining = amount as u64;
+            }
+            remaining -= reservation.count;
+            runs.push(reservation);
+        }
+        Ok(SequenceBlock { worker, runs })
+    }
+
+    /// Generate an amount of Spaceflakes on this worker and append their IDs to the vector, for high-throughput batches.
+    ///
+    /// The time is read, and the sequence reserved, once for every run of Spaceflakes of the same millisecond rather
+    /// than once per Spaceflake, waiting for the next millisecond when the sequence is exhausted. If an error is
+    /// returned, the IDs generated before it are kept in the vector.
+    pub fn generate_many(&self, amount: usize, out: &mut Vec<u64>) -> Result<(), SpaceflakeError> {
+        out.reserve(amount);
+        let mut worker = self.clone();
+        let mut remaining = amount as u64;
+        while remaining > 0 {
+            let reservation =
+                reserve_on_node_and_worker(self.node_id, &mut worker, None, true, remaining)?;
+            for index in reservation.index..reservation.index + reservation.count {
+                out.push(compose_reserved(self.node_id, &worker, reservation.time, index)?.id);
+            }
+            remaining -= reservation.count;
+        }
+        Ok(())
+    }
 }
 
-/// Settings to bulk generate Spaceflakes easily.
+/// The default implementation of a standalone worker, with the node and worker IDs set to `0`.
+impl Default for Worker {
+    fn default() -> Self {
+        Worker::new(0, 0)
+    }
+}
+
+/// Stable numeric codes for the errors returned by the generator, so they can be translated consistently across
+/// versions by FFI layers and servers.
+///
+/// The codes must never be changed nor reused once released.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// An error that does not have a dedicated code.
+    Unknown = 0,
+    /// The node ID does not fit in its bits.
+    InvalidNodeId = 1,
+    /// The worker ID does not fit in its bits.
+    InvalidWorkerId = 2,
+    /// The sequence does not fit in its bits.
+    InvalidSequence = 3,
+    /// Too many bits are reserved for the version tag.
+    InvalidVersionBits = 4,
+    /// The version tag does not fit in the reserved version bits.
+    InvalidVersion = 5,
+    /// The time granularity is zero.
+    InvalidTimeGranularity = 6,
+    /// The base epoch is after the time the Spaceflake should be generated at.
+    EpochAfterGenerationTime = 7,
+    /// The base epoch is after the current time.
+    EpochInFuture = 8,
+    /// The time the Spaceflake should be generated at is after the current time.
+    GenerationTimeInFuture = 9,
+    /// The time the Spaceflake should be generated at does not fit in its bits.
+    TimeOverflow = 10,
+    /// The chunk size of a chunked bulk generation is zero.
+    InvalidChunkSize = 11,
+    /// The encoded Spaceflake could not be decoded.
+    InvalidEncoding = 12,
+    /// The time window is inverted or can not hold the requested amount of Spaceflakes.
+    InvalidTimeWindow = 13,
+    /// The clock moved backwards.
+    ClockMovedBackwards = 14,
+    /// The bit layout does not sum to 63 bits, or has no bit for the time or the sequence.
+    InvalidBitLayout = 15,
+    /// The sequence of the millisecond is exhausted.
+    SequenceExhausted = 16,
+    /// The generation would have to wait.
+    WouldBlock = 17,
+    /// An argument, or a configuration, is invalid.
+    InvalidArgument = 18,
+    /// An external source, such as a remote generator, a commit timestamp authority or a callback, failed.
+    Source = 19,
+    /// An I/O operation failed.
+    Io = 20,
+}
+
+/// The default implementation of an error code.
+impl ErrorCode {
+    /// All the error codes, in order.
+    const ALL: [ErrorCode; 21] = [
+        ErrorCode::Unknown,
+        ErrorCode::InvalidNodeId,
+        ErrorCode::InvalidWorkerId,
+        ErrorCode::InvalidSequence,
+        ErrorCode::InvalidVersionBits,
+        ErrorCode::InvalidVersion,
+        ErrorCode::InvalidTimeGranularity,
+        ErrorCode::EpochAfterGenerationTime,
+        ErrorCode::EpochInFuture,
+        ErrorCode::GenerationTimeInFuture,
+        ErrorCode::TimeOverflow,
+        ErrorCode::InvalidChunkSize,
+        ErrorCode::InvalidEncoding,
+        ErrorCode::InvalidTimeWindow,
+        ErrorCode::ClockMovedBackwards,
+        ErrorCode::InvalidBitLayout,
+        ErrorCode::SequenceExhausted,
+        ErrorCode::WouldBlock,
+        ErrorCode::InvalidArgument,
+        ErrorCode::Source,
+        ErrorCode::Io,
+    ];
+
+    /// Returns the numeric code of the error.
+    pub fn as_code(&self) -> u32 {
+        *self as u32
+    }
+
+    /// Returns the error for the given numeric code, if it exists.
+    pub fn from_code(code: u32) -> Option<ErrorCode> {
+        ErrorCode::ALL.get(code as usize).copied()
+    }
+}
+
+/// The errors returned by the generator and the other fallible functions of the crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpaceflakeError {
+    /// The node ID is not less than the maximum.
+    InvalidNodeId { max: u64 },
+    /// The worker ID is not less than the maximum.
+    InvalidWorkerId { max: u64 },
+    /// The sequence is not less than the maximum.
+    InvalidSequence { max: u64 },
+    /// More bits than the maximum are reserved for the version tag.
+    InvalidVersionBits { max: u64 },
+    /// The version tag is not less than the maximum allowed by the reserved version bits.
+    InvalidVersion { max: u64 },
+    /// The time granularity is zero.
+    InvalidTimeGranularity,
+    /// The base epoch is after the time the Spaceflake should be generated at.
+    EpochAfterGenerationTime,
+    /// The base epoch is after the current time.
+    EpochInFuture,
+    /// The time the Spaceflake should be generated at is after the current time.
+    GenerationTimeInFuture,
+    /// The clock moved backwards by the given amount of milliseconds.
+    ClockMovedBackwards { delta_ms: u64 },
+    /// The time of the Spaceflake does not fit in its bits.
+    TimeOverflow,
+    /// The bit layout does not sum to 63 bits, has no bit for the time or the sequence, or too many for the sequence.
+    InvalidBitLayout,
+    /// The sequence of the millisecond is exhausted, and the worker does not wait for the next one.
+    SequenceExhausted,
+    /// The generation would have to wait for the given amount of milliseconds, see [`Worker::try_generate`].
+    WouldBlock { retry_after_ms: u64 },
+    /// The chunk size of a chunked bulk generation is zero.
+    InvalidChunkSize,
+    /// The encoded Spaceflake could not be decoded.
+    InvalidEncoding(String),
+    /// The time window is inverted or can not hold the requested amount of Spaceflakes.
+    InvalidTimeWindow(String),
+    /// An argument, or a configuration, is invalid.
+    InvalidArgument(String),
+    /// An external source, such as a remote generator, a commit timestamp authority or a callback, failed.
+    Source(String),
+    /// An I/O operation failed.
+    Io(String),
+}
+
+/// The default implementation of a Spaceflake error.
+impl SpaceflakeError {
+    /// Returns the stable numeric code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            SpaceflakeError::InvalidNodeId { .. } => ErrorCode::InvalidNodeId,
+            SpaceflakeError::InvalidWorkerId { .. } => ErrorCode::InvalidWorkerId,
+            SpaceflakeError::InvalidSequence { .. } => ErrorCode::InvalidSequence,
+            SpaceflakeError::InvalidVersionBits { .. } => ErrorCode::InvalidVersionBits,
+            SpaceflakeError::InvalidVersion { .. } => ErrorCode::InvalidVersion,
+            SpaceflakeError::InvalidTimeGranularity => ErrorCode::InvalidTimeGranularity,
+            SpaceflakeError::EpochAfterGenerationTime => ErrorCode::EpochAfterGenerationTime,
+            SpaceflakeError::EpochInFuture => ErrorCode::EpochInFuture,
+            SpaceflakeError::GenerationTimeInFuture => ErrorCode::GenerationTimeInFuture,
+            SpaceflakeError::ClockMovedBackwards { .. } => ErrorCode::ClockMovedBackwards,
+            SpaceflakeError::TimeOverflow => ErrorCode::TimeOverflow,
+            SpaceflakeError::InvalidBitLayout => ErrorCode::InvalidBitLayout,
+            SpaceflakeError::SequenceExhausted => ErrorCode::SequenceExhausted,
+            SpaceflakeError::WouldBlock { .. } => ErrorCode::WouldBlock,
+            SpaceflakeError::InvalidChunkSize => ErrorCode::InvalidChunkSize,
+            SpaceflakeError::InvalidEncoding(_) => ErrorCode::InvalidEncoding,
+            SpaceflakeError::InvalidTimeWindow(_) => ErrorCode::InvalidTimeWindow,
+            SpaceflakeError::InvalidArgument(_) => ErrorCode::InvalidArgument,
+            SpaceflakeError::Source(_) => ErrorCode::Source,
+            SpaceflakeError::Io(_) => ErrorCode::Io,
+        }
+    }
+
+    /// Returns whether the error is caused by the clock and may disappear by retrying later.
+    ///
+    /// A base epoch after the current time is not transient, as it is a configuration error that retrying does not fix.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            SpaceflakeError::ClockMovedBackwards { .. }
+                | SpaceflakeError::SequenceExhausted
+                | SpaceflakeError::WouldBlock { .. }
+        )
+    }
+}
+
+/// The display implementation of a Spaceflake error.
+impl fmt::Display for SpaceflakeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpaceflakeError::InvalidNodeId { max } => write!(f, "Node ID must be less than {}", max),
+            SpaceflakeError::InvalidWorkerId { max } => {
+                write!(f, "Worker ID must be less than {}", max)
+            }
+            SpaceflakeError::InvalidSequence { max } => {
+                write!(f, "Sequence must be less than {}", max)
+            }
+            SpaceflakeError::InvalidVersionBits { max } => {
+                write!(f, "Version bits must be less than or equals to {}", max)
+            }
+            SpaceflakeError::InvalidVersion { max } => {
+                write!(f, "Version must be less than {}", max)
+            }
+            SpaceflakeError::InvalidTimeGranularity => {
+                write!(f, "Time granularity must be greater than 0")
+            }
+            SpaceflakeError::EpochAfterGenerationTime => write!(
+                f,
+                "Base epoch must be less than the time you want to generate the Spaceflake at"
+            ),
+            SpaceflakeError::EpochInFuture => write!(
+                f,
+                "Base epoch must be less than or equals to current epoch time"
+            ),
+            SpaceflakeError::GenerationTimeInFuture => write!(
+                f,
+                "The current time must be greater than the time you want to generate the Spaceflake at"
+            ),
+            SpaceflakeError::ClockMovedBackwards { delta_ms } => {
+                write!(f, "The clock moved backwards by {}ms", delta_ms)
+            }
+            SpaceflakeError::TimeOverflow => {
+                write!(f, "The time of the Spaceflake does not fit in its bits")
+            }
+            SpaceflakeError::InvalidBitLayout => write!(
+                f,
+                "Bit layout must sum to 63 bits, with at least 1 time bit and between 1 and 21 sequence bits"
+            ),
+            SpaceflakeError::SequenceExhausted => {
+                write!(f, "The sequence of the millisecond is exhausted")
+            }
+            SpaceflakeError::WouldBlock { retry_after_ms } => write!(
+                f,
+                "The generation would block, retry after {}ms",
+                retry_after_ms
+            ),
+            SpaceflakeError::InvalidChunkSize => write!(f, "Chunk size must be greater than 0"),
+            SpaceflakeError::InvalidEncoding(message)
+            | SpaceflakeError::InvalidTimeWindow(message)
+            | SpaceflakeError::InvalidArgument(message)
+            | SpaceflakeError::Source(message)
+            | SpaceflakeError::Io(message) => f.write_str(message),
+        }
+    }
+}
+
+/// The error implementation of a Spaceflake error.
+impl std::error::Error for SpaceflakeError {}
+
+/// The conversion of an I/O error into a Spaceflake error.
+impl From<std::io::Error> for SpaceflakeError {
+    fn from(error: std::io::Error) -> Self {
+        SpaceflakeError::Io(error.to_string())
+    }
+}
+
+/// The state of a worker, shared between its clones so they all generate from the same sequence and timestamp.
+///
+/// Generating Spaceflakes only reads its atomics, and its configuration once after every reconfiguration, so generating
+/// Spaceflakes from multiple threads does not block on a lock.
 #[derive(Debug)]
+struct WorkerState {
+    /// The time, in milliseconds, at which the last Spaceflake was generated, in the high bits, and the amount of
+    /// Spaceflakes generated in that millisecond, in the low [`STAMP_COUNT_BITS`] bits, updated together with a
+    /// compare-and-swap.
+    stamp: AtomicU64,
+    /// The last time, in milliseconds, read from the clock, used when caching the time.
+    clock_time: AtomicU64,
+    /// The amount of Spaceflakes issued.
+    issued: AtomicU64,
+    /// The generation of the configuration, bumped twice on every reconfiguration: it is odd while the reconfiguration
+    /// is in progress, and even again once it is done.
+    generation: AtomicU64,
+    /// The configuration of the last reconfiguration, which the workers of an older generation switch to.
+    config: RwLock<WorkerConfig>,
+    /// The seed of the random offsets of the sequence, see [`Worker::random_sequence_offset`].
+    seed: u64,
+    /// The last time, in milliseconds, recorded in the state persistence, see [`Worker::set_state_persistence`].
+    persisted: AtomicU64,
+    /// The time, in milliseconds, restored from the state persistence, which the clock must pass before Spaceflakes
+    /// are generated, see [`Worker::set_state_persistence`].
+    restored: AtomicU64,
+}
+
+/// The default implementation of a worker state.
+impl WorkerState {
+    /// Returns the time at which the last Spaceflake was generated, and the amount of Spaceflakes generated in that
+    /// millisecond.
+    fn stamp(&self) -> (u64, u64) {
+        let stamp = self.stamp.load(Ordering::Acquire);
+        (stamp >> STAMP_COUNT_BITS, stamp & STAMP_COUNT_MASK)
+    }
+
+    /// Returns the offset at which the sequence starts in the given millisecond, when it is randomized.
+    fn sequence_offset(&self, time: u64, max_sequence: u64) -> u64 {
+        splitmix64(self.seed ^ time) % max_sequence
+    }
+}
+
+/// The default implementation of a worker state, with a random seed.
+impl Default for WorkerState {
+    fn default() -> Self {
+        WorkerState {
+            stamp: AtomicU64::new(0),
+            clock_time: AtomicU64::new(0),
+            issued: AtomicU64::new(0),
+            generation: AtomicU64::new(0),
+            config: RwLock::new(WorkerConfig::default()),
+            seed: rand::random(),
+            persisted: AtomicU64::new(0),
+            restored: AtomicU64::new(0),
+        }
+    }
+}
+
+/// A block of consecutive sequence slots reserved on a worker with [`Worker::reserve_block`], whose Spaceflakes are
+/// materialized lazily, for example to pre-allocate the IDs of a database batch insert.
+#[derive(Debug, Clone)]
+pub struct SequenceBlock {
+    /// The worker the slots are reserved on.
+    worker: Worker,
+    /// The runs of slots of each millisecond of the block.
+    runs: Vec<Reservation>,
+}
+
+/// The default implementation of a sequence block.
+impl SequenceBlock {
+    /// Returns the amount of Spaceflakes of the block.
+    pub fn len(&self) -> usize {
+        self.runs.iter().map(|run| run.count as usize).sum()
+    }
+
+    /// Returns whether the block has no Spaceflake.
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// Returns an iterator materializing the Spaceflakes of the block, in order.
+    pub fn iter(&self) -> impl Iterator<Item = Result<Spaceflake, SpaceflakeError>> + '_ {
+        self.runs.iter().flat_map(move |run| {
+            (run.index..run.index + run.count).map(move |index| {
+                compose_reserved(self.worker.node_id, &self.worker, run.time, index)
+            })
+        })
+    }
+}
+
+/// The configuration of a worker that can be switched at runtime, see [`Worker::reconfigure`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkerConfig {
+    /// The base epoch that will be used to generate the Spaceflakes.
+    pub base_epoch: u64,
+    /// The amount of high bits of the sequence reserved for the version tag, up to [`MAX_VERSION_BITS`].
+    pub version_bits: u64,
+    /// The version tag written into every generated Spaceflake, must fit in the reserved version bits.
+    pub version: u64,
+    /// The granularity, in milliseconds, of the embedded timestamp.
+    pub time_granularity: u64,
+    /// The layout of the bits of the generated Spaceflakes.
+    pub layout: BitLayout,
+    /// How the worker handles the clock going backwards.
+    pub drift_policy: DriftPolicy,
+}
+
+/// The default implementation of a worker configuration.
+impl WorkerConfig {
+    /// Returns an error if the configuration can not be used to generate Spaceflakes at the given time.
+    fn validate(&self, now: u64) -> Result<(), SpaceflakeError> {
+        self.layout.validate()?;
+        let max_version_bits = MAX_VERSION_BITS.min(self.layout.sequence_bits - 1);
+        if self.version_bits > max_version_bits {
+            return Err(SpaceflakeError::InvalidVersionBits {
+                max: max_version_bits,
+            });
+        }
+        if self.version > (1 << self.version_bits) - 1 {
+            return Err(SpaceflakeError::InvalidVersion {
+                max: 1 << self.version_bits,
+            });
+        }
+        if self.time_granularity == 0 {
+            return Err(SpaceflakeError::InvalidTimeGranularity);
+        }
+        if self.base_epoch > now {
+            return Err(SpaceflakeError::EpochInFuture);
+        }
+        Ok(())
+    }
+}
+
+/// The default implementation of a worker configuration.
+impl Default for WorkerConfig {
+    fn default() -> Self {
+        WorkerConfig {
+            base_epoch: EPOCH,
+            version_bits: 0,
+            version: 0,
+            time_granularity: 1,
+            layout: BitLayout::SPACEFLAKE,
+            drift_policy: DriftPolicy::default(),
+        }
+    }
+}
+
+/// How a worker handles its clock going backwards, which happens when it is stepped back, for example by NTP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum DriftPolicy {
+    /// Return [`SpaceflakeError::ClockMovedBackwards`].
+    Error,
+    /// Wait for the clock to catch up if it went backwards by up to the duration, else return
+    /// [`SpaceflakeError::ClockMovedBackwards`].
+    WaitUpTo(Duration),
+    /// Keep generating Spaceflakes at the time of the last one, consuming its remaining sequence, until the clock
+    /// catches up, if it is behind the time of the last Spaceflake by up to the duration, else return
+    /// [`SpaceflakeError::ClockMovedBackwards`].
+    ///
+    /// Once that sequence is exhausted, the sequence of the next millisecond is borrowed instead of waiting, so the
+    /// generation never blocks on small corrections of the clock, while the Spaceflakes are never ahead of the clock by
+    /// more than the duration and a millisecond.
+    BorrowSequence(Duration),
+}
+
+/// The default implementation of a drift policy, which waits for the clock to catch up if it went backwards by up to
+/// 10 milliseconds.
+impl Default for DriftPolicy {
+    fn default() -> Self {
+        DriftPolicy::WaitUpTo(Duration::from_millis(10))
+    }
+}
+
+/// A policy to retry the generation of a Spaceflake on transient failures, see [`Worker::generate_with_retry`].
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// The maximum amount of attempts, including the first one.
+    pub max_attempts: u32,
+    /// The time to wait before the first retry, which doubles after each retry.
+    pub initial_backoff: Duration,
+    /// The maximum time to wait between two retries.
+    pub max_backoff: Duration,
+}
+
+/// The default implementation of a retry policy.
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(100),
+        }
+    }
+}
+
+/// Settings to bulk generate Spaceflakes easily.
+#[derive(Debug, Clone)]
 pub struct BulkGeneratorSettings {
     /// The amount of Spaceflakes to generate.
     amount: usize,
     /// The base epoch that will be used to generate the Spaceflakes, default is [`EPOCH`].
     pub base_epoch: u64,
+    /// The node ID for which the Spaceflakes will be generated, default is `1`.
+    pub node_id: u64,
+    /// The worker IDs the generation is scaled across, default is all of them from `1` to `31`.
+    pub worker_id_range: Option<RangeInclusive<u64>>,
 }
 
 /// The default implementation of a bulk generator settings.
@@ -303,53 +2133,339 @@ impl BulkGeneratorSettings {
         BulkGeneratorSettings {
             amount,
             base_epoch: EPOCH,
+            node_id: 1,
+            worker_id_range: None,
         }
     }
+
+    /// Returns the settings generating the Spaceflakes for the given node ID, so bulk generations on different hosts
+    /// do not collide.
+    pub fn with_node_id(mut self, node_id: u64) -> Self {
+        self.node_id = node_id;
+        self
+    }
+
+    /// Returns the settings scaling the generation across the given worker IDs only, so bulk generations sharing a
+    /// node ID do not collide.
+    pub fn with_worker_id_range(mut self, worker_id_range: RangeInclusive<u64>) -> Self {
+        self.worker_id_range = Some(worker_id_range);
+        self
+    }
+}
+
+/// The rotation of the workers of a bulk generation, within the node and worker IDs of its settings.
+#[derive(Debug)]
+pub(crate) struct BulkWorkers {
+    /// The worker IDs the generation is scaled across.
+    worker_ids: RangeInclusive<u64>,
+    /// The base epoch of the workers.
+    base_epoch: u64,
+    /// The worker currently generating the Spaceflakes.
+    worker: Worker,
+}
+
+/// The default implementation of the rotation of the workers of a bulk generation.
+impl BulkWorkers {
+    /// Create a new rotation starting at the first worker ID of the settings.
+    ///
+    /// Returns an error if the node ID does not fit in 5 bits, or if the worker ID range is empty or does not fit in 5
+    /// bits.
+    pub(crate) fn new(settings: &BulkGeneratorSettings) -> Result<Self, SpaceflakeError> {
+        let worker_ids = settings.worker_id_range.clone().unwrap_or(1..=MAX_5_BITS);
+        if worker_ids.is_empty() {
+            return Err(SpaceflakeError::InvalidArgument(String::from(
+                "Worker ID range must not be empty",
+            )));
+        }
+        if *worker_ids.end() > MAX_5_BITS {
+            return Err(SpaceflakeError::InvalidWorkerId { max: MAX_5_BITS });
+        }
+        let mut worker = Worker::standalone(settings.node_id, *worker_ids.start())?;
+        worker.base_epoch = settings.base_epoch;
+
+        Ok(BulkWorkers {
+            worker_ids,
+            base_epoch: settings.base_epoch,
+            worker,
+        })
+    }
+
+    /// Returns the worker currently generating the Spaceflakes.
+    pub(crate) fn worker(&self) -> &Worker {
+        &self.worker
+    }
+
+    /// Move on to the given worker ID, for example the one of a checkpoint.
+    fn resume(&mut self, worker_id: u64) -> Result<(), SpaceflakeError> {
+        if !self.worker_ids.contains(&worker_id) {
+            return Err(SpaceflakeError::InvalidArgument(format!(
+                "Checkpoint worker ID must be in the worker ID range, which is {}..={}",
+                self.worker_ids.start(),
+                self.worker_ids.end()
+            )));
+        }
+        self.switch(worker_id);
+        Ok(())
+    }
+
+    /// Move on to the next worker ID, once the sequence of the current worker is exhausted.
+    ///
+    /// Returns whether the rotation wrapped around to the first worker ID, in which case the caller must wait for the
+    /// next millisecond before generating on it again.
+    pub(crate) fn rotate(&mut self) -> bool {
+        let wrapped = self.worker.id >= *self.worker_ids.end();
+        let next = if wrapped {
+            *self.worker_ids.start()
+        } else {
+            self.worker.id + 1
+        };
+        self.switch(next);
+        wrapped
+    }
+
+    /// Replace the current worker by a new one, with a fresh state, for the given worker ID.
+    fn switch(&mut self, worker_id: u64) {
+        self.worker = Worker::new(worker_id, self.worker.node_id);
+        self.worker.base_epoch = self.base_epoch;
+    }
 }
 
 /// Generate an amount of Spaceflakes for the given settings.
 ///
 /// Nodes and workers will be automatically scaled, and the function will also sleep of a millisecond if needed.
-pub fn bulk_generate(settings: BulkGeneratorSettings) -> Result<Vec<Spaceflake>, String> {
-    let mut node = Node::new(1);
-    let mut worker = node.new_worker();
-    worker.base_epoch = settings.base_epoch;
+pub fn bulk_generate(settings: BulkGeneratorSettings) -> Result<Vec<Spaceflake>, SpaceflakeError> {
     let mut spaceflakes = Vec::<Spaceflake>::new();
-    for i in 1..=settings.amount {
-        if i % ((MAX_12_BITS * MAX_5_BITS * MAX_5_BITS) as usize) == 0 {
-            thread::sleep(Duration::from_millis(1));
-            let mut new_node = Node::new(1);
-            let mut new_worker = new_node.new_worker();
-            new_worker.base_epoch = settings.base_epoch;
-            node = new_node;
-            worker = new_worker;
-        } else if node.workers.len() % MAX_5_BITS as usize == 0
-            && i % ((MAX_5_BITS * MAX_12_BITS) as usize) == 0
+    bulk_generate_each(&settings, |spaceflake| {
+        spaceflakes.push(spaceflake);
+        true
+    })?;
+
+    Ok(spaceflakes)
+}
+
+/// Lazily generate an amount of Spaceflakes for the given settings.
+///
+/// Nodes and workers will be automatically scaled, and the iterator will also sleep of a millisecond if needed. The
+/// iterator ends after the amount of Spaceflakes, or after the first error.
+///
+/// Returns an error if the node ID or the worker ID range of the settings is invalid.
+pub fn bulk_iter(settings: BulkGeneratorSettings) -> Result<BulkIter, SpaceflakeError> {
+    Ok(BulkIter {
+        workers: BulkWorkers::new(&settings)?,
+        settings,
+        generated: 0,
+        failed: false,
+    })
+}
+
+/// An iterator lazily generating Spaceflakes in bulk, created by [`bulk_iter`].
+#[derive(Debug)]
+pub struct BulkIter {
+    settings: BulkGeneratorSettings,
+    workers: BulkWorkers,
+    generated: usize,
+    failed: bool,
+}
+
+impl Iterator for BulkIter {
+    type Item = Result<Spaceflake, SpaceflakeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.generated >= self.settings.amount {
+            return None;
+        }
+        if self.generated > 0
+            && self.generated.is_multiple_of(MAX_12_BITS as usize)
+            && self.workers.rotate()
         {
-            let mut new_node = Node::new(1);
-            let mut new_worker = new_node.new_worker();
-            new_worker.base_epoch = settings.base_epoch;
-            node = new_node;
-            worker = new_worker;
-        } else if i % MAX_12_BITS as usize == 0 {
-            let mut new_worker = node.new_worker();
-            new_worker.base_epoch = settings.base_epoch;
-            worker = new_worker;
-        }
-
-        match generate_on_node_and_worker(node.id, worker.clone(), None) {
-            Ok(spaceflake) => {
-                spaceflakes.push(spaceflake);
-            }
-            Err(error) => return Err(error),
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        self.generated += 1;
+        let result = self.workers.worker().generate();
+        self.failed = result.is_err();
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.failed {
+            return (0, Some(0));
+        }
+        let remaining = self.settings.amount - self.generated;
+        (0, Some(remaining))
+    }
+}
+
+impl std::iter::FusedIterator for BulkIter {}
+
+/// The amount of chunks that can be waiting in the channel of [`bulk_generate_chunked`] before generation pauses.
+const CHUNKED_CHANNEL_BOUND: usize = 2;
+
+/// Generate an amount of Spaceflakes for the given settings on a separate thread, and receive them in chunks.
+///
+/// The chunks are sent on a bounded channel, so the consumer can, for example, insert a chunk into a database while the
+/// next one is being generated. Every chunk holds `chunk_size` Spaceflakes, except the last one which may be smaller.
+/// Generation stops after the first error, which is sent on the channel, or when the receiver is dropped.
+pub fn bulk_generate_chunked(
+    settings: BulkGeneratorSettings,
+    chunk_size: usize,
+) -> Receiver<Result<Vec<Spaceflake>, SpaceflakeError>> {
+    let (sender, receiver) = sync_channel(CHUNKED_CHANNEL_BOUND);
+    thread::spawn(move || {
+        if chunk_size == 0 {
+            let _ = sender.send(Err(SpaceflakeError::InvalidChunkSize));
+            return;
+        }
+
+        let mut chunk = Vec::<Spaceflake>::with_capacity(chunk_size);
+        let result = bulk_generate_each(&settings, |spaceflake| {
+            chunk.push(spaceflake);
+            if chunk.len() < chunk_size {
+                return true;
+            }
+            let full = std::mem::replace(&mut chunk, Vec::with_capacity(chunk_size));
+            sender.send(Ok(full)).is_ok()
+        });
+        match result {
+            Ok(()) if !chunk.is_empty() => {
+                let _ = sender.send(Ok(chunk));
+            }
+            Ok(()) => {}
+            Err(error) => {
+                let _ = sender.send(Err(error));
+            }
+        }
+    });
+    receiver
+}
+
+/// A snapshot of the progress of a bulk generation, to resume it with [`bulk_generate_checkpointed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BulkCheckpoint {
+    /// The amount of Spaceflakes generated so far.
+    pub generated: usize,
+    /// The time, in milliseconds since the Unix epoch, at which the last Spaceflake was generated.
+    pub last_time: u64,
+    /// The ID of the worker that generated the last Spaceflake, which gives the rotation of the workers.
+    pub worker_id: u64,
+}
+
+/// Generate an amount of Spaceflakes for the given settings, passing them to the callback in batches of `interval`
+/// Spaceflakes along with the checkpoint reached after each batch.
+///
+/// The callback should persist the batch and its checkpoint together, so an interrupted generation can be resumed by
+/// passing the last persisted checkpoint. Resuming waits for the clock to pass the time of the checkpoint, so the
+/// Spaceflakes generated afterwards can not collide with the ones generated before. Generation stops after the first
+/// error returned by the callback.
+pub fn bulk_generate_checkpointed<F>(
+    settings: BulkGeneratorSettings,
+    resume: Option<BulkCheckpoint>,
+    interval: usize,
+    mut callback: F,
+) -> Result<(), SpaceflakeError>
+where
+    F: FnMut(Vec<Spaceflake>, BulkCheckpoint) -> Result<(), SpaceflakeError>,
+{
+    if interval == 0 {
+        return Err(SpaceflakeError::InvalidArgument(String::from(
+            "Checkpoint interval must be greater than 0",
+        )));
+    }
+    if let Some(checkpoint) = resume {
+        if checkpoint.generated > settings.amount {
+            return Err(SpaceflakeError::InvalidArgument(format!(
+                "Checkpoint must be at most at the amount of Spaceflakes to generate, which is {}",
+                settings.amount
+            )));
+        }
+    }
+
+    let mut batch = Vec::<Spaceflake>::with_capacity(interval);
+    let mut error = None;
+    let last = bulk_generate_from(&settings, resume, |spaceflake, checkpoint| {
+        batch.push(spaceflake);
+        if batch.len() < interval {
+            return true;
+        }
+        let full = std::mem::replace(&mut batch, Vec::with_capacity(interval));
+        error = callback(full, checkpoint).err();
+        error.is_none()
+    })?;
+    if let Some(error) = error {
+        return Err(error);
+    }
+    match last {
+        Some(checkpoint) if !batch.is_empty() => callback(batch, checkpoint),
+        _ => Ok(()),
+    }
+}
+
+/// Generate an amount of Spaceflakes for the given settings, passing each of them to the callback.
+///
+/// Generation stops early if the callback returns `false`.
+fn bulk_generate_each<F>(
+    settings: &BulkGeneratorSettings,
+    mut callback: F,
+) -> Result<(), SpaceflakeError>
+where
+    F: FnMut(Spaceflake) -> bool,
+{
+    bulk_generate_from(settings, None, |spaceflake, _| callback(spaceflake))?;
+    Ok(())
+}
+
+/// Generate an amount of Spaceflakes for the given settings, from the checkpoint if any, passing each of them to the
+/// callback along with the checkpoint reached.
+///
+/// Generation stops early if the callback returns `false`, and the last checkpoint reached is returned.
+fn bulk_generate_from<F>(
+    settings: &BulkGeneratorSettings,
+    resume: Option<BulkCheckpoint>,
+    mut callback: F,
+) -> Result<Option<BulkCheckpoint>, SpaceflakeError>
+where
+    F: FnMut(Spaceflake, BulkCheckpoint) -> bool,
+{
+    let mut workers = BulkWorkers::new(settings)?;
+    let mut start = 1;
+    if let Some(checkpoint) = resume {
+        workers.resume(checkpoint.worker_id)?;
+        while current_time() <= checkpoint.last_time {
+            thread::sleep(Duration::from_millis(1));
+        }
+        start = checkpoint.generated + 1;
+    }
+    let mut last = resume;
+    for i in start..=settings.amount {
+        if i > 1 && (i - 1) % MAX_12_BITS as usize == 0 && workers.rotate() {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        let worker = workers.worker();
+        let spaceflake = worker.generate()?;
+        let checkpoint = BulkCheckpoint {
+            generated: i,
+            last_time: spaceflake.time(),
+            worker_id: worker.id,
         };
+        last = Some(checkpoint);
+        if !callback(spaceflake, checkpoint) {
+            break;
+        }
     }
 
-    Ok(spaceflakes)
+    Ok(last)
 }
 
 /// Settings to generate Spaceflakes normally.
+///
+/// With the `serde` feature, the missing fields are deserialized to their default.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(::serde::Serialize, ::serde::Deserialize),
+    serde(default)
+)]
 pub struct GeneratorSettings {
     /// The base epoch that will be used to generate the Spaceflakes, default is [`EPOCH`].
     pub base_epoch: u64,
@@ -358,82 +2474,319 @@ pub struct GeneratorSettings {
     /// The worker ID for which the Spaceflake will be generated.
     pub worker_id: u64,
     /// The sequence of the generated Spaceflake.
+    ///
+    /// If set to 0, default, [`generate`] uses a number incremented across the whole process for the node and worker
+    /// IDs.
     pub sequence: u64,
+    /// The amount of high bits of the sequence reserved for the version tag, up to [`MAX_VERSION_BITS`], default is `0`.
+    pub version_bits: u64,
+    /// The version tag written into the generated Spaceflake, must fit in the reserved version bits.
+    pub version: u64,
+    /// The granularity, in milliseconds, of the embedded timestamp, default is `1`.
+    ///
+    /// When greater than `1`, the timestamp is truncated to the granularity, rounded up to the unit of the layout, so
+    /// the exact creation time of the Spaceflake is not disclosed, see [`Worker::time_granularity`].
+    pub time_granularity: u64,
+    /// The layout of the bits of the generated Spaceflake, default is [`BitLayout::SPACEFLAKE`].
+    pub layout: BitLayout,
+    /// The clock the time is read from, default is `None` for the system clock.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub clock: Option<&'static dyn Clock>,
+    /// How the clock going backwards is handled, default is to wait for it to catch up if it went backwards by up to 10
+    /// milliseconds, see [`DriftPolicy::WaitUpTo`].
+    pub drift_policy: DriftPolicy,
 }
 
 /// The default implementation of a generator settings.
 impl GeneratorSettings {
     /// Create a new generator settings for the given node and worker IDs.
+    ///
+    /// Panics if an ID is too large.
+    #[deprecated(since = "1.2.0", note = "Use `GeneratorSettings::try_new`")]
     pub fn new(node_id: u64, worker_id: u64) -> Self {
         if node_id > MAX_5_BITS {
             panic!("Node ID must be less than {}", MAX_5_BITS);
         }
-        if worker_id > MAX_12_BITS {
-            panic!("Worker ID must be less than {}", MAX_12_BITS);
+        if worker_id > MAX_5_BITS {
+            panic!("Worker ID must be less than {}", MAX_5_BITS);
         }
 
         GeneratorSettings {
-            base_epoch: EPOCH,
             node_id,
             worker_id,
-            sequence: 0,
+            ..Default::default()
         }
     }
+
+    /// Create a new generator settings for the given node and worker IDs.
+    ///
+    /// Returns an error if an ID does not fit in 5 bits.
+    pub fn try_new(node_id: u64, worker_id: u64) -> Result<Self, SpaceflakeError> {
+        if node_id > MAX_5_BITS {
+            return Err(SpaceflakeError::InvalidNodeId { max: MAX_5_BITS });
+        }
+        if worker_id > MAX_5_BITS {
+            return Err(SpaceflakeError::InvalidWorkerId { max: MAX_5_BITS });
+        }
+
+        Ok(GeneratorSettings {
+            node_id,
+            worker_id,
+            ..Default::default()
+        })
+    }
+
+    /// Returns the generator settings with the base epoch set to the given time, such as a `SystemTime` or, with the
+    /// `chrono` and `time` features, a `chrono::DateTime` or a `time::OffsetDateTime`.
+    ///
+    /// Returns an error if the time is before the Unix epoch or too far after it.
+    pub fn with_base_epoch_at<T: Into<SystemTime>>(
+        mut self,
+        base_epoch: T,
+    ) -> Result<Self, SpaceflakeError> {
+        self.base_epoch = base_epoch
+            .into()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .and_then(|elapsed| u64::try_from(elapsed.as_millis()).ok())
+            .ok_or_else(|| {
+                SpaceflakeError::InvalidArgument(
+                    "The base epoch is before the Unix epoch".to_string(),
+                )
+            })?;
+        Ok(self)
+    }
 }
 
 /// The default implementation of a generator settings.
 impl Default for GeneratorSettings {
     fn default() -> Self {
-        Self::new(0, 0)
+        GeneratorSettings {
+            base_epoch: EPOCH,
+            node_id: 0,
+            worker_id: 0,
+            sequence: 0,
+            version_bits: 0,
+            version: 0,
+            time_granularity: 1,
+            layout: BitLayout::SPACEFLAKE,
+            clock: None,
+            drift_policy: DriftPolicy::default(),
+        }
     }
 }
 
+/// The key of the states shared by the workers of [`generate`], which is the node ID, the worker ID, the base epoch,
+/// the layout and the address of the clock, if any.
+type GenerateKey = (u64, u64, u64, BitLayout, Option<usize>);
+
+/// The states shared by the workers of [`generate`], one per key, so their sequences are incremented across the whole
+/// process while a clock ahead of the others, or another base epoch, can not hold them back.
+static GENERATE_STATES: OnceLock<Mutex<HashMap<GenerateKey, Arc<WorkerState>>>> = OnceLock::new();
+
 /// Generate a Spaceflake for the given settings.
 ///
-/// If the sequence is set to `0`, which is default, it it will get randomly generated.
-pub fn generate(settings: GeneratorSettings) -> Result<Spaceflake, String> {
-    let mut worker = Worker::new(settings.worker_id, settings.node_id);
-    if settings.sequence == 0 {
-        worker.sequence = rand::thread_rng().gen_range(1..=MAX_12_BITS);
-    } else {
-        worker.sequence = settings.sequence;
-    }
+/// If the sequence is set to `0`, which is default, it will be a number incremented across the whole process for the
+/// node and worker IDs, base epoch, layout and clock of the settings, so Spaceflakes generated back-to-back in the
+/// same millisecond are unique.
+pub fn generate(settings: GeneratorSettings) -> Result<Spaceflake, SpaceflakeError> {
+    let mut worker = Worker::from_settings(settings)?;
+    let key = (
+        settings.node_id,
+        settings.worker_id,
+        settings.base_epoch,
+        settings.layout,
+        settings
+            .clock
+            .map(|clock| clock as *const dyn Clock as *const () as usize),
+    );
+    worker.state = Arc::clone(
+        GENERATE_STATES
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(key)
+            .or_default(),
+    );
     generate_on_node_and_worker(settings.node_id, worker, None)
 }
 
 /// Generate a Spaceflake for the given settings at a specific time.
 ///
-/// If the sequence is set to `0`, which is default, it it will get randomly generated.
-pub fn generate_at(settings: GeneratorSettings, at: u64) -> Result<Spaceflake, String> {
-    let mut worker = Worker::new(settings.worker_id, settings.node_id);
+/// If the sequence is set to `0`, which is default, it will get randomly generated.
+pub fn generate_at(settings: GeneratorSettings, at: u64) -> Result<Spaceflake, SpaceflakeError> {
+    let mut worker = Worker::from_settings(settings)?;
     if settings.sequence == 0 {
-        worker.sequence = rand::thread_rng().gen_range(1..=MAX_12_BITS);
-    } else {
-        worker.sequence = settings.sequence;
+        worker.sequence = rand::thread_rng().gen_range(
+            1..=(settings.layout.max_sequence() >> settings.version_bits.min(MAX_VERSION_BITS))
+                .max(1),
+        );
     }
     generate_on_node_and_worker(settings.node_id, worker, Option::from(at))
 }
 
-/// Parse the time of a Spaceflake ID.
+/// Generate an amount of Spaceflakes for the given settings, spread evenly across a past time window.
+///
+/// Both the start and the end of the window are inclusive, in milliseconds. The sequences restart at `1` every
+/// millisecond, so the amount can not be greater than the window's capacity for the settings' node and worker. The
+/// sequence and time granularity of the settings are ignored.
+pub fn bulk_generate_between(
+    settings: GeneratorSettings,
+    start_ms: u64,
+    end_ms: u64,
+    amount: usize,
+) -> Result<Vec<Spaceflake>, SpaceflakeError> {
+    if start_ms > end_ms {
+        return Err(SpaceflakeError::InvalidTimeWindow(String::from(
+            "The start of the time window must be less than or equals to its end",
+        )));
+    }
+    let window = end_ms - start_ms + 1;
+    let max_sequence =
+        settings.layout.max_sequence() >> settings.version_bits.min(MAX_VERSION_BITS);
+    let capacity = window.saturating_mul(max_sequence);
+    if amount as u64 > capacity {
+        return Err(SpaceflakeError::InvalidTimeWindow(format!(
+            "Amount must be less than or equals to the capacity of the time window, which is {}",
+            capacity
+        )));
+    }
+
+    generate_at_times(
+        settings,
+        (0..amount as u64).map(|i| start_ms + (i as u128 * window as u128 / amount as u128) as u64),
+    )
+}
+
+/// Generate a Spaceflake for the given settings at each of the given times, which must be in ascending order.
+///
+/// The sequences restart at `1` every millisecond, and the sequence and time granularity of the settings are ignored.
+fn generate_at_times<I>(
+    settings: GeneratorSettings,
+    times: I,
+) -> Result<Vec<Spaceflake>, SpaceflakeError>
+where
+    I: IntoIterator<Item = u64>,
+{
+    let times = times.into_iter();
+    let max_sequence =
+        settings.layout.max_sequence() >> settings.version_bits.min(MAX_VERSION_BITS);
+    let mut worker = Worker::from_settings(settings)?;
+    worker.time_granularity = 1;
+    let mut spaceflakes = Vec::<Spaceflake>::with_capacity(times.size_hint().0);
+    let mut last_ms = None;
+    for at in times {
+        if last_ms == Some(at) {
+            worker.sequence += 1;
+            if worker.sequence > max_sequence {
+                return Err(SpaceflakeError::InvalidTimeWindow(format!(
+                    "Amount must be less than or equals to the capacity of a millisecond, which is {}",
+                    max_sequence
+                )));
+            }
+        } else {
+            worker.sequence = 1;
+            last_ms = Some(at);
+        }
+        spaceflakes.push(generate_on_node_and_worker(
+            settings.node_id,
+            worker.clone(),
+            Some(at),
+        )?);
+    }
+
+    Ok(spaceflakes)
+}
+
+/// Derive a Spaceflake deterministically for a tenant, an idempotency key and a time, with the base epoch and the
+/// layout of the settings.
+///
+/// All the bits below the time, so the node ID, worker ID and sequence, are taken from an HMAC-SHA256 of the tenant,
+/// the idempotency key and the time, keyed by the secret, so retried requests with the same idempotency key map to the
+/// same Spaceflake without a lookup table. The node and worker IDs of the settings are not used.
+///
+/// Requests with different idempotency keys in the same unit of time are not guaranteed to map to different
+/// Spaceflakes: with the 22 bits below the time of the default layout, two keys collide with a probability of about
+/// one in four million, and a collision becomes likely from around two thousand keys in the same millisecond. The
+/// uniqueness of the derived Spaceflakes must still be enforced where it matters, for example by a unique constraint.
+///
+/// The secret can be shared by all the tenants, as the tenant is part of the message.
+#[cfg(feature = "hmac")]
+pub fn derive_id(
+    settings: &GeneratorSettings,
+    secret: &[u8],
+    tenant: &str,
+    idempotency_key: &[u8],
+    at_ms: u64,
+) -> Result<Spaceflake, SpaceflakeError> {
+    use hmac::{Hmac, Mac};
+
+    let layout = settings.layout;
+    layout.validate()?;
+    if at_ms < settings.base_epoch {
+        return Err(SpaceflakeError::EpochAfterGenerationTime);
+    }
+    let time = (at_ms - settings.base_epoch) / layout.time_unit.as_millis();
+    if time > layout.max_time() {
+        return Err(SpaceflakeError::TimeOverflow);
+    }
+
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(secret)
+        .map_err(|error| SpaceflakeError::InvalidArgument(error.to_string()))?;
+    // The variable-length inputs are prefixed with their length, so they can not be shifted into each other.
+    mac.update(&(tenant.len() as u64).to_be_bytes());
+    mac.update(tenant.as_bytes());
+    mac.update(&(idempotency_key.len() as u64).to_be_bytes());
+    mac.update(idempotency_key);
+    mac.update(&at_ms.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+
+    let shift = layout.time_shift();
+    Ok(Spaceflake::new(
+        (time << shift) | (u64::from_be_bytes(bytes) & ((1 << shift) - 1)),
+        settings.base_epoch,
+    )
+    .with_layout(layout))
+}
+
+/// Parse the time of a Spaceflake ID with the default layout, see [`BitLayout::parse_time`] for other layouts.
+#[cfg_attr(all(feature = "no-panic", not(debug_assertions)), no_panic::no_panic)]
 pub fn parse_time(spaceflake_id: u64, base_epoch: u64) -> u64 {
     (spaceflake_id >> 22) + base_epoch
 }
 
-/// Parse the node ID of a Spaceflake ID.
+/// Parse the node ID of a Spaceflake ID with the default layout, see [`BitLayout::parse_node_id`] for other layouts.
+#[cfg_attr(all(feature = "no-panic", not(debug_assertions)), no_panic::no_panic)]
 pub fn parse_node_id(spaceflake_id: u64) -> u64 {
     (spaceflake_id & 0x3E0000) >> 17
 }
 
-/// Parse the worker ID of a Spaceflake ID.
+/// Parse the worker ID of a Spaceflake ID with the default layout, see [`BitLayout::parse_worker_id`] for other
+/// layouts.
+#[cfg_attr(all(feature = "no-panic", not(debug_assertions)), no_panic::no_panic)]
 pub fn parse_worker_id(spaceflake_id: u64) -> u64 {
     (spaceflake_id & 0x1F000) >> 12
 }
 
-/// Parse the sequence of a Spaceflake ID.
+/// Parse the sequence of a Spaceflake ID with the default layout, see [`BitLayout::parse_sequence`] for other
+/// layouts.
+#[cfg_attr(all(feature = "no-panic", not(debug_assertions)), no_panic::no_panic)]
 pub fn parse_sequence(spaceflake_id: u64) -> u64 {
     spaceflake_id & 0xFFF
 }
 
+/// Parse the version tag of a Spaceflake ID, given the amount of bits that were reserved for it, which are all the bits
+/// of the sequence when greater than 12.
+#[cfg_attr(all(feature = "no-panic", not(debug_assertions)), no_panic::no_panic)]
+pub fn parse_version(spaceflake_id: u64, version_bits: u64) -> u64 {
+    if version_bits == 0 {
+        return 0;
+    }
+    (spaceflake_id & 0xFFF) >> (12 - version_bits.min(12))
+}
+
 /// Decompose a Spaceflake ID, and get a key-value hashmap with each part of a Spaceflake.
 pub fn decompose(spaceflake_id: u64, base_epoch: u64) -> HashMap<String, u64> {
     Spaceflake::new(spaceflake_id, base_epoch).decompose()
@@ -449,56 +2802,409 @@ fn generate_on_node_and_worker(
     node_id: u64,
     worker: Worker,
     at: Option<u64>,
-) -> Result<Spaceflake, String> {
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("Time went backwards?")
-        .as_millis() as u64;
+) -> Result<Spaceflake, SpaceflakeError> {
+    generate_on_node_and_worker_with(node_id, worker, at, true)
+}
+
+/// Generates a Spaceflake for a given worker and node ID, returning [`SpaceflakeError::WouldBlock`] instead of waiting
+/// for the next millisecond when not blocking.
+fn generate_on_node_and_worker_with(
+    node_id: u64,
+    mut worker: Worker,
+    at: Option<u64>,
+    blocking: bool,
+) -> Result<Spaceflake, SpaceflakeError> {
+    let reservation = reserve_on_node_and_worker(node_id, &mut worker, at, blocking, 1)?;
+    compose_reserved(node_id, &worker, reservation.time, reservation.index)
+}
+
+/// A run of consecutive sequence slots of a millisecond reserved on a worker, see [`reserve_on_node_and_worker`].
+#[derive(Debug, Clone, Copy)]
+struct Reservation {
+    /// The time of the slots, in milliseconds since the Unix epoch, truncated to the window of the time granularity.
+    time: u64,
+    /// The index of the first slot in the millisecond.
+    index: u64,
+    /// The amount of slots, at least `1`.
+    count: u64,
+}
+
+/// Reserves up to the given amount, at least `1`, of consecutive sequence slots of a millisecond for a given worker
+/// and node ID, returning [`SpaceflakeError::WouldBlock`] instead of waiting for the next millisecond when not
+/// blocking.
+///
+/// The worker is first switched to the configuration of its last reconfiguration, if any, so the slots are composed
+/// with the configuration they were reserved with.
+fn reserve_on_node_and_worker(
+    node_id: u64,
+    worker: &mut Worker,
+    at: Option<u64>,
+    blocking: bool,
+    amount: u64,
+) -> Result<Reservation, SpaceflakeError> {
+    loop {
+        let generation = worker.sync_config(blocking)?;
+        let reservation = reserve_with_config(node_id, worker, at, blocking, amount)?;
+        // A reconfiguration started meanwhile, and the slots may be in the millisecond it fences, so they are given up
+        // and reserved again with the new configuration.
+        if worker.state.generation.load(Ordering::SeqCst) == generation {
+            return Ok(reservation);
+        }
+    }
+}
+
+/// Reserves slots like [`reserve_on_node_and_worker`], with the current configuration of the worker.
+fn reserve_with_config(
+    node_id: u64,
+    worker: &Worker,
+    at: Option<u64>,
+    blocking: bool,
+    amount: u64,
+) -> Result<Reservation, SpaceflakeError> {
+    let state = &worker.state;
+    let layout = worker.layout;
+    layout.validate()?;
+    let step = worker.time_step();
+    let unit = layout.time_unit.as_millis();
+    let now = if at.is_none() && worker.cache_time {
+        let max_sequence = layout.max_sequence() >> worker.version_bits.min(MAX_VERSION_BITS);
+        let clock_time = state.clock_time.load(Ordering::Acquire);
+        let (last_timestamp, count) = state.stamp();
+        let exhausted = clock_time != 0
+            && worker.available_in_window(last_timestamp, count, clock_time, max_sequence) == 0;
+        if clock_time == 0 || exhausted {
+            if exhausted {
+                worker
+                    .events
+                    .emit(|handler| handler.on_sequence_exhausted(worker));
+            }
+            let last_time = worker.truncate_time(clock_time);
+            let mut now = worker.now()?;
+            while worker.truncate_time(now) <= last_time {
+                if !blocking {
+                    return Err(SpaceflakeError::WouldBlock {
+                        retry_after_ms: last_time + step - now,
+                    });
+                }
+                thread::sleep(Duration::from_micros(100));
+                now = worker.now()?;
+            }
+            state.clock_time.fetch_max(now, Ordering::AcqRel).max(now)
+        } else {
+            clock_time
+        }
+    } else {
+        worker.now()?
+    };
+
+    // Spaceflakes may have been issued up to the time restored from the state persistence before a restart, so the
+    // worker waits for the clock to pass it instead of applying its drift policy, which could borrow past it.
+    let restored = state.restored.load(Ordering::Acquire);
+    let now = if at.is_none() && now <= restored {
+        let mut now = now;
+        while now <= restored {
+            if restored - now > PERSISTENCE_WINDOW_MS {
+                return Err(SpaceflakeError::ClockMovedBackwards {
+                    delta_ms: restored - now,
+                });
+            }
+            if !blocking {
+                return Err(SpaceflakeError::WouldBlock {
+                    retry_after_ms: restored + 1 - now,
+                });
+            }
+            thread::sleep(Duration::from_micros(100));
+            now = worker.now()?;
+        }
+        now
+    } else {
+        now
+    };
 
     let generate_at = at.unwrap_or(now);
 
-    if node_id > MAX_5_BITS {
-        return Err(format!("Node ID must be less than {}", MAX_5_BITS));
+    if node_id > layout.max_node_id() {
+        return Err(SpaceflakeError::InvalidNodeId {
+            max: layout.max_node_id(),
+        });
     }
-    if worker.id > MAX_12_BITS {
-        return Err(format!("Worker ID must be less than {}", MAX_12_BITS));
+    if worker.id > layout.max_worker_id() {
+        return Err(SpaceflakeError::InvalidWorkerId {
+            max: layout.max_worker_id(),
+        });
+    }
+    let max_version_bits = MAX_VERSION_BITS.min(layout.sequence_bits - 1);
+    if worker.version_bits > max_version_bits {
+        return Err(SpaceflakeError::InvalidVersionBits {
+            max: max_version_bits,
+        });
+    }
+    let max_sequence = layout.max_sequence() >> worker.version_bits;
+    if worker.version > (1 << worker.version_bits) - 1 {
+        return Err(SpaceflakeError::InvalidVersion {
+            max: 1 << worker.version_bits,
+        });
+    }
+    if worker.sequence > max_sequence {
+        return Err(SpaceflakeError::InvalidSequence { max: max_sequence });
+    }
+    if worker.time_granularity == 0 {
+        return Err(SpaceflakeError::InvalidTimeGranularity);
     }
     if worker.base_epoch > generate_at {
-        return Err(String::from(
-            "Base epoch must be less than the time you want to generate the Spaceflake at",
-        ));
+        return Err(SpaceflakeError::EpochAfterGenerationTime);
     }
     if worker.base_epoch > now {
-        return Err(String::from(
-            "Base epoch must be less than or equals to current epoch time",
-        ));
+        return Err(SpaceflakeError::EpochInFuture);
     }
     if generate_at > now {
-        return Err(String::from(
-            "The current time must be greater than the time you want to generate the Spaceflake at",
-        ));
+        return Err(SpaceflakeError::GenerationTimeInFuture);
     }
 
-    let mut increment = worker.increment.lock().unwrap();
-    if *increment >= MAX_12_BITS {
-        *increment = 0
+    // The clock going backwards must not make the worker, nor any of its clones, issue Spaceflakes older than the last
+    // one, so they keep being generated at the time of the last one until the clock catches up.
+    let mut generate_at = worker.truncate_time(generate_at);
+    let mut stamp = state.stamp.load(Ordering::Acquire);
+    let (generate_at, index, count, drift) = loop {
+        let (last_timestamp, count) = (stamp >> STAMP_COUNT_BITS, stamp & STAMP_COUNT_MASK);
+        let (time, drift) = if at.is_none() && generate_at < last_timestamp {
+            // A later unit of the current window is not a drift, the sequence of its first units was exhausted.
+            match last_timestamp - generate_at {
+                ahead if ahead < step => (last_timestamp, 0),
+                ahead => (last_timestamp, ahead),
+            }
+        } else {
+            (generate_at, 0)
+        };
+        if drift > 0 {
+            // The time may have been read before the thread was preempted while the clones kept generating, so the
+            // drift is confirmed with a fresh reading of the clock.
+            let fresh = worker.truncate_time(worker.now()?);
+            if fresh > generate_at {
+                generate_at = fresh;
+                stamp = state.stamp.load(Ordering::Acquire);
+                continue;
+            }
+        }
+        let max_drift = match worker.drift_policy {
+            DriftPolicy::Error => 0,
+            DriftPolicy::WaitUpTo(max_drift) | DriftPolicy::BorrowSequence(max_drift) => {
+                max_drift.as_millis() as u64
+            }
+        };
+        if drift > max_drift {
+            worker
+                .events
+                .emit(|handler| handler.on_drift(worker, drift));
+            return Err(SpaceflakeError::ClockMovedBackwards { delta_ms: drift });
+        }
+        if drift > 0 && !matches!(worker.drift_policy, DriftPolicy::BorrowSequence(_)) {
+            worker
+                .events
+                .emit(|handler| handler.on_drift(worker, drift));
+            let mut now = worker.now()?;
+            while worker.truncate_time(now) + step <= last_timestamp {
+                if !blocking {
+                    return Err(SpaceflakeError::WouldBlock {
+                        retry_after_ms: last_timestamp - now,
+                    });
+                }
+                thread::sleep(Duration::from_micros(100));
+                now = worker.now()?;
+            }
+            generate_at = worker.truncate_time(now);
+            stamp = state.stamp.load(Ordering::Acquire);
+            continue;
+        }
+        let index = if time == last_timestamp { count } else { 0 };
+        // Once the incremented sequence of the millisecond is exhausted, wrapping it would issue duplicates, so the
+        // worker waits for the next millisecond instead.
+        if worker.sequence == 0 && index >= max_sequence {
+            // The next unit of the window, freed by truncating the time, is used without waiting, so the sequence is
+            // exhausted only at the end of the window.
+            let within_window = at.is_none() && drift == 0 && time + unit - generate_at < step;
+            if !within_window {
+                worker
+                    .events
+                    .emit(|handler| handler.on_sequence_exhausted(worker));
+                if at.is_some() || worker.fail_on_exhaustion {
+                    return Err(SpaceflakeError::SequenceExhausted);
+                }
+            }
+            // When the clock is behind, waiting for it could take a while: the next millisecond is borrowed instead.
+            if within_window || drift > 0 {
+                let borrowed = time + unit;
+                let count = amount.min(max_sequence);
+                let next = (borrowed << STAMP_COUNT_BITS) | count;
+                match state.stamp.compare_exchange_weak(
+                    stamp,
+                    next,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                ) {
+                    Ok(_) => break (borrowed, 0, count, drift),
+                    Err(current) => stamp = current,
+                }
+                continue;
+            }
+            let mut now = worker.now()?;
+            while worker.truncate_time(now) <= time {
+                if !blocking {
+                    return Err(SpaceflakeError::WouldBlock {
+                        retry_after_ms: worker.truncate_time(time) + step - now,
+                    });
+                }
+                thread::sleep(Duration::from_micros(100));
+                now = worker.now()?;
+            }
+            if worker.cache_time {
+                state.clock_time.fetch_max(now, Ordering::AcqRel);
+            }
+            generate_at = worker.truncate_time(now);
+            stamp = state.stamp.load(Ordering::Acquire);
+            continue;
+        }
+        let count = if worker.sequence == 0 {
+            amount.min(max_sequence - index)
+        } else {
+            1
+        };
+        let next = (time << STAMP_COUNT_BITS) | ((index + count) & STAMP_COUNT_MASK);
+        match state
+            .stamp
+            .compare_exchange_weak(stamp, next, Ordering::AcqRel, Ordering::Acquire)
+        {
+            Ok(_) => break (time, index, count, drift),
+            Err(current) => stamp = current,
+        }
+    };
+    if let Some(persistence) = &worker.persistence.0 {
+        let until = generate_at + PERSISTENCE_WINDOW_MS;
+        if state.persisted.fetch_max(until, Ordering::AcqRel) < generate_at {
+            persistence.store(node_id, worker.id, until)?;
+        }
+    }
+    if drift > 0 {
+        worker
+            .events
+            .emit(|handler| handler.on_drift(worker, drift));
     }
-    *increment += 1;
 
-    let milliseconds = generate_at - worker.base_epoch;
+    Ok(Reservation {
+        time: generate_at,
+        index,
+        count,
+    })
+}
+
+/// Composes the Spaceflake of a sequence slot reserved with [`reserve_on_node_and_worker`].
+fn compose_reserved(
+    node_id: u64,
+    worker: &Worker,
+    generate_at: u64,
+    index: u64,
+) -> Result<Spaceflake, SpaceflakeError> {
+    let state = &worker.state;
+    let layout = worker.layout;
+    let max_sequence = layout.max_sequence() >> worker.version_bits;
+    let time = (generate_at - worker.base_epoch) / layout.time_unit.as_millis();
+    if time > layout.max_time() {
+        return Err(SpaceflakeError::TimeOverflow);
+    }
 
-    let base = pad_left(decimal_binary(milliseconds), 41);
-    let node_id = pad_left(decimal_binary(node_id), 5);
-    let worker_id = pad_left(decimal_binary(worker.id), 5);
     let mut actual_sequence = worker.sequence;
     if worker.sequence == 0 {
-        actual_sequence = *increment
+        let offset = if worker.random_sequence_offset {
+            state.sequence_offset(generate_at, max_sequence)
+        } else {
+            0
+        };
+        actual_sequence = (offset + index) % max_sequence + 1;
+    }
+    let sequence =
+        (worker.version << (layout.sequence_bits - worker.version_bits)) | actual_sequence;
+    let id = layout.compose(time, node_id, worker.id, sequence);
+    if !layout.is_sign_safe(id) {
+        return Err(SpaceflakeError::TimeOverflow);
     }
-    drop(increment);
-    let sequence = pad_left(decimal_binary(actual_sequence), 12);
-    let id = binary_decimal(format!("0{}{}{}{}", base, node_id, worker_id, sequence));
+    state.issued.fetch_add(1, Ordering::Relaxed);
 
-    Ok(Spaceflake::new(id, worker.base_epoch))
+    Ok(Spaceflake::new(id, worker.base_epoch)
+        .with_version_bits(worker.version_bits)
+        .with_layout(layout))
+}
+
+/// Returns the current time in milliseconds since the Unix epoch.
+fn current_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+/// Mix the bits of a number, to derive hashes from it.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// Compute the Luhn check digit of a string of decimal digits.
+fn luhn_check_digit(digits: &str) -> u32 {
+    let sum: u32 = digits
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(i, b)| {
+            let digit = (b - b'0') as u32;
+            if i % 2 == 0 {
+                let doubled = digit * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                digit
+            }
+        })
+        .sum();
+    (10 - sum % 10) % 10
+}
+
+/// Hash the data with the murmur2 variant used by Kafka's Java client.
+fn murmur2(data: &[u8]) -> u32 {
+    const SEED: u32 = 0x9747B28C;
+    const M: u32 = 0x5BD1E995;
+    const R: u32 = 24;
+
+    let mut h = SEED ^ data.len() as u32;
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h = h.wrapping_mul(M);
+        h ^= k;
+    }
+
+    let remainder = chunks.remainder();
+    if remainder.len() >= 3 {
+        h ^= (remainder[2] as u32) << 16;
+    }
+    if remainder.len() >= 2 {
+        h ^= (remainder[1] as u32) << 8;
+    }
+    if !remainder.is_empty() {
+        h ^= remainder[0] as u32;
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^= h >> 15;
+    h
 }
 
 /// Convert a decimal number to a binary number.
@@ -506,11 +3212,6 @@ fn decimal_binary(n: u64) -> String {
     format!("{:b}", n).to_string()
 }
 
-/// Convert a binary number to a decimal number.
-fn binary_decimal(n: String) -> u64 {
-    u64::from_str_radix(&n, 2).unwrap()
-}
-
 /// Add zeroes to the left of the string for the given width.
 fn pad_left(string: String, width: usize) -> String {
     format!("{:0>1$}", string, width)
@@ -0,0 +1,50 @@
+use crate::{
+    generate, BitLayout, GeneratorSettings, Spaceflake, SpaceflakeError, Worker, MAX_5_BITS,
+};
+
+/// The maximum machine ID, when the node and worker IDs are merged into a single 10-bit machine field.
+pub const MAX_MACHINE_ID: u64 = 1023;
+
+/// Split a machine ID into the node ID, stored in its high 5 bits, and the worker ID, stored in its low 5 bits.
+///
+/// Merging the node and worker IDs gives up to 1024 machines with a sequence each, for deployments that do not need
+/// the two-level hierarchy.
+pub fn split_machine_id(machine_id: u64) -> Result<(u64, u64), SpaceflakeError> {
+    if machine_id > MAX_MACHINE_ID {
+        return Err(SpaceflakeError::InvalidArgument(format!(
+            "Machine ID must be less than or equals to {}",
+            MAX_MACHINE_ID
+        )));
+    }
+    Ok((machine_id >> 5, machine_id & MAX_5_BITS))
+}
+
+/// Parse the machine ID of a Spaceflake ID with the default layout, which is its node and worker IDs merged, see
+/// [`BitLayout::parse_machine_id`] for other layouts.
+pub fn parse_machine_id(spaceflake_id: u64) -> u64 {
+    BitLayout::SPACEFLAKE.parse_machine_id(spaceflake_id)
+}
+
+/// Create a worker generating the Spaceflakes of the given machine.
+pub fn machine_worker(machine_id: u64) -> Result<Worker, SpaceflakeError> {
+    let (node_id, worker_id) = split_machine_id(machine_id)?;
+    Worker::standalone(node_id, worker_id)
+}
+
+/// Generate a Spaceflake for the given settings on the given machine, overriding their node and worker IDs.
+pub fn generate_on_machine(
+    settings: GeneratorSettings,
+    machine_id: u64,
+) -> Result<Spaceflake, SpaceflakeError> {
+    let mut settings = settings;
+    (settings.node_id, settings.worker_id) = split_machine_id(machine_id)?;
+    generate(settings)
+}
+
+/// The machine implementation of a Spaceflake.
+impl Spaceflake {
+    /// Returns the machine ID of the Spaceflake, which is its node and worker IDs merged.
+    pub fn machine_id(&self) -> u64 {
+        self.layout().parse_machine_id(self.id)
+    }
+}
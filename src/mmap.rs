@@ -0,0 +1,42 @@
+use std::fs::OpenOptions;
+use std::path::Path;
+
+use memmap2::MmapMut;
+
+use crate::{bulk_generate_each, BulkGeneratorSettings, SpaceflakeError};
+
+/// Generate an amount of Spaceflakes for the given settings, and write their IDs as raw little-endian `u64`s into a
+/// memory-mapped file at the given path.
+///
+/// The file is created, or truncated, and pre-sized to hold all the Spaceflakes, so the amount of Spaceflakes that can
+/// be generated is bounded by the disk rather than by the memory.
+pub fn bulk_generate_to_mmap<P: AsRef<Path>>(
+    settings: BulkGeneratorSettings,
+    path: P,
+) -> Result<(), SpaceflakeError> {
+    let size = settings.amount.checked_mul(8).ok_or_else(|| {
+        SpaceflakeError::InvalidArgument(String::from(
+            "Amount is too large to be written to a file",
+        ))
+    })?;
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
+    file.set_len(size as u64)?;
+    if size == 0 {
+        return Ok(());
+    }
+
+    // SAFETY: The file was just created and is only written through this mapping until it is dropped.
+    let mut mmap = unsafe { MmapMut::map_mut(&file) }?;
+    let mut offset = 0;
+    bulk_generate_each(&settings, |spaceflake| {
+        mmap[offset..offset + 8].copy_from_slice(&spaceflake.id.to_le_bytes());
+        offset += 8;
+        true
+    })?;
+    mmap.flush().map_err(SpaceflakeError::from)
+}
@@ -0,0 +1,21 @@
+use utoipa::openapi::schema::{ObjectBuilder, Schema, SchemaFormat, Type};
+use utoipa::openapi::RefOr;
+use utoipa::{PartialSchema, ToSchema};
+
+use crate::Spaceflake;
+
+/// The OpenAPI schema of a Spaceflake, its ID as a decimal string, as JavaScript numbers can not hold 64-bit integers.
+impl PartialSchema for Spaceflake {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(Type::String)
+            .format(Some(SchemaFormat::Custom("int64-as-string".to_string())))
+            .pattern(Some("^[0-9]{1,19}$"))
+            .description(Some("A Spaceflake ID as a decimal string"))
+            .examples(["1560497923970564097"])
+            .into()
+    }
+}
+
+/// The named OpenAPI schema of a Spaceflake, `Spaceflake`.
+impl ToSchema for Spaceflake {}
@@ -0,0 +1,27 @@
+use rayon::prelude::*;
+
+use crate::{Node, Spaceflake, SpaceflakeError, Worker, MAX_5_BITS};
+
+/// The parallel implementation of a node.
+impl Node {
+    /// Generate an amount of Spaceflakes on the node, in parallel with [`rayon`].
+    ///
+    /// The amount is split across the 31 worker IDs of the node, each generating its part on a separate thread with
+    /// its own state, and the Spaceflakes are then merged in increasing order.
+    pub fn par_bulk_generate(&self, amount: usize) -> Result<Vec<Spaceflake>, SpaceflakeError> {
+        let workers = MAX_5_BITS as usize;
+        let parts = (1..=workers)
+            .into_par_iter()
+            .map(|id| {
+                let part = amount / workers + usize::from(id <= amount % workers);
+                let mut worker = Worker::new(id as u64, self.id);
+                worker.events = self.events.clone();
+                worker.bulk_generate(part)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut spaceflakes = parts.concat();
+        spaceflakes.par_sort_unstable_by_key(|spaceflake| spaceflake.id);
+        Ok(spaceflakes)
+    }
+}
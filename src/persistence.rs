@@ -0,0 +1,102 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+use crate::SpaceflakeError;
+
+/// How far ahead, in milliseconds, of the last time issued by a worker the time recorded in its state persistence is,
+/// so it is only written about once per window.
+pub const PERSISTENCE_WINDOW_MS: u64 = 1000;
+
+/// A store of the last time issued by each worker, so a restarted worker does not reissue times it already used
+/// before a backwards clock adjustment, see [`crate::Worker::set_state_persistence`].
+pub trait StatePersistence: Send + Sync {
+    /// Returns the last time, in milliseconds since the Unix epoch, recorded for the node and worker IDs, if any.
+    fn load(&self, node_id: u64, worker_id: u64) -> Result<Option<u64>, SpaceflakeError>;
+
+    /// Record the last time, in milliseconds since the Unix epoch, for the node and worker IDs.
+    fn store(&self, node_id: u64, worker_id: u64, last_time: u64) -> Result<(), SpaceflakeError>;
+}
+
+/// A state persistence recording the last times as lines of comma-separated `node_id,worker_id,last_time` values in a
+/// file, which is replaced atomically on every store.
+#[derive(Debug)]
+pub struct FileStatePersistence {
+    /// The path of the file the last times are recorded in.
+    path: PathBuf,
+    /// The lock serializing the stores.
+    lock: Mutex<()>,
+}
+
+/// The default implementation of a file state persistence.
+impl FileStatePersistence {
+    /// Create a new file state persistence recording the last times in the file at the given path, which is created on
+    /// the first store.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        FileStatePersistence {
+            path: path.as_ref().to_path_buf(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Read the recorded last times, by node and worker IDs.
+    fn read(&self) -> Result<Vec<(u64, u64, u64)>, SpaceflakeError> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error.into()),
+        };
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let values = line
+                    .split(',')
+                    .map(|value| value.trim().parse::<u64>())
+                    .collect::<Result<Vec<u64>, _>>();
+                match values.as_deref() {
+                    Ok([node_id, worker_id, last_time]) => Ok((*node_id, *worker_id, *last_time)),
+                    _ => Err(SpaceflakeError::InvalidEncoding(format!(
+                        "Invalid worker state: {}",
+                        line
+                    ))),
+                }
+            })
+            .collect()
+    }
+}
+
+/// The state persistence implementation of a file state persistence.
+impl StatePersistence for FileStatePersistence {
+    fn load(&self, node_id: u64, worker_id: u64) -> Result<Option<u64>, SpaceflakeError> {
+        let _lock = self.lock.lock().unwrap_or_else(PoisonError::into_inner);
+        Ok(self
+            .read()?
+            .into_iter()
+            .find(|(node, worker, _)| *node == node_id && *worker == worker_id)
+            .map(|(_, _, last_time)| last_time))
+    }
+
+    fn store(&self, node_id: u64, worker_id: u64, last_time: u64) -> Result<(), SpaceflakeError> {
+        let _lock = self.lock.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut states = self.read()?;
+        match states
+            .iter_mut()
+            .find(|(node, worker, _)| *node == node_id && *worker == worker_id)
+        {
+            Some(state) => state.2 = state.2.max(last_time),
+            None => states.push((node_id, worker_id, last_time)),
+        }
+        let content = states
+            .iter()
+            .map(|(node, worker, last_time)| format!("{},{},{}\n", node, worker, last_time))
+            .collect::<String>();
+
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        fs::write(&temporary, content)?;
+        fs::rename(&temporary, &self.path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, Thread};
+
+use crate::{IdGenerator, Spaceflake, SpaceflakeError};
+
+/// The state shared between an ID pool and its background thread.
+#[derive(Debug, Default)]
+struct PoolState {
+    /// The amount of Spaceflakes pre-generated and not taken yet.
+    available: AtomicUsize,
+    /// Whether the pool is dropped, so the background thread must stop.
+    stopped: AtomicBool,
+}
+
+/// A pool of pre-generated Spaceflakes, kept warm by a background thread, so taking a Spaceflake returns instantly
+/// instead of waiting for the next millisecond when the sequence is exhausted on bursty workloads.
+///
+/// The background thread fills the pool up to its capacity, then waits until the amount of Spaceflakes left drops to
+/// the refill watermark before filling it again. It stops after the first error of the generator, which is returned by
+/// [`IdPool::take`] once the Spaceflakes generated before are taken.
+#[derive(Debug)]
+pub struct IdPool {
+    /// The receiving end of the bounded channel holding the pre-generated Spaceflakes.
+    receiver: Mutex<Receiver<Result<Spaceflake, SpaceflakeError>>>,
+    /// The state shared with the background thread.
+    state: Arc<PoolState>,
+    /// The amount of Spaceflakes left at or below which the pool is refilled.
+    watermark: usize,
+    /// The background thread filling the pool.
+    producer: Thread,
+}
+
+/// The default implementation of an ID pool.
+impl IdPool {
+    /// Create a new ID pool holding up to the given capacity of Spaceflakes from the generator, refilled once the
+    /// amount of Spaceflakes left drops to the watermark.
+    ///
+    /// Returns an error if the capacity is `0`, if the watermark is not less than the capacity, or if the background
+    /// thread could not be spawned.
+    pub fn try_new<G>(
+        generator: G,
+        capacity: usize,
+        watermark: usize,
+    ) -> Result<Self, SpaceflakeError>
+    where
+        G: IdGenerator + Send + 'static,
+    {
+        if capacity == 0 {
+            return Err(SpaceflakeError::InvalidArgument(String::from(
+                "ID pool capacity must be greater than 0",
+            )));
+        }
+        if watermark >= capacity {
+            return Err(SpaceflakeError::InvalidArgument(String::from(
+                "ID pool refill watermark must be less than its capacity",
+            )));
+        }
+
+        let (sender, receiver) = sync_channel(capacity);
+        let state = Arc::new(PoolState::default());
+        let shared = state.clone();
+        let producer = thread::Builder::new()
+            .name(String::from("spaceflake-id-pool"))
+            .spawn(move || fill(generator, sender, &shared, capacity, watermark))
+            .map_err(|error| SpaceflakeError::Io(error.to_string()))?;
+
+        Ok(IdPool {
+            receiver: Mutex::new(receiver),
+            state,
+            watermark,
+            producer: producer.thread().clone(),
+        })
+    }
+
+    /// Take a pre-generated Spaceflake from the pool, only waiting if the pool is empty.
+    pub fn take(&self) -> Result<Spaceflake, SpaceflakeError> {
+        let result = self
+            .receiver
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .recv()
+            .map_err(|_| SpaceflakeError::Source(String::from("The ID pool is stopped")))?;
+        let left = self.state.available.fetch_sub(1, Ordering::AcqRel) - 1;
+        if left <= self.watermark {
+            self.producer.unpark();
+        }
+        result
+    }
+
+    /// Returns the amount of Spaceflakes currently pre-generated in the pool.
+    pub fn available(&self) -> usize {
+        self.state.available.load(Ordering::Acquire)
+    }
+}
+
+/// The generator implementation of an ID pool.
+impl IdGenerator for IdPool {
+    fn generate(&self) -> Result<Spaceflake, SpaceflakeError> {
+        self.take()
+    }
+}
+
+impl Drop for IdPool {
+    fn drop(&mut self) {
+        self.state.stopped.store(true, Ordering::Release);
+        self.producer.unpark();
+    }
+}
+
+/// Fill the pool up to its capacity with Spaceflakes from the generator, each time the amount of Spaceflakes left
+/// drops to the watermark, until the pool is dropped or the generator fails.
+fn fill<G: IdGenerator>(
+    generator: G,
+    sender: SyncSender<Result<Spaceflake, SpaceflakeError>>,
+    state: &PoolState,
+    capacity: usize,
+    watermark: usize,
+) {
+    loop {
+        while state.available.load(Ordering::Acquire) < capacity {
+            if state.stopped.load(Ordering::Acquire) {
+                return;
+            }
+            let result = generator.generate();
+            let failed = result.is_err();
+            state.available.fetch_add(1, Ordering::AcqRel);
+            if sender.send(result).is_err() || failed {
+                return;
+            }
+        }
+        while state.available.load(Ordering::Acquire) > watermark {
+            if state.stopped.load(Ordering::Acquire) {
+                return;
+            }
+            thread::park();
+        }
+    }
+}
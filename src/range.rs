@@ -0,0 +1,101 @@
+use std::ops::{Bound, Range, RangeBounds, RangeInclusive};
+use std::time::Duration;
+
+use crate::{Spaceflake, SpaceflakeError};
+
+/// The inclusive range of the IDs of the Spaceflakes generated in a range of timestamps, which can be handed directly
+/// to the range queries of a `BTreeMap` or to SQL builders, for example to paginate by creation time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SpaceflakeRange {
+    /// The first timestamp of the range, in milliseconds since the Unix epoch.
+    start_time: u64,
+    /// The timestamp after the last one of the range, in milliseconds since the Unix epoch.
+    end_time: u64,
+    /// The base epoch of the Spaceflakes.
+    base_epoch: u64,
+    /// The smallest ID of the range.
+    start: u64,
+    /// The largest ID of the range.
+    end: u64,
+}
+
+/// The default implementation of a Spaceflake range.
+impl SpaceflakeRange {
+    /// Create the range of the IDs of the Spaceflakes generated in the range of timestamps, in milliseconds since the
+    /// Unix epoch, see [`Spaceflake::range_for`].
+    ///
+    /// Returns an error if the range is empty, or if a timestamp is before the base epoch or does not fit in the bits
+    /// of the time.
+    pub fn new(range: Range<u64>, base_epoch: u64) -> Result<Self, SpaceflakeError> {
+        if range.is_empty() {
+            return Err(SpaceflakeError::InvalidTimeWindow(format!(
+                "The range of timestamps {}..{} is empty",
+                range.start, range.end
+            )));
+        }
+        Ok(SpaceflakeRange {
+            start_time: range.start,
+            end_time: range.end,
+            base_epoch,
+            start: Spaceflake::first_at(range.start, base_epoch)?.id,
+            end: Spaceflake::last_at(range.end - 1, base_epoch)?.id,
+        })
+    }
+
+    /// Returns the smallest ID of the range.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the largest ID of the range.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns the range of timestamps, in milliseconds since the Unix epoch, the range was created for.
+    pub fn times(&self) -> Range<u64> {
+        self.start_time..self.end_time
+    }
+
+    /// Returns an iterator over the consecutive ranges of the given width of time splitting the range, the last one
+    /// being shorter if the width does not divide the range, for example to query the IDs of each hour of a day.
+    ///
+    /// Returns an error if the width is shorter than a millisecond.
+    pub fn buckets(
+        &self,
+        width: Duration,
+    ) -> Result<impl Iterator<Item = SpaceflakeRange>, SpaceflakeError> {
+        let width = u64::try_from(width.as_millis()).unwrap_or(u64::MAX);
+        if width == 0 {
+            return Err(SpaceflakeError::InvalidTimeWindow(
+                "The width of the buckets is shorter than a millisecond".to_string(),
+            ));
+        }
+        let range = *self;
+        let mut start = Some(range.start_time);
+        Ok(std::iter::from_fn(move || {
+            let bucket_start = start?;
+            let bucket_end = bucket_start.saturating_add(width).min(range.end_time);
+            start = Some(bucket_end).filter(|&end| end < range.end_time);
+            SpaceflakeRange::new(bucket_start..bucket_end, range.base_epoch).ok()
+        }))
+    }
+}
+
+/// The bounds of a Spaceflake range, both included.
+impl RangeBounds<u64> for SpaceflakeRange {
+    fn start_bound(&self) -> Bound<&u64> {
+        Bound::Included(&self.start)
+    }
+
+    fn end_bound(&self) -> Bound<&u64> {
+        Bound::Included(&self.end)
+    }
+}
+
+/// Converts a Spaceflake range into the inclusive range of its IDs.
+impl From<SpaceflakeRange> for RangeInclusive<u64> {
+    fn from(range: SpaceflakeRange) -> Self {
+        range.start..=range.end
+    }
+}
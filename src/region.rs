@@ -0,0 +1,115 @@
+use std::sync::{PoisonError, RwLock};
+
+use crate::{generate, BitLayout, GeneratorSettings, Spaceflake, SpaceflakeError};
+
+/// The amount of high bits of the node ID dedicated to the region code.
+pub const REGION_BITS: u64 = 2;
+
+/// The registry of the region names and their codes, see [`register_regions`].
+static REGIONS: RwLock<Vec<(String, u64)>> = RwLock::new(Vec::new());
+
+/// Register the region names and their codes, replacing the previous ones, for example `&[("eu", 1), ("us", 2)]`.
+///
+/// The region code is stored in the high [`REGION_BITS`] of the node ID, so data-residency routing can be done from
+/// the Spaceflake alone.
+///
+/// Returns an error if a region code does not fit in [`REGION_BITS`], or if a name or a code is registered twice.
+pub fn register_regions(regions: &[(&str, u64)]) -> Result<(), SpaceflakeError> {
+    for (i, (name, code)) in regions.iter().enumerate() {
+        if *code >> REGION_BITS != 0 {
+            return Err(SpaceflakeError::InvalidArgument(format!(
+                "Region code of {} must be less than {}",
+                name,
+                1 << REGION_BITS
+            )));
+        }
+        if let Some((other, _)) = regions[..i]
+            .iter()
+            .find(|(other, other_code)| other == name || other_code == code)
+        {
+            return Err(SpaceflakeError::InvalidArgument(format!(
+                "Region {} conflicts with region {}",
+                name, other
+            )));
+        }
+    }
+    *REGIONS.write().unwrap_or_else(PoisonError::into_inner) = regions
+        .iter()
+        .map(|(name, code)| (name.to_string(), *code))
+        .collect();
+    Ok(())
+}
+
+/// Returns the code of a registered region.
+pub fn region_code(region: &str) -> Option<u64> {
+    REGIONS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .find(|(name, _)| name == region)
+        .map(|(_, code)| *code)
+}
+
+/// Returns the name of the registered region with the given code.
+pub fn region_name(code: u64) -> Option<String> {
+    REGIONS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .find(|(_, c)| *c == code)
+        .map(|(name, _)| name.clone())
+}
+
+/// Returns the amount of low bits of the node ID left for the node itself when using regions with the layout.
+fn region_node_bits(layout: &BitLayout) -> Result<u64, SpaceflakeError> {
+    layout.node_bits.checked_sub(REGION_BITS).ok_or_else(|| {
+        SpaceflakeError::InvalidArgument(format!(
+            "Layout must have at least {} node bits to store the region code",
+            REGION_BITS
+        ))
+    })
+}
+
+/// Returns the node ID of a node in a registered region for the given layout, to create nodes with
+/// [`crate::Node::try_new`].
+///
+/// The node itself can only use the low bits of the node ID that are not dedicated to the region code.
+pub fn region_node_id(
+    region: &str,
+    node_id: u64,
+    layout: BitLayout,
+) -> Result<u64, SpaceflakeError> {
+    let node_bits = region_node_bits(&layout)?;
+    let code = region_code(region)
+        .ok_or_else(|| SpaceflakeError::InvalidArgument(format!("Unknown region: {}", region)))?;
+    if node_id >> node_bits != 0 {
+        return Err(SpaceflakeError::InvalidNodeId {
+            max: 1 << node_bits,
+        });
+    }
+    Ok((code << node_bits) | node_id)
+}
+
+/// Generate a Spaceflake for the given settings in a registered region, by storing the region code in the high bits
+/// of the node ID of the layout of the settings.
+pub fn generate_in_region(
+    settings: GeneratorSettings,
+    region: &str,
+) -> Result<Spaceflake, SpaceflakeError> {
+    let mut settings = settings;
+    settings.node_id = region_node_id(region, settings.node_id, settings.layout)?;
+    generate(settings)
+}
+
+/// The region implementation of a Spaceflake.
+impl Spaceflake {
+    /// Returns the region code stored in the high bits of the node ID of the layout of the Spaceflake.
+    pub fn region_code(&self) -> u64 {
+        self.node_id() >> self.layout().node_bits.saturating_sub(REGION_BITS)
+    }
+
+    /// Returns the name of the registered region the Spaceflake was generated in, if any.
+    pub fn region(&self) -> Option<String> {
+        region_name(self.region_code())
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, PoisonError};
+
+use crate::{IdGenerator, Spaceflake, SpaceflakeError, Worker};
+
+/// A source of blocks of Spaceflake IDs, usually a client of a remote Spaceflake server, used by a
+/// [`RemoteGenerator`].
+pub trait RemoteSource {
+    /// Fetch a block of up to `amount` unique Spaceflake IDs, in ascending order.
+    fn fetch_block(&self, amount: usize) -> Result<Vec<u64>, SpaceflakeError>;
+}
+
+/// A generator serving Spaceflakes fetched in blocks from a [`RemoteSource`], from a local buffer.
+///
+/// When the remote source is unreachable, the Spaceflakes are generated by the fallback worker, if any.
+#[derive(Debug)]
+pub struct RemoteGenerator<S: RemoteSource> {
+    /// The source the blocks of Spaceflake IDs are fetched from.
+    source: S,
+    /// The amount of Spaceflake IDs fetched at once.
+    block_size: usize,
+    /// The base epoch the remote Spaceflakes were generated with.
+    base_epoch: u64,
+    /// The worker generating Spaceflakes when the remote source is unreachable.
+    fallback: Option<Worker>,
+    /// The Spaceflake IDs fetched but not served yet.
+    buffer: Mutex<VecDeque<u64>>,
+}
+
+/// The default implementation of a remote generator.
+impl<S: RemoteSource> RemoteGenerator<S> {
+    /// Create a new remote generator fetching blocks of the given size from the source, for Spaceflakes generated with
+    /// the given base epoch.
+    ///
+    /// Returns an error if the block size is `0`.
+    pub fn try_new(source: S, block_size: usize, base_epoch: u64) -> Result<Self, SpaceflakeError> {
+        if block_size == 0 {
+            return Err(SpaceflakeError::InvalidArgument(String::from(
+                "Block size must be greater than 0",
+            )));
+        }
+
+        Ok(RemoteGenerator {
+            source,
+            block_size,
+            base_epoch,
+            fallback: None,
+            buffer: Mutex::new(VecDeque::with_capacity(block_size)),
+        })
+    }
+
+    /// Set the worker generating Spaceflakes when the remote source is unreachable.
+    pub fn with_fallback(mut self, worker: Worker) -> Self {
+        self.fallback = Some(worker);
+        self
+    }
+
+    /// Returns the amount of Spaceflakes that can be served before fetching a new block.
+    pub fn buffered(&self) -> usize {
+        self.buffer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+}
+
+/// The generator implementation of a remote generator.
+impl<S: RemoteSource> IdGenerator for RemoteGenerator<S> {
+    fn generate(&self) -> Result<Spaceflake, SpaceflakeError> {
+        let buffered = self
+            .buffer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop_front();
+        if let Some(id) = buffered {
+            return Ok(Spaceflake::new(id, self.base_epoch));
+        }
+
+        // The buffer is not locked while fetching, so a slow remote source does not block the other callers.
+        let mut block = match self.source.fetch_block(self.block_size) {
+            Ok(block) => block.into_iter(),
+            Err(error) => {
+                return match &self.fallback {
+                    Some(worker) => worker.generate(),
+                    None => Err(error),
+                }
+            }
+        };
+        match block.next() {
+            Some(id) => {
+                self.buffer
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .extend(block);
+                Ok(Spaceflake::new(id, self.base_epoch))
+            }
+            None => match &self.fallback {
+                Some(worker) => worker.generate(),
+                None => Err(SpaceflakeError::Source(String::from(
+                    "Remote source returned an empty block",
+                ))),
+            },
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use sea_orm::sea_query::{ArrayType, ColumnType, Nullable, ValueType, ValueTypeErr};
+use sea_orm::{
+    ActiveValue, ColIdx, DbErr, QueryResult, TryFromU64, TryGetError, TryGetable, Value,
+};
+
+use crate::{Spaceflake, Worker, EPOCH};
+
+/// Converts a Spaceflake into a SeaORM value, its ID in a `BIGINT`.
+impl From<Spaceflake> for Value {
+    fn from(spaceflake: Spaceflake) -> Self {
+        Value::BigInt(Some(spaceflake.as_i64()))
+    }
+}
+
+/// The SeaORM value type of a Spaceflake generated with the default [`EPOCH`], from its ID in a `BIGINT`.
+impl ValueType for Spaceflake {
+    fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+        match v {
+            Value::BigInt(Some(id)) => {
+                Spaceflake::try_from_i64(id, EPOCH).map_err(|_| ValueTypeErr)
+            }
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        "Spaceflake".to_string()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::BigInt
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::BigInteger
+    }
+}
+
+/// The SeaORM null value of an optional Spaceflake.
+impl Nullable for Spaceflake {
+    fn null() -> Value {
+        Value::BigInt(None)
+    }
+}
+
+/// Reads a Spaceflake generated with the default [`EPOCH`] from a SeaORM query result, from its ID in a `BIGINT`.
+impl TryGetable for Spaceflake {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        Spaceflake::try_from_i64(i64::try_get_by(res, index)?, EPOCH)
+            .map_err(|error| TryGetError::DbErr(DbErr::Type(error.to_string())))
+    }
+}
+
+/// Converts the last inserted ID of a SeaORM insert into a Spaceflake generated with the default [`EPOCH`].
+impl TryFromU64 for Spaceflake {
+    fn try_from_u64(n: u64) -> Result<Self, DbErr> {
+        <Spaceflake as TryFrom<u64>>::try_from(n).map_err(|_| DbErr::ConvertFromU64("Spaceflake"))
+    }
+}
+
+/// The SeaORM implementation of a worker.
+impl Worker {
+    /// Returns a new Spaceflake as a set SeaORM active value, for example to populate the ID of new rows in
+    /// `ActiveModelBehavior::before_save`.
+    pub fn active_value(&self) -> Result<ActiveValue<Spaceflake>, DbErr> {
+        self.generate()
+            .map(ActiveValue::Set)
+            .map_err(|error| DbErr::Custom(error.to_string()))
+    }
+}
@@ -0,0 +1,71 @@
+use std::fmt;
+
+use ::serde::de::{self, Deserialize, Deserializer, Visitor};
+use ::serde::ser::{Serialize, Serializer};
+
+use crate::Spaceflake;
+
+/// The `serde` serialization of a Spaceflake, which is its ID as a number.
+impl Serialize for Spaceflake {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.id)
+    }
+}
+
+/// The `serde` deserialization of a Spaceflake, from its ID as a number or as a decimal string.
+///
+/// The Spaceflake is assumed to be generated with the default [`crate::EPOCH`].
+impl<'de> Deserialize<'de> for Spaceflake {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = deserializer.deserialize_any(IdVisitor)?;
+        Spaceflake::try_from(id).map_err(de::Error::custom)
+    }
+}
+
+/// The visitor of a Spaceflake ID, as a number or as a decimal string.
+struct IdVisitor;
+
+impl Visitor<'_> for IdVisitor {
+    type Value = u64;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a Spaceflake ID as a number or a decimal string")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<u64, E> {
+        Ok(value)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<u64, E> {
+        u64::try_from(value).map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<u64, E> {
+        value
+            .parse()
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+}
+
+/// Serialize Spaceflakes as their ID in a decimal string, for JavaScript clients whose numbers can not hold 64-bit IDs
+/// without losing precision.
+///
+/// Use it on a field with `#[serde(with = "spaceflake::serde::string")]`, deserializing also accepts a number.
+pub mod string {
+    use ::serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::Spaceflake;
+
+    /// Serialize the Spaceflake as its ID in a decimal string.
+    pub fn serialize<S: Serializer>(
+        spaceflake: &Spaceflake,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&spaceflake.id)
+    }
+
+    /// Deserialize a Spaceflake from its ID as a decimal string, or as a number.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Spaceflake, D::Error> {
+        Spaceflake::deserialize(deserializer)
+    }
+}
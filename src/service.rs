@@ -0,0 +1,165 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use http::header::{HeaderName, HeaderValue};
+use http::Request;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{Node, Spaceflake, SpaceflakeError, Worker, REQUEST_ID_HEADER};
+
+/// The future of the `tower` services generating Spaceflakes, which waits for the next millisecond with
+/// [`tokio::time::sleep`] instead of blocking the thread.
+pub type GenerateFuture<T> = Pin<Box<dyn Future<Output = Result<T, SpaceflakeError>> + Send>>;
+
+/// A request to generate a single Spaceflake with a `tower` service.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GenerateRequest;
+
+/// A request to generate an amount of Spaceflakes at once with a `tower` service.
+#[derive(Debug, Clone, Copy)]
+pub struct BulkGenerateRequest {
+    /// The amount of Spaceflakes to generate.
+    pub amount: usize,
+}
+
+/// The `tower` service implementation of a worker, to generate a single Spaceflake.
+impl Service<GenerateRequest> for Worker {
+    type Response = Spaceflake;
+    type Error = SpaceflakeError;
+    type Future = GenerateFuture<Spaceflake>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), SpaceflakeError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _: GenerateRequest) -> Self::Future {
+        let worker = self.clone();
+        Box::pin(async move { worker.generate_async().await })
+    }
+}
+
+/// The `tower` service implementation of a worker, to generate an amount of Spaceflakes at once.
+impl Service<BulkGenerateRequest> for Worker {
+    type Response = Vec<Spaceflake>;
+    type Error = SpaceflakeError;
+    type Future = GenerateFuture<Vec<Spaceflake>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), SpaceflakeError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: BulkGenerateRequest) -> Self::Future {
+        let worker = self.clone();
+        Box::pin(async move { worker.bulk_generate_async(request.amount).await })
+    }
+}
+
+/// The `tower` service implementation of a node, to generate an amount of Spaceflakes at once.
+impl Service<BulkGenerateRequest> for Node {
+    type Response = Vec<Spaceflake>;
+    type Error = SpaceflakeError;
+    type Future = GenerateFuture<Vec<Spaceflake>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), SpaceflakeError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: BulkGenerateRequest) -> Self::Future {
+        let node = Node::unchecked(self.id);
+        Box::pin(async move { node.bulk_generate_async(request.amount).await })
+    }
+}
+
+/// A `tower` layer stamping every request with a Spaceflake request ID, see [`SetSpaceflakeRequestId`].
+#[derive(Debug, Clone)]
+pub struct SetSpaceflakeRequestIdLayer {
+    /// The worker generating the request IDs.
+    worker: Worker,
+}
+
+/// The default implementation of a request ID layer.
+impl SetSpaceflakeRequestIdLayer {
+    /// Create a new layer generating the request IDs with the given worker, sharing its sequence with its clones.
+    pub fn new(worker: Worker) -> Self {
+        SetSpaceflakeRequestIdLayer { worker }
+    }
+}
+
+/// The `tower` layer implementation of a request ID layer.
+impl<S> Layer<S> for SetSpaceflakeRequestIdLayer {
+    type Service = SetSpaceflakeRequestId<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SetSpaceflakeRequestId {
+            inner,
+            worker: self.worker.clone(),
+        }
+    }
+}
+
+/// A `tower` middleware generating a Spaceflake per request, injecting it in the [`REQUEST_ID_HEADER`] header and
+/// exposing it in the extensions of the request, so the request IDs are sortable by time.
+///
+/// Requests that already have a request ID header are left untouched, and if the generation fails, the request is
+/// passed through without a request ID. When the sequence of the worker is exhausted, the request waits for the next
+/// millisecond with [`tokio::time::sleep`] instead of blocking the thread.
+#[derive(Debug, Clone)]
+pub struct SetSpaceflakeRequestId<S> {
+    /// The wrapped service.
+    inner: S,
+    /// The worker generating the request IDs.
+    worker: Worker,
+}
+
+/// Inject the Spaceflake in the request ID header and the extensions of the request.
+fn set_request_id<B>(request: &mut Request<B>, id: Spaceflake) {
+    request.headers_mut().insert(
+        HeaderName::from_static(REQUEST_ID_HEADER),
+        HeaderValue::from(id.id),
+    );
+    request.extensions_mut().insert(id);
+}
+
+/// The `tower` service implementation of a request ID middleware.
+impl<S, B> Service<Request<B>> for SetSpaceflakeRequestId<S>
+where
+    S: Service<Request<B>> + Clone + Send + 'static,
+    S::Future: Send,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        if request.headers().contains_key(REQUEST_ID_HEADER) {
+            return Box::pin(self.inner.call(request));
+        }
+        match self.worker.try_generate() {
+            Ok(id) => {
+                set_request_id(&mut request, id);
+                Box::pin(self.inner.call(request))
+            }
+            Err(SpaceflakeError::WouldBlock { .. }) => {
+                // The inner service driven to readiness is taken, as the request is only passed to it once the
+                // Spaceflake is generated, and replaced by a clone.
+                let clone = self.inner.clone();
+                let mut inner = std::mem::replace(&mut self.inner, clone);
+                let worker = self.worker.clone();
+                Box::pin(async move {
+                    if let Ok(id) = worker.generate_async().await {
+                        set_request_id(&mut request, id);
+                    }
+                    inner.call(request).await
+                })
+            }
+            Err(_) => Box::pin(self.inner.call(request)),
+        }
+    }
+}
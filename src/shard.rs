@@ -0,0 +1,234 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::{BitLayout, SpaceflakeError, EPOCH};
+
+/// A version of the assignments of a [`ShardMap`], effective from a point in time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardMapVersion {
+    /// The time, in milliseconds since the Unix epoch, from which the assignments are effective.
+    pub effective_from: u64,
+    /// The assignments of the node and worker IDs to the logical shards, as `(shard, node_id, worker_id)`.
+    pub assignments: Vec<(u32, u64, u64)>,
+}
+
+/// A map of logical shards to the node and worker IDs generating the Spaceflakes of each shard, for data placement.
+///
+/// The map is versioned, so rebalancing the shards does not move the Spaceflakes generated before: each Spaceflake is
+/// resolved with the version that was effective at its time.
+///
+/// It can be loaded from a configuration where every version starts with a `[version <effective_from>]` header,
+/// followed by one `<shard> = <node_id>:<worker_id>, ...` line per shard, for example:
+///
+/// ```text
+/// [version 1420070400000]
+/// 0 = 1:1, 1:2
+/// 1 = 2:1
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardMap {
+    /// The base epoch the Spaceflakes were generated with, default is [`EPOCH`].
+    pub base_epoch: u64,
+    /// The layout of the bits of the Spaceflakes, default is [`BitLayout::SPACEFLAKE`], which the assignments are
+    /// validated against when they are added.
+    pub layout: BitLayout,
+    /// The versions of the assignments, ordered by the time from which they are effective.
+    versions: Vec<ShardMapVersion>,
+}
+
+/// The default implementation of a shard map.
+impl ShardMap {
+    /// Create a new shard map with the initial assignments, effective from the given time.
+    pub fn new(
+        effective_from: u64,
+        assignments: Vec<(u32, u64, u64)>,
+    ) -> Result<Self, SpaceflakeError> {
+        Self::with_layout(effective_from, assignments, BitLayout::SPACEFLAKE)
+    }
+
+    /// Create a new shard map of Spaceflakes with the given bit layout, with the initial assignments, effective from
+    /// the given time.
+    pub fn with_layout(
+        effective_from: u64,
+        assignments: Vec<(u32, u64, u64)>,
+        layout: BitLayout,
+    ) -> Result<Self, SpaceflakeError> {
+        let mut map = ShardMap {
+            base_epoch: EPOCH,
+            layout,
+            versions: Vec::new(),
+        };
+        map.rebalance(effective_from, assignments)?;
+        Ok(map)
+    }
+
+    /// Load a shard map from its configuration, see [`ShardMap`] for the format.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn from_config(config: &str) -> Result<Self, SpaceflakeError> {
+        Self::from_config_with_layout(config, BitLayout::SPACEFLAKE)
+    }
+
+    /// Load a shard map of Spaceflakes with the given bit layout from its configuration, see [`ShardMap::from_config`].
+    pub fn from_config_with_layout(
+        config: &str,
+        layout: BitLayout,
+    ) -> Result<Self, SpaceflakeError> {
+        let mut map = ShardMap {
+            base_epoch: EPOCH,
+            layout,
+            versions: Vec::new(),
+        };
+        let mut pending: Option<ShardMapVersion> = None;
+        for (number, line) in config.lines().enumerate() {
+            let line = line.trim();
+            let invalid = || {
+                SpaceflakeError::InvalidArgument(format!(
+                    "Invalid shard map configuration at line {}",
+                    number + 1
+                ))
+            };
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(header) = line
+                .strip_prefix("[version ")
+                .and_then(|header| header.strip_suffix(']'))
+            {
+                if let Some(version) = pending.take() {
+                    map.rebalance(version.effective_from, version.assignments)?;
+                }
+                pending = Some(ShardMapVersion {
+                    effective_from: header.trim().parse().map_err(|_| invalid())?,
+                    assignments: Vec::new(),
+                });
+                continue;
+            }
+
+            let version = pending.as_mut().ok_or_else(invalid)?;
+            let (shard, generators) = line.split_once('=').ok_or_else(invalid)?;
+            let shard = shard.trim().parse::<u32>().map_err(|_| invalid())?;
+            for generator in generators.split(',') {
+                let (node_id, worker_id) = generator.trim().split_once(':').ok_or_else(invalid)?;
+                version.assignments.push((
+                    shard,
+                    node_id.parse().map_err(|_| invalid())?,
+                    worker_id.parse().map_err(|_| invalid())?,
+                ));
+            }
+        }
+        match pending {
+            Some(version) => map.rebalance(version.effective_from, version.assignments)?,
+            None => {
+                return Err(SpaceflakeError::InvalidArgument(String::from(
+                    "Shard map configuration has no version",
+                )))
+            }
+        }
+        Ok(map)
+    }
+
+    /// Add a new version of the assignments, effective from the given time, which must be later than the one of the
+    /// current version.
+    pub fn rebalance(
+        &mut self,
+        effective_from: u64,
+        assignments: Vec<(u32, u64, u64)>,
+    ) -> Result<(), SpaceflakeError> {
+        if let Some(current) = self.versions.last() {
+            if effective_from <= current.effective_from {
+                return Err(SpaceflakeError::InvalidArgument(format!(
+                    "Rebalancing must be effective after the current version, which is effective from {}",
+                    current.effective_from
+                )));
+            }
+        }
+        let mut generators = HashSet::new();
+        for (shard, node_id, worker_id) in &assignments {
+            if *node_id > self.layout.max_node_id() {
+                return Err(SpaceflakeError::InvalidNodeId {
+                    max: self.layout.max_node_id(),
+                });
+            }
+            if *worker_id > self.layout.max_worker_id() {
+                return Err(SpaceflakeError::InvalidWorkerId {
+                    max: self.layout.max_worker_id(),
+                });
+            }
+            if !generators.insert((*node_id, *worker_id)) {
+                return Err(SpaceflakeError::InvalidArgument(format!(
+                    "Node ID {} and worker ID {} can not be assigned to shard {} and to another one",
+                    node_id, worker_id, shard
+                )));
+            }
+        }
+
+        self.versions.push(ShardMapVersion {
+            effective_from,
+            assignments,
+        });
+        Ok(())
+    }
+
+    /// Returns the versions of the assignments, ordered by the time from which they are effective.
+    pub fn versions(&self) -> &[ShardMapVersion] {
+        &self.versions
+    }
+
+    /// Returns the node and worker IDs currently assigned to the shard, to generate its Spaceflakes.
+    pub fn assignments(&self, shard: u32) -> Vec<(u64, u64)> {
+        self.versions
+            .last()
+            .map(|version| {
+                version
+                    .assignments
+                    .iter()
+                    .filter(|(s, _, _)| *s == shard)
+                    .map(|(_, node_id, worker_id)| (*node_id, *worker_id))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the shard of a Spaceflake ID, resolved with the version that was effective at its time, if its node and
+    /// worker IDs were assigned to one.
+    pub fn shard_of(&self, spaceflake_id: u64) -> Option<u32> {
+        let time = self.layout.parse_time(spaceflake_id, self.base_epoch);
+        let node_id = self.layout.parse_node_id(spaceflake_id);
+        let worker_id = self.layout.parse_worker_id(spaceflake_id);
+        self.versions
+            .iter()
+            .rev()
+            .find(|version| version.effective_from <= time)?
+            .assignments
+            .iter()
+            .find(|(_, n, w)| *n == node_id && *w == worker_id)
+            .map(|(shard, _, _)| *shard)
+    }
+}
+
+/// The display implementation of a shard map, which is its configuration.
+impl fmt::Display for ShardMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for version in &self.versions {
+            writeln!(f, "[version {}]", version.effective_from)?;
+            let mut shards = version
+                .assignments
+                .iter()
+                .map(|(shard, _, _)| *shard)
+                .collect::<Vec<u32>>();
+            shards.sort_unstable();
+            shards.dedup();
+            for shard in shards {
+                let generators = version
+                    .assignments
+                    .iter()
+                    .filter(|(s, _, _)| *s == shard)
+                    .map(|(_, node_id, worker_id)| format!("{}:{}", node_id, worker_id))
+                    .collect::<Vec<String>>();
+                writeln!(f, "{} = {}", shard, generators.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{GeneratorSettings, MAX_VERSION_BITS};
+
+/// Returns the SQL expression of the version tag and the maximum sequence for the settings.
+fn version_and_max_sequence(settings: &GeneratorSettings) -> (u64, u64) {
+    let layout = settings.layout;
+    let version_bits = settings
+        .version_bits
+        .min(MAX_VERSION_BITS)
+        .min(layout.sequence_bits.saturating_sub(1));
+    (
+        settings.version << layout.sequence_bits.saturating_sub(version_bits),
+        layout.max_sequence() >> version_bits,
+    )
+}
+
+/// Returns the SQL expression of a Spaceflake for the settings' layout, from the expressions of the milliseconds since
+/// the base epoch, of the integer division and of the sequence.
+fn id_expression(settings: &GeneratorSettings, elapsed: &str, div: &str, sequence: &str) -> String {
+    let layout = settings.layout;
+    let (version, _) = version_and_max_sequence(settings);
+    let time = match layout.time_unit.as_millis() {
+        1 => elapsed.to_string(),
+        unit => format!("({} {} {})", elapsed, div, unit),
+    };
+    let sequence = match layout.sequence_shift() {
+        0 => format!("{} | {}", version, sequence),
+        shift => format!("(({} | {}) << {})", version, sequence, shift),
+    };
+    format!(
+        "({} << {}) | ({} << {}) | ({} << {}) | {}",
+        time,
+        layout.time_shift(),
+        settings.node_id,
+        layout.node_shift(),
+        settings.worker_id,
+        layout.worker_shift(),
+        sequence
+    )
+}
+
+/// Returns a Postgres snippet creating a sequence and a function, both named after the given name, that mint
+/// Spaceflakes for the settings' base epoch, node ID, worker ID, version tag and bit layout.
+///
+/// The sequence of the Spaceflakes is taken from the Postgres sequence, wrapping within the sequence bits.
+///
+/// # Example
+///
+/// ```rust
+/// fn main() {
+///     let settings = spaceflake::GeneratorSettings::try_new(1, 1).unwrap();
+///     println!("{}", spaceflake::sql::postgres_function(&settings, "spaceflake_next"));
+///     println!("{}", spaceflake::sql::postgres_default("spaceflake_next"));
+/// }
+/// ```
+pub fn postgres_function(settings: &GeneratorSettings, name: &str) -> String {
+    let (_, max_sequence) = version_and_max_sequence(settings);
+    format!(
+        "CREATE SEQUENCE IF NOT EXISTS {name}_sequence;
+
+CREATE OR REPLACE FUNCTION {name}(OUT result bigint) AS $$
+DECLARE
+    base_epoch bigint := {base_epoch};
+    now_ms bigint;
+    seq bigint;
+BEGIN
+    SELECT nextval('{name}_sequence') % {max_sequence} + 1 INTO seq;
+    SELECT FLOOR(EXTRACT(EPOCH FROM clock_timestamp()) * 1000) INTO now_ms;
+    result := {id};
+END;
+$$ LANGUAGE plpgsql;",
+        name = name,
+        base_epoch = settings.base_epoch,
+        max_sequence = max_sequence,
+        id = id_expression(settings, "(now_ms - base_epoch)", "/", "seq"),
+    )
+}
+
+/// Returns the Postgres `DEFAULT` expression of a column, calling the function created by [`postgres_function`].
+pub fn postgres_default(name: &str) -> String {
+    format!("DEFAULT {}()", name)
+}
+
+/// Returns a MySQL snippet creating a trigger, named after the given name, that mints a Spaceflake for the given column
+/// of the table on insert, unless a value is provided, for the settings' base epoch, node ID, worker ID, version tag
+/// and bit layout.
+///
+/// MySQL has no sequences, so the sequence of the Spaceflakes is random rather than incremented like with
+/// [`crate::generate`], and rows inserted in the same millisecond may collide.
+pub fn mysql_trigger(
+    settings: &GeneratorSettings,
+    name: &str,
+    table: &str,
+    column: &str,
+) -> String {
+    let (_, max_sequence) = version_and_max_sequence(settings);
+    let elapsed = format!(
+        "(CAST(UNIX_TIMESTAMP(NOW(3)) * 1000 AS UNSIGNED) - {})",
+        settings.base_epoch
+    );
+    let sequence = format!("(FLOOR(RAND() * {}) + 1)", max_sequence);
+    format!(
+        "CREATE TRIGGER {name} BEFORE INSERT ON {table}
+FOR EACH ROW
+BEGIN
+    IF NEW.{column} IS NULL THEN
+        SET NEW.{column} = {id};
+    END IF;
+END;",
+        name = name,
+        table = table,
+        column = column,
+        id = id_expression(settings, &elapsed, "DIV", &sequence),
+    )
+}
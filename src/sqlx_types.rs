@@ -0,0 +1,41 @@
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::{Database, Decode, Encode, Type};
+
+use crate::{Spaceflake, EPOCH};
+
+/// The `sqlx` type of a Spaceflake, a `BIGINT`.
+impl<DB: Database> Type<DB> for Spaceflake
+where
+    i64: Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <i64 as Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <i64 as Type<DB>>::compatible(ty)
+    }
+}
+
+/// The `sqlx` encoding of a Spaceflake, as its ID in a `BIGINT`.
+impl<'q, DB: Database> Encode<'q, DB> for Spaceflake
+where
+    i64: Encode<'q, DB>,
+{
+    fn encode_by_ref(&self, buf: &mut DB::ArgumentBuffer<'q>) -> Result<IsNull, BoxDynError> {
+        self.as_i64().encode_by_ref(buf)
+    }
+}
+
+/// The `sqlx` decoding of a Spaceflake generated with the default [`EPOCH`], from its ID in a `BIGINT`.
+///
+/// Returns an error if the ID is negative.
+impl<'r, DB: Database> Decode<'r, DB> for Spaceflake
+where
+    i64: Decode<'r, DB>,
+{
+    fn decode(value: DB::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(Spaceflake::try_from_i64(i64::decode(value)?, EPOCH)?)
+    }
+}
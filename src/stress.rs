@@ -0,0 +1,160 @@
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bloom::RollingBloom;
+use crate::{Node, SpaceflakeError, MAX_5_BITS};
+
+/// The configuration of a [`soak_test`].
+#[derive(Debug, Clone, Copy)]
+pub struct SoakConfig {
+    /// The wall-clock duration of the test.
+    pub duration: Duration,
+    /// The amount of threads generating Spaceflakes, each with its own worker, up to 31.
+    pub threads: usize,
+    /// The node ID the workers are created on.
+    pub node_id: u64,
+    /// The amount of Spaceflakes each of the two rolling bloom filters holds before they are rotated, which bounds the
+    /// memory used to check the uniqueness.
+    pub bloom_capacity: usize,
+}
+
+/// The default implementation of a soak configuration.
+impl Default for SoakConfig {
+    fn default() -> Self {
+        SoakConfig {
+            duration: Duration::from_secs(60),
+            threads: 4,
+            node_id: 1,
+            bloom_capacity: 10_000_000,
+        }
+    }
+}
+
+/// The report of a [`soak_test`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SoakReport {
+    /// The actual duration of the test, in milliseconds.
+    pub duration_ms: u64,
+    /// The amount of threads that generated Spaceflakes.
+    pub threads: usize,
+    /// The amount of Spaceflakes generated.
+    pub generated: u64,
+    /// The amount of Spaceflakes generated per second.
+    pub per_second: f64,
+    /// The amount of failed generations.
+    pub errors: u64,
+    /// The amount of Spaceflakes that were not greater than the previous one of the same worker.
+    pub monotonicity_violations: u64,
+    /// The amount of Spaceflakes that may have been generated before, false positives of the bloom filters included.
+    ///
+    /// They are not taken into account by [`SoakReport::passed`], as some of them may be unique, but a count well
+    /// above the false positive rate of the bloom filters is worth investigating.
+    pub possible_duplicates: u64,
+}
+
+/// The default implementation of a soak report.
+impl SoakReport {
+    /// Returns whether no error nor monotonicity violation was found, not taking the possible duplicates into
+    /// account, see [`SoakReport::possible_duplicates`].
+    pub fn passed(&self) -> bool {
+        self.errors == 0 && self.monotonicity_violations == 0
+    }
+
+    /// Returns the report as JSON.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"duration_ms\":{},\"threads\":{},\"generated\":{},\"per_second\":{:.2},\"errors\":{},\"monotonicity_violations\":{},\"possible_duplicates\":{},\"passed\":{}}}",
+            self.duration_ms,
+            self.threads,
+            self.generated,
+            self.per_second,
+            self.errors,
+            self.monotonicity_violations,
+            self.possible_duplicates,
+            self.passed()
+        )
+    }
+}
+
+/// The amount of Spaceflakes a thread generates before checking them against the bloom filters.
+const BATCH_SIZE: usize = 1024;
+
+/// Generate Spaceflakes on multiple threads for the configured duration, continuously checking that they are unique
+/// and increasing for each worker, and report the results.
+///
+/// The uniqueness is checked with rolling bloom filters, so the memory stays bounded however long the test runs.
+pub fn soak_test(config: SoakConfig) -> Result<SoakReport, SpaceflakeError> {
+    if config.threads == 0 || config.threads as u64 > MAX_5_BITS {
+        return Err(SpaceflakeError::InvalidArgument(format!(
+            "Amount of threads must be between 1 and {}",
+            MAX_5_BITS
+        )));
+    }
+    if config.bloom_capacity == 0 {
+        return Err(SpaceflakeError::InvalidArgument(String::from(
+            "Bloom capacity must be greater than 0",
+        )));
+    }
+
+    let mut node = Node::try_new(config.node_id)?;
+    let bloom = Arc::new(Mutex::new(RollingBloom::new(config.bloom_capacity)));
+    let start = Instant::now();
+    let handles = (0..config.threads)
+        .map(|_| {
+            let worker = node.new_worker();
+            let bloom = bloom.clone();
+            thread::spawn(move || {
+                let mut report = SoakReport::default();
+                let mut last = 0;
+                let mut batch = Vec::<u64>::with_capacity(BATCH_SIZE);
+                while start.elapsed() < config.duration {
+                    match worker.generate() {
+                        Ok(spaceflake) => {
+                            report.generated += 1;
+                            if spaceflake.id <= last {
+                                report.monotonicity_violations += 1;
+                            }
+                            last = spaceflake.id;
+                            batch.push(spaceflake.id);
+                        }
+                        Err(_) => report.errors += 1,
+                    }
+                    if batch.len() == BATCH_SIZE {
+                        let mut bloom = bloom.lock().unwrap_or_else(PoisonError::into_inner);
+                        for id in batch.drain(..) {
+                            if bloom.check_and_insert(id) {
+                                report.possible_duplicates += 1;
+                            }
+                        }
+                    }
+                }
+                let mut bloom = bloom.lock().unwrap_or_else(PoisonError::into_inner);
+                for id in batch {
+                    if bloom.check_and_insert(id) {
+                        report.possible_duplicates += 1;
+                    }
+                }
+                report
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut report = SoakReport {
+        threads: config.threads,
+        ..Default::default()
+    };
+    for handle in handles {
+        let thread_report = handle
+            .join()
+            .map_err(|_| SpaceflakeError::Source(String::from("A soak test thread panicked")))?;
+        report.generated += thread_report.generated;
+        report.errors += thread_report.errors;
+        report.monotonicity_violations += thread_report.monotonicity_violations;
+        report.possible_duplicates += thread_report.possible_duplicates;
+    }
+    let elapsed = start.elapsed();
+    report.duration_ms = elapsed.as_millis() as u64;
+    report.per_second = report.generated as f64 / elapsed.as_secs_f64();
+    Ok(report)
+}
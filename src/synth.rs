@@ -0,0 +1,179 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use crate::{generate_at_times, BitLayout, GeneratorSettings, Spaceflake, SpaceflakeError};
+
+/// The amount of milliseconds in a day.
+const DAY_MS: u64 = 86_400_000;
+
+/// The amount of candidate times rejected in a row after which the daily cycle is considered to have no arrivals in
+/// the time range.
+const MAX_REJECTIONS: u32 = 1_000_000;
+
+/// The distribution followed by the arrival times of the Spaceflakes of a synthetic dataset.
+#[derive(Debug, Clone, Copy)]
+pub enum Arrival {
+    /// A fixed amount of Spaceflakes, arriving uniformly at random across the time range.
+    Uniform {
+        /// The amount of Spaceflakes to generate.
+        amount: usize,
+    },
+    /// A Poisson process with a constant rate, so the amount of Spaceflakes depends on the length of the time range.
+    Poisson {
+        /// The average amount of Spaceflakes arriving per second.
+        rate_per_second: f64,
+    },
+    /// A fixed amount of Spaceflakes, arriving following a daily cycle that peaks at the given hour (UTC).
+    Diurnal {
+        /// The amount of Spaceflakes to generate.
+        amount: usize,
+        /// The hour of the day, between `0` and `24`, at which the most Spaceflakes arrive.
+        peak_hour: f64,
+        /// How pronounced the daily cycle is, between `0` (uniform) and `1` (no arrivals at the opposite hour).
+        ///
+        /// A time range with (almost) no arrivals, such as one within the opposite hour with an amplitude of `1`,
+        /// returns an error.
+        amplitude: f64,
+    },
+}
+
+/// Settings to generate a synthetic dataset of Spaceflakes.
+#[derive(Debug, Clone, Copy)]
+pub struct DatasetSettings {
+    /// The settings used to generate the Spaceflakes, the sequence and time granularity are ignored.
+    pub generator: GeneratorSettings,
+    /// The start of the time range, inclusive, in milliseconds.
+    pub start_ms: u64,
+    /// The end of the time range, inclusive, in milliseconds.
+    pub end_ms: u64,
+    /// The distribution followed by the arrival times.
+    pub arrival: Arrival,
+}
+
+/// The default implementation of a dataset settings.
+impl DatasetSettings {
+    /// Create a new dataset settings for the given time range and arrival distribution.
+    pub fn new(start_ms: u64, end_ms: u64, arrival: Arrival) -> Self {
+        DatasetSettings {
+            generator: GeneratorSettings::default(),
+            start_ms,
+            end_ms,
+            arrival,
+        }
+    }
+}
+
+/// Generate a synthetic dataset of Spaceflakes, sorted by time, for the given settings.
+///
+/// This is meant to load-test downstream systems with production-like ID patterns.
+pub fn generate_dataset<R: Rng + ?Sized>(
+    rng: &mut R,
+    settings: DatasetSettings,
+) -> Result<Vec<Spaceflake>, SpaceflakeError> {
+    if settings.start_ms > settings.end_ms {
+        return Err(SpaceflakeError::InvalidTimeWindow(String::from(
+            "The start of the time window must be less than or equals to its end",
+        )));
+    }
+
+    let mut times = match settings.arrival {
+        Arrival::Uniform { amount } => (0..amount)
+            .map(|_| rng.gen_range(settings.start_ms..=settings.end_ms))
+            .collect::<Vec<u64>>(),
+        Arrival::Poisson { rate_per_second } => {
+            if !(rate_per_second > 0.0 && rate_per_second.is_finite()) {
+                return Err(SpaceflakeError::InvalidArgument(String::from(
+                    "Arrival rate must be greater than 0",
+                )));
+            }
+            let rate_per_ms = rate_per_second / 1000.0;
+            let mut times = Vec::<u64>::new();
+            let mut at = settings.start_ms as f64;
+            loop {
+                at += -(1.0 - rng.gen::<f64>()).ln() / rate_per_ms;
+                if at > settings.end_ms as f64 {
+                    break;
+                }
+                times.push(at as u64);
+            }
+            times
+        }
+        Arrival::Diurnal {
+            amount,
+            peak_hour,
+            amplitude,
+        } => {
+            if !(0.0..=1.0).contains(&amplitude) {
+                return Err(SpaceflakeError::InvalidArgument(String::from(
+                    "Amplitude must be between 0 and 1",
+                )));
+            }
+            let mut times = Vec::<u64>::with_capacity(amount);
+            let mut rejections = 0;
+            while times.len() < amount {
+                let at = rng.gen_range(settings.start_ms..=settings.end_ms);
+                let hour = (at % DAY_MS) as f64 / 3_600_000.0;
+                let weight = 1.0 + amplitude * (2.0 * PI * (hour - peak_hour) / 24.0).cos();
+                if rng.gen::<f64>() * (1.0 + amplitude) < weight {
+                    times.push(at);
+                    rejections = 0;
+                    continue;
+                }
+                rejections += 1;
+                if rejections >= MAX_REJECTIONS {
+                    return Err(SpaceflakeError::InvalidTimeWindow(String::from(
+                        "The time window has no arrivals for the daily cycle",
+                    )));
+                }
+            }
+            times
+        }
+    };
+    times.sort_unstable();
+
+    generate_at_times(settings.generator, times)
+}
+
+/// Sample an amount of structurally valid Spaceflakes, uniformly distributed within a time window, for the given base
+/// epoch and bit layout.
+///
+/// Both the start and the end of the window are inclusive, in milliseconds, and the node ID, worker ID and sequence
+/// are random too. The Spaceflakes are not guaranteed to be unique, this is meant for fixtures and anonymized test
+/// databases.
+pub fn sample_in_range<R: Rng + ?Sized>(
+    rng: &mut R,
+    start_ms: u64,
+    end_ms: u64,
+    base_epoch: u64,
+    layout: BitLayout,
+    amount: usize,
+) -> Result<Vec<Spaceflake>, SpaceflakeError> {
+    if start_ms > end_ms {
+        return Err(SpaceflakeError::InvalidTimeWindow(String::from(
+            "The start of the time window must be less than or equals to its end",
+        )));
+    }
+    if base_epoch > start_ms {
+        return Err(SpaceflakeError::EpochAfterGenerationTime);
+    }
+    layout.validate()?;
+    let unit = layout.time_unit.as_millis();
+    if (end_ms - base_epoch) / unit > layout.max_time() {
+        return Err(SpaceflakeError::TimeOverflow);
+    }
+
+    Ok((0..amount)
+        .map(|_| {
+            let time = (rng.gen_range(start_ms..=end_ms) - base_epoch) / unit;
+            let node_id = rng.gen_range(0..=layout.max_node_id());
+            let worker_id = rng.gen_range(0..=layout.max_worker_id());
+            let sequence = rng.gen_range(1..=layout.max_sequence());
+            Spaceflake::new(
+                layout.compose(time, node_id, worker_id, sequence),
+                base_epoch,
+            )
+            .with_layout(layout)
+        })
+        .collect())
+}
@@ -0,0 +1,13 @@
+use chrono::{DateTime, TimeZone};
+use chrono_tz::Tz;
+
+use crate::Spaceflake;
+
+/// The `chrono-tz` implementation of a Spaceflake.
+impl Spaceflake {
+    /// Returns the time of the Spaceflake in the given timezone.
+    pub fn time_in(&self, tz: Tz) -> DateTime<Tz> {
+        let utc = DateTime::from_timestamp_millis(self.time() as i64).unwrap_or_default();
+        tz.from_utc_datetime(&utc.naive_utc())
+    }
+}
@@ -0,0 +1,32 @@
+use ulid::Ulid;
+
+use crate::{Spaceflake, SpaceflakeError};
+
+/// The `ulid` implementation of a Spaceflake.
+impl Spaceflake {
+    /// Returns the ULID of the Spaceflake, with its timestamp and the bits other than the time, such as the node ID,
+    /// worker ID and sequence, as the randomness, so the same Spaceflake always translates to the same ULID.
+    pub fn to_ulid(&self) -> Ulid {
+        let shift = self.layout.time_shift();
+        Ulid::from_parts(self.time(), (self.id & ((1 << shift) - 1)) as u128)
+    }
+
+    /// Create a Spaceflake from a ULID returned by [`Spaceflake::to_ulid`], for the given base epoch.
+    ///
+    /// Returns an error if the randomness of the ULID does not fit in the bits other than the time, or if its
+    /// timestamp is before the base epoch or does not fit in the bits of the time.
+    pub fn try_from_ulid(ulid: Ulid, base_epoch: u64) -> Result<Spaceflake, SpaceflakeError> {
+        let spaceflake = Spaceflake::new(0, base_epoch);
+        if ulid.random() >> spaceflake.layout.time_shift() != 0 {
+            return Err(SpaceflakeError::InvalidEncoding(format!(
+                "The randomness of the ULID {} does not fit in a Spaceflake",
+                ulid
+            )));
+        }
+        let milliseconds = ulid
+            .timestamp_ms()
+            .checked_sub(base_epoch)
+            .ok_or(SpaceflakeError::EpochAfterGenerationTime)?;
+        Spaceflake::new(ulid.random() as u64, base_epoch).with_milliseconds(Some(milliseconds))
+    }
+}
@@ -0,0 +1,51 @@
+use uuid::{Builder, Uuid, Variant};
+
+use crate::{Spaceflake, SpaceflakeError};
+
+/// The `uuid` implementation of a Spaceflake.
+impl Spaceflake {
+    /// Returns the Spaceflake as a version 8 UUID embedding its base epoch, in the first 48 bits, and its whole ID, so
+    /// it can be restored with [`Spaceflake::try_from_uuid_v8`].
+    pub fn to_uuid_v8(&self) -> Uuid {
+        let mut bytes = [0u8; 16];
+        bytes[0..6].copy_from_slice(&self.base_epoch.to_be_bytes()[2..8]);
+        bytes[6..8].copy_from_slice(&((self.id >> 52) as u16).to_be_bytes());
+        bytes[8..16].copy_from_slice(&(self.id & ((1 << 52) - 1)).to_be_bytes());
+        Builder::from_custom_bytes(bytes).into_uuid()
+    }
+
+    /// Create a Spaceflake from a version 8 UUID returned by [`Spaceflake::to_uuid_v8`], with the base epoch embedded
+    /// in it.
+    ///
+    /// Returns an error if the UUID is not a version 8 UUID.
+    pub fn try_from_uuid_v8(uuid: Uuid) -> Result<Spaceflake, SpaceflakeError> {
+        if uuid.get_version_num() != 8 || uuid.get_variant() != Variant::RFC4122 {
+            return Err(SpaceflakeError::InvalidEncoding(format!(
+                "Invalid UUID {}: not a version 8 UUID",
+                uuid
+            )));
+        }
+        let bytes = uuid.as_bytes();
+        let mut base_epoch = [0u8; 8];
+        base_epoch[2..8].copy_from_slice(&bytes[0..6]);
+        let high = (u16::from_be_bytes([bytes[6], bytes[7]]) & 0x0FFF) as u64;
+        let mut low = [0u8; 8];
+        low.copy_from_slice(&bytes[8..16]);
+        let low = u64::from_be_bytes(low) & ((1 << 52) - 1);
+        Ok(Spaceflake::new(
+            (high << 52) | low,
+            u64::from_be_bytes(base_epoch),
+        ))
+    }
+
+    /// Returns a standard version 7 UUID with the time of the Spaceflake as its timestamp and the bits other than the
+    /// time, such as the node ID, worker ID and sequence, as its random bits.
+    ///
+    /// The base epoch of the Spaceflake is not kept, so it can not be restored from the UUID.
+    pub fn to_uuid_v7_lossy(&self) -> Uuid {
+        let shift = self.layout.time_shift();
+        let mut random = [0u8; 10];
+        random[2..10].copy_from_slice(&(self.id & ((1 << shift) - 1)).to_be_bytes());
+        Builder::from_unix_timestamp_millis(self.time(), &random).into_uuid()
+    }
+}
@@ -2,93 +2,912 @@ extern crate spaceflake;
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::collections::HashSet;
     use std::thread;
-    use std::time::Duration;
+    use std::time::{Duration, UNIX_EPOCH};
 
-    use spaceflake::Spaceflake;
+    use spaceflake::{Spaceflake, SpaceflakeError};
 
     #[test]
     fn bulk_generation() {
-        let mut spaceflakes: HashMap<String, Spaceflake> = HashMap::new();
+        let mut spaceflakes: HashSet<Spaceflake> = HashSet::new();
         let settings = spaceflake::BulkGeneratorSettings::new(1_000_000);
         let bulk = spaceflake::bulk_generate(settings).expect("Failed generating the Spaceflakes");
         for spaceflake in bulk {
-            if spaceflakes.contains_key(spaceflake.to_string().as_str()) {
+            if !spaceflakes.insert(spaceflake) {
                 panic!("Spaceflake ID {} is a duplicate", spaceflake.id);
             }
-            spaceflakes.insert(spaceflake.to_string(), spaceflake);
         }
     }
 
+    #[test]
+    fn bulk_generation_chunked() {
+        let mut spaceflakes: HashSet<Spaceflake> = HashSet::new();
+        let settings = spaceflake::BulkGeneratorSettings::new(10_500);
+        let chunks = spaceflake::bulk_generate_chunked(settings, 1_000);
+        let mut amount_of_chunks = 0;
+        for chunk in chunks {
+            let chunk = chunk.expect("Failed generating the Spaceflakes");
+            amount_of_chunks += 1;
+            for spaceflake in chunk {
+                if !spaceflakes.insert(spaceflake) {
+                    panic!("Spaceflake ID {} is a duplicate", spaceflake.id);
+                }
+            }
+        }
+        assert_eq!(amount_of_chunks, 11);
+        assert_eq!(spaceflakes.len(), 10_500);
+    }
+
+    #[test]
+    fn bulk_generation_checkpointed() {
+        let mut spaceflakes: HashSet<Spaceflake> = HashSet::new();
+        let mut persisted = None;
+        let settings = spaceflake::BulkGeneratorSettings::new(10_500);
+        let interrupted = spaceflake::bulk_generate_checkpointed(
+            settings.clone(),
+            None,
+            1_000,
+            |batch, checkpoint| {
+                if checkpoint.generated > 5_000 {
+                    return Err(SpaceflakeError::Source(String::from("Interrupted")));
+                }
+                for spaceflake in batch {
+                    spaceflakes.insert(spaceflake);
+                }
+                persisted = Some(checkpoint);
+                Ok(())
+            },
+        );
+        assert_eq!(
+            interrupted,
+            Err(SpaceflakeError::Source(String::from("Interrupted")))
+        );
+        assert_eq!(persisted.unwrap().generated, 5_000);
+
+        spaceflake::bulk_generate_checkpointed(settings, persisted, 1_000, |batch, checkpoint| {
+            for spaceflake in batch {
+                if !spaceflakes.insert(spaceflake) {
+                    panic!("Spaceflake ID {} is a duplicate", spaceflake.id);
+                }
+            }
+            persisted = Some(checkpoint);
+            Ok(())
+        })
+        .expect("Failed resuming the generation");
+        assert_eq!(persisted.unwrap().generated, 10_500);
+        assert_eq!(spaceflakes.len(), 10_500);
+    }
+
     #[test]
     fn bulk_generation_node() {
-        let mut spaceflakes: HashMap<String, Spaceflake> = HashMap::new();
-        let node = spaceflake::Node::new(1);
+        let mut spaceflakes: HashSet<Spaceflake> = HashSet::new();
+        let node = spaceflake::Node::try_new(1).expect("Failed creating the node");
         let bulk = node
             .bulk_generate(1_000_000)
             .expect("Failed generating the Spaceflakes");
         for spaceflake in bulk {
-            if spaceflakes.contains_key(spaceflake.to_string().as_str()) {
+            if !spaceflakes.insert(spaceflake) {
                 panic!("Spaceflake ID {} is a duplicate", spaceflake.id);
             }
-            spaceflakes.insert(spaceflake.to_string(), spaceflake);
         }
     }
 
     #[test]
     fn bulk_generation_worker() {
-        let mut spaceflakes: HashMap<String, Spaceflake> = HashMap::new();
-        let mut node = spaceflake::Node::new(1);
+        let mut spaceflakes: HashSet<Spaceflake> = HashSet::new();
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
         let worker = node.new_worker();
         let bulk = worker
             .bulk_generate(1_000_000)
             .expect("Failed generating the Spaceflakes");
         for spaceflake in bulk {
-            if spaceflakes.contains_key(spaceflake.to_string().as_str()) {
+            if !spaceflakes.insert(spaceflake) {
                 panic!("Spaceflake ID {} is a duplicate", spaceflake.id);
             }
-            spaceflakes.insert(spaceflake.to_string(), spaceflake);
         }
     }
 
     #[test]
     fn generate_at() {
-        let mut node = spaceflake::Node::new(1);
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
         let worker = node.new_worker();
         let sf = worker.generate_at(1532180612064).unwrap();
         assert_eq!(sf.time(), 1532180612064);
     }
 
+    #[test]
+    fn bulk_generation_between() {
+        let settings =
+            spaceflake::GeneratorSettings::try_new(1, 1).expect("Failed creating the settings");
+        let start = 1532180612064;
+        let bulk = spaceflake::bulk_generate_between(settings, start, start + 9, 20_000)
+            .expect("Failed generating the Spaceflakes");
+        assert_eq!(bulk.len(), 20_000);
+        assert_eq!(bulk[0].time(), start);
+        assert_eq!(bulk[19_999].time(), start + 9);
+        for pair in bulk.windows(2) {
+            assert!(pair[0].id < pair[1].id);
+        }
+
+        assert!(spaceflake::bulk_generate_between(settings, start, start + 9, 50_000).is_err());
+    }
+
+    #[test]
+    fn synthetic_dataset() {
+        use rand::SeedableRng;
+        use spaceflake::synth::{generate_dataset, Arrival, DatasetSettings};
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1337);
+        let start = 1532180612064;
+        let end = start + 86_400_000;
+        for arrival in [
+            Arrival::Uniform { amount: 10_000 },
+            Arrival::Poisson {
+                rate_per_second: 0.1,
+            },
+            Arrival::Diurnal {
+                amount: 10_000,
+                peak_hour: 12.0,
+                amplitude: 0.8,
+            },
+        ] {
+            let dataset = generate_dataset(&mut rng, DatasetSettings::new(start, end, arrival))
+                .expect("Failed generating the dataset");
+            assert!(!dataset.is_empty());
+            for pair in dataset.windows(2) {
+                assert!(pair[0].id < pair[1].id);
+                assert!(pair[0].time() >= start && pair[1].time() <= end);
+            }
+        }
+
+        // Midnight is the opposite hour of the peak, where no Spaceflake arrives with the full amplitude.
+        let midnight = 1532131200000;
+        let arrival = Arrival::Diurnal {
+            amount: 1,
+            peak_hour: 12.0,
+            amplitude: 1.0,
+        };
+        assert!(
+            generate_dataset(&mut rng, DatasetSettings::new(midnight, midnight, arrival)).is_err()
+        );
+    }
+
+    #[test]
+    fn sql_snippets() {
+        let mut settings =
+            spaceflake::GeneratorSettings::try_new(5, 7).expect("Failed creating the settings");
+        settings.version_bits = 1;
+        settings.version = 1;
+        let function = spaceflake::sql::postgres_function(&settings, "spaceflake_next");
+        assert!(function.contains("CREATE SEQUENCE IF NOT EXISTS spaceflake_next_sequence;"));
+        assert!(function.contains("base_epoch bigint := 1420070400000;"));
+        assert!(function.contains("% 2047 + 1"));
+        assert!(function.contains("(5 << 17) | (7 << 12) | 2048 | seq"));
+        assert_eq!(
+            spaceflake::sql::postgres_default("spaceflake_next"),
+            "DEFAULT spaceflake_next()"
+        );
+        let trigger = spaceflake::sql::mysql_trigger(&settings, "users_id", "users", "id");
+        assert!(trigger.contains("CREATE TRIGGER users_id BEFORE INSERT ON users"));
+        assert!(trigger.contains("IF NEW.id IS NULL THEN"));
+
+        settings.layout = spaceflake::BitLayout::sonyflake();
+        settings.worker_id = 0;
+        let function = spaceflake::sql::postgres_function(&settings, "sonyflake_next");
+        assert!(function.contains("% 127 + 1"));
+        assert!(function.contains(
+            "((now_ms - base_epoch) / 10) << 24) | (5 << 0) | (0 << 0) | ((128 | seq) << 16)"
+        ));
+        let trigger = spaceflake::sql::mysql_trigger(&settings, "users_id", "users", "id");
+        assert!(trigger.contains(" DIV 10) << 24)"));
+    }
+
+    #[test]
+    fn sample_in_range() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1337);
+        let start = 1532180612064;
+        let end = start + 60_000;
+        let samples = spaceflake::synth::sample_in_range(
+            &mut rng,
+            start,
+            end,
+            spaceflake::EPOCH,
+            spaceflake::BitLayout::SPACEFLAKE,
+            1000,
+        )
+        .expect("Failed sampling the Spaceflakes");
+        assert_eq!(samples.len(), 1000);
+        for sf in samples {
+            assert!(sf.time() >= start && sf.time() <= end);
+            assert!(sf.node_id() <= 31 && sf.worker_id() <= 31 && sf.sequence() >= 1);
+        }
+        assert!(spaceflake::synth::sample_in_range(
+            &mut rng,
+            end,
+            start,
+            spaceflake::EPOCH,
+            spaceflake::BitLayout::SPACEFLAKE,
+            1
+        )
+        .is_err());
+
+        let layout = spaceflake::BitLayout::twitter();
+        let samples = spaceflake::synth::sample_in_range(
+            &mut rng,
+            start,
+            end,
+            spaceflake::EPOCH,
+            layout,
+            1000,
+        )
+        .expect("Failed sampling the Spaceflakes");
+        assert!(samples.iter().any(|sf| sf.node_id() > 31));
+        for sf in samples {
+            assert_eq!(sf.layout(), layout);
+            assert!(sf.time() >= start && sf.time() <= end);
+            assert_eq!(sf.worker_id(), 0);
+        }
+    }
+
+    #[test]
+    fn generate_shared_state() {
+        // A clock ahead of the others does not hold back the Spaceflakes generated with the other clocks.
+        let now = UNIX_EPOCH.elapsed().unwrap().as_millis() as u64;
+        let clock: &'static spaceflake::MockClock =
+            Box::leak(Box::new(spaceflake::MockClock::new(now + 3_600_000)));
+        let settings = spaceflake::GeneratorSettings {
+            node_id: 17,
+            worker_id: 23,
+            drift_policy: spaceflake::DriftPolicy::Error,
+            ..Default::default()
+        };
+        let ahead = spaceflake::generate(spaceflake::GeneratorSettings {
+            clock: Some(clock),
+            ..settings
+        })
+        .expect("Failed generating the Spaceflake");
+        assert_eq!(ahead.time(), now + 3_600_000);
+        let sf = spaceflake::generate(settings).expect("Failed generating the Spaceflake");
+        assert!(sf.time() < ahead.time());
+
+        // The sequence is still shared by the Spaceflakes generated with the same settings.
+        let spaceflakes = (0..100)
+            .map(|_| {
+                spaceflake::generate(spaceflake::GeneratorSettings {
+                    clock: Some(clock),
+                    ..settings
+                })
+                .expect("Failed generating the Spaceflake")
+            })
+            .collect::<HashSet<Spaceflake>>();
+        assert_eq!(spaceflakes.len(), 100);
+    }
+
     #[test]
     fn generate_future() {
-        let mut node = spaceflake::Node::new(1);
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
         let worker = node.new_worker();
         let sf = worker.generate_at(2662196938000).unwrap_err();
+        assert_eq!(sf, SpaceflakeError::GenerationTimeInFuture);
         assert_eq!(
-            sf,
+            sf.to_string(),
             "The current time must be greater than the time you want to generate the Spaceflake at"
         );
     }
 
+    #[test]
+    fn generate_with_retry() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let mut worker = node.new_worker();
+        let policy = spaceflake::RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(2),
+        };
+        worker
+            .generate_with_retry(policy)
+            .expect("Failed generating the Spaceflake");
+
+        // A base epoch after the current time is not retried.
+        worker.base_epoch = 2662196938000;
+        let policy = spaceflake::RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::MAX,
+        };
+        let start = std::time::Instant::now();
+        assert_eq!(
+            worker.generate_with_retry(policy).unwrap_err(),
+            SpaceflakeError::EpochAfterGenerationTime
+        );
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(!SpaceflakeError::EpochInFuture.is_transient());
+        assert!(SpaceflakeError::SequenceExhausted.is_transient());
+    }
+
+    #[test]
+    fn error_codes() {
+        use spaceflake::ErrorCode;
+
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let worker = node.new_worker();
+        let error = worker.generate_at(2662196938000).unwrap_err();
+        let code = error.code();
+        assert_eq!(code, ErrorCode::GenerationTimeInFuture);
+        assert_eq!(code.as_code(), 9);
+        assert_eq!(ErrorCode::from_code(9), Some(code));
+        assert_eq!(ErrorCode::from_code(1337), None);
+        assert_eq!(
+            SpaceflakeError::InvalidArgument(String::from("Worker ID range must not be empty"))
+                .code(),
+            ErrorCode::InvalidArgument
+        );
+        assert_eq!(ErrorCode::InvalidArgument.as_code(), 18);
+        assert_eq!(SpaceflakeError::Source(String::new()).code().as_code(), 19);
+        assert_eq!(
+            ErrorCode::from_code(20),
+            Some(SpaceflakeError::Io(String::new()).code())
+        );
+    }
+
+    #[test]
+    fn reconfigure() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let mut worker = node.new_worker();
+        let sf1 = worker.generate().expect("Failed generating the Spaceflake");
+        let config = spaceflake::WorkerConfig {
+            version_bits: 1,
+            version: 1,
+            ..worker.config()
+        };
+        worker
+            .reconfigure(config)
+            .expect("Failed reconfiguring the worker");
+        assert_eq!(worker.generation(), 1);
+        assert_eq!(worker.config(), config);
+        let sf2 = worker.generate().expect("Failed generating the Spaceflake");
+        assert!(sf2.time() > sf1.time());
+        assert_eq!(sf2.version(), 1);
+
+        let invalid = spaceflake::WorkerConfig {
+            time_granularity: 0,
+            ..config
+        };
+        assert!(worker.reconfigure(invalid).is_err());
+        assert_eq!(worker.generation(), 1);
+
+        let clone = worker.clone();
+        let layout =
+            spaceflake::BitLayout::SPACEFLAKE.with_time_unit(spaceflake::TimeUnit::Centisecond);
+        let config = spaceflake::WorkerConfig {
+            layout,
+            drift_policy: spaceflake::DriftPolicy::Error,
+            ..config
+        };
+        worker
+            .reconfigure(config)
+            .expect("Failed reconfiguring the worker");
+        assert_eq!(clone.generation(), 2);
+        assert_eq!(clone.config(), config);
+        let sf3 = clone.generate().expect("Failed generating the Spaceflake");
+        assert_eq!(sf3.layout(), layout);
+        assert!(sf3.time() > sf2.time());
+        let narrow = spaceflake::WorkerConfig {
+            layout: spaceflake::BitLayout::twitter(),
+            ..config
+        };
+        assert!(worker.reconfigure(narrow).is_err());
+
+        // The base epoch is validated against the clock of the worker.
+        let now = UNIX_EPOCH.elapsed().unwrap().as_millis() as u64;
+        let clock = spaceflake::MockClock::new(now + 60_000);
+        worker.set_clock(std::sync::Arc::new(clock.clone()));
+        let ticker = thread::spawn(move || {
+            for _ in 0..100 {
+                thread::sleep(Duration::from_millis(1));
+                clock.advance(Duration::from_millis(1));
+            }
+        });
+        let config = spaceflake::WorkerConfig {
+            base_epoch: now + 30_000,
+            ..config
+        };
+        worker
+            .reconfigure(config)
+            .expect("Failed reconfiguring the worker");
+        ticker.join().expect("Failed joining the ticker");
+        worker.set_clock(std::sync::Arc::new(spaceflake::MockClock::new(now)));
+        assert_eq!(
+            worker
+                .reconfigure(spaceflake::WorkerConfig {
+                    base_epoch: now + 1,
+                    ..config
+                })
+                .unwrap_err(),
+            SpaceflakeError::EpochInFuture
+        );
+    }
+
+    #[test]
+    fn reconfigure_in_flight() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let mut worker = spaceflake::Worker::standalone(1, 1).expect("Failed creating the worker");
+        let stop = std::sync::Arc::new(AtomicBool::new(false));
+        let handles = (0..4)
+            .map(|_| {
+                let worker = worker.clone();
+                let stop = stop.clone();
+                std::thread::spawn(move || {
+                    let mut spaceflakes = Vec::new();
+                    while !stop.load(Ordering::Relaxed) {
+                        spaceflakes
+                            .push(worker.generate().expect("Failed generating the Spaceflake"));
+                    }
+                    spaceflakes
+                })
+            })
+            .collect::<Vec<_>>();
+        std::thread::sleep(Duration::from_millis(20));
+        worker
+            .reconfigure(spaceflake::WorkerConfig {
+                version_bits: 1,
+                version: 1,
+                ..worker.config()
+            })
+            .expect("Failed reconfiguring the worker");
+        std::thread::sleep(Duration::from_millis(20));
+        stop.store(true, Ordering::Relaxed);
+
+        let spaceflakes = handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("Failed joining the thread"))
+            .collect::<Vec<Spaceflake>>();
+        let last_old = spaceflakes
+            .iter()
+            .filter(|sf| sf.version() == 0)
+            .map(|sf| sf.time())
+            .max()
+            .expect("No Spaceflake generated before the reconfiguration");
+        let first_new = spaceflakes
+            .iter()
+            .filter(|sf| sf.version() == 1)
+            .map(|sf| sf.time())
+            .min()
+            .expect("No Spaceflake generated after the reconfiguration");
+        assert!(last_old < first_new);
+    }
+
+    #[test]
+    fn random_sequence_offset() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let mut worker = node.new_worker();
+        worker.random_sequence_offset = true;
+        let mut sequences = Vec::new();
+        for i in 0..10 {
+            let sf1 = worker
+                .generate_at(1532180612064 + i)
+                .expect("Failed generating the Spaceflake");
+            let sf2 = worker
+                .generate_at(1532180612064 + i)
+                .expect("Failed generating the Spaceflake");
+            assert_eq!(sf2.sequence(), sf1.sequence() % 4095 + 1);
+            sequences.push(sf1.sequence());
+        }
+        assert!(sequences.iter().any(|sequence| *sequence > 10));
+    }
+
+    #[test]
+    fn cached_time() {
+        let mut spaceflakes: HashSet<Spaceflake> = HashSet::new();
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let mut worker = node.new_worker();
+        worker.cache_time = true;
+        let first = worker.generate().expect("Failed generating the Spaceflake");
+        thread::sleep(Duration::from_millis(5));
+        let second = worker.generate().expect("Failed generating the Spaceflake");
+        assert_eq!(first.time(), second.time());
+
+        for _ in 0..10_000 {
+            let sf = worker.generate().expect("Failed generating the Spaceflake");
+            if !spaceflakes.insert(sf) {
+                panic!("Spaceflake ID {} is a duplicate", sf.id);
+            }
+        }
+    }
+
+    #[test]
+    fn ticker_clock() {
+        let ticker = spaceflake::TickerClock::new().expect("Failed starting the ticker clock");
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let mut worker = node.new_worker();
+        worker.set_clock(std::sync::Arc::new(ticker.clone()));
+        let before = ticker.now();
+        thread::sleep(Duration::from_millis(10));
+        assert!(ticker.now() > before);
+        let sf = worker.generate().expect("Failed generating the Spaceflake");
+        assert!(sf.time() <= ticker.now());
+    }
+
+    #[test]
+    fn process_worker() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let worker = node.new_process_worker();
+        assert_eq!(worker.id, std::process::id() as u64 % 32);
+
+        spaceflake::set_process_worker_id_hook(Some(|| 7));
+        let worker = node.new_process_worker();
+        spaceflake::set_process_worker_id_hook(None);
+        assert_eq!(worker.id, 7);
+        let sf = worker.generate().expect("Failed generating the Spaceflake");
+        assert_eq!(sf.worker_id(), 7);
+    }
+
+    #[test]
+    fn remote_generator() {
+        use spaceflake::{IdGenerator, RemoteGenerator, RemoteSource};
+        use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+        struct Server {
+            next: AtomicU64,
+            reachable: AtomicBool,
+            slow: AtomicBool,
+        }
+
+        impl RemoteSource for &Server {
+            fn fetch_block(&self, amount: usize) -> Result<Vec<u64>, SpaceflakeError> {
+                if !self.reachable.load(Ordering::SeqCst) {
+                    return Err(SpaceflakeError::Source(String::from(
+                        "Server is unreachable",
+                    )));
+                }
+                if self.slow.load(Ordering::SeqCst) {
+                    thread::sleep(Duration::from_millis(300));
+                }
+                let start = self.next.fetch_add(amount as u64, Ordering::SeqCst);
+                Ok((start..start + amount as u64).collect())
+            }
+        }
+
+        let server = Server {
+            next: AtomicU64::new(1 << 22),
+            reachable: AtomicBool::new(true),
+            slow: AtomicBool::new(false),
+        };
+        let generator = RemoteGenerator::try_new(&server, 10, spaceflake::EPOCH)
+            .expect("Failed creating the remote generator");
+        let sf = generator
+            .generate()
+            .expect("Failed generating the Spaceflake");
+        assert_eq!(sf.id, 1 << 22);
+        assert_eq!(generator.buffered(), 9);
+
+        server.reachable.store(false, Ordering::SeqCst);
+        for _ in 0..9 {
+            generator
+                .generate()
+                .expect("Failed generating the Spaceflake");
+        }
+        assert_eq!(
+            generator.generate().unwrap_err().to_string(),
+            "Server is unreachable"
+        );
+
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let generator = generator.with_fallback(node.new_worker());
+        let sf = generator
+            .generate()
+            .expect("Failed generating the Spaceflake");
+        assert_eq!(sf.node_id(), 1);
+
+        // The buffer is not locked while fetching, so a slow remote source does not block the other callers.
+        server.reachable.store(true, Ordering::SeqCst);
+        server.slow.store(true, Ordering::SeqCst);
+        let generator = RemoteGenerator::try_new(&server, 10, spaceflake::EPOCH)
+            .expect("Failed creating the remote generator");
+        thread::scope(|scope| {
+            let fetching = scope.spawn(|| generator.generate());
+            thread::sleep(Duration::from_millis(50));
+            let start = std::time::Instant::now();
+            assert_eq!(generator.buffered(), 0);
+            assert!(start.elapsed() < Duration::from_millis(100));
+            fetching
+                .join()
+                .expect("Failed joining the thread")
+                .expect("Failed generating the Spaceflake");
+        });
+        assert_eq!(generator.buffered(), 9);
+    }
+
+    #[test]
+    fn failover_generator() {
+        use spaceflake::{FailoverGenerator, IdGenerator};
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use std::sync::Arc;
+
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let mut primary = node.new_worker();
+        primary.base_epoch = 2662196938000;
+        let mut backup_node = spaceflake::Node::try_new(2).expect("Failed creating the node");
+        let backup = backup_node.new_worker();
+        let alerts = Arc::new(AtomicU32::new(0));
+        let counter = alerts.clone();
+        let generator = FailoverGenerator::try_new(primary, backup, 3)
+            .expect("Failed creating the failover generator")
+            .on_failover(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+
+        for _ in 0..5 {
+            let sf = generator
+                .generate()
+                .expect("Failed generating the Spaceflake");
+            assert_eq!(sf.node_id(), 2);
+        }
+        assert!(generator.is_failed_over());
+        assert_eq!(alerts.load(Ordering::SeqCst), 1);
+
+        generator.recover();
+        assert!(!generator.is_failed_over());
+    }
+
+    #[test]
+    fn audit_log() {
+        use spaceflake::{AuditedGenerator, FileAuditSink, IdGenerator};
+
+        let directory =
+            std::env::temp_dir().join(format!("spaceflake-audit-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("audit.log");
+        let sink = FileAuditSink::new(&path, 200, 2).expect("Failed opening the audit log");
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let generator = AuditedGenerator::new(node.new_worker(), sink);
+
+        let mut ids = Vec::new();
+        for _ in 0..10 {
+            let sf = generator
+                .generate()
+                .expect("Failed generating the Spaceflake");
+            ids.push(sf.id);
+        }
+        let current = std::fs::read_to_string(&path).unwrap();
+        let rotated = std::fs::read_to_string(directory.join("audit.log.1")).unwrap();
+        assert!(current.contains(&ids[9].to_string()));
+        assert!(rotated.lines().all(|line| line.split(',').count() == 4));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn available_without_wait() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let mut worker = node.new_worker();
+        assert_eq!(worker.available_without_wait(), 4095);
+        worker.cache_time = true;
+        for _ in 0..10 {
+            worker.generate().expect("Failed generating the Spaceflake");
+        }
+        assert_eq!(worker.available_without_wait(), 4085);
+    }
+
+    #[test]
+    fn leap_smear() {
+        // The leap second at the end of 2016, at 2017-01-01T00:00:00Z.
+        let smear = spaceflake::LeapSmear::new(1483228800000);
+        let start = 1483228800000 - 43_200_000;
+        assert_eq!(smear.to_utc(start - 1), start - 1);
+        assert_eq!(
+            smear.to_utc(start + 43_200_000 - 864),
+            start + 43_200_000 - 365
+        );
+        assert_eq!(smear.to_utc(1483228800000), 1483228800000 - 500);
+        assert_eq!(smear.to_utc(start + 86_400_000), start + 86_400_000);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn bulk_generation_mmap() {
+        let path = std::env::temp_dir().join(format!("spaceflake-mmap-{}.bin", std::process::id()));
+        let settings = spaceflake::BulkGeneratorSettings::new(10_000);
+        spaceflake::bulk_generate_to_mmap(settings, &path)
+            .expect("Failed generating the Spaceflakes");
+
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(bytes.len(), 10_000 * 8);
+        let ids = bytes
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect::<std::collections::HashSet<u64>>();
+        assert_eq!(ids.len(), 10_000);
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        let settings = spaceflake::GeneratorSettings {
+            worker_id: 4096,
+            ..Default::default()
+        };
+        assert!(spaceflake::generate(settings).is_err());
+        let sf = Spaceflake::from_bytes(1165925685034747967u64.to_be_bytes(), spaceflake::EPOCH);
+        assert!(sf.kafka_partition(0).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Node ID must be less than 31")]
+    #[allow(deprecated)]
+    fn deprecated_constructor_panics() {
+        spaceflake::Node::new(32);
+    }
+
+    #[test]
+    #[should_panic(expected = "Worker ID must be less than 31")]
+    #[allow(deprecated)]
+    fn deprecated_settings_constructor_panics() {
+        spaceflake::GeneratorSettings::new(1, 32);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_support() {
+        let sf = spaceflake::generate(spaceflake::GeneratorSettings::default())
+            .expect("Failed generating the Spaceflake");
+        let json = serde_json::to_string(&sf).expect("Failed serializing the Spaceflake");
+        assert_eq!(json, sf.id.to_string());
+        let number: Spaceflake = serde_json::from_str(&json).expect("Failed deserializing");
+        let string: Spaceflake =
+            serde_json::from_str(&format!("\"{}\"", sf.id)).expect("Failed deserializing");
+        assert_eq!(number.id, sf.id);
+        assert_eq!(string.time(), sf.time());
+        assert!(serde_json::from_str::<Spaceflake>("\"spaceflake\"").is_err());
+        assert!(serde_json::from_str::<Spaceflake>("-1").is_err());
+
+        let settings: spaceflake::GeneratorSettings =
+            serde_json::from_str("{\"node_id\": 5, \"worker_id\": 7}")
+                .expect("Failed deserializing");
+        assert_eq!(settings.node_id, 5);
+        assert_eq!(settings.base_epoch, spaceflake::EPOCH);
+        assert_eq!(settings.time_granularity, 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_string() {
+        #[derive(serde::Serialize, serde::Deserialize)]
+        struct User {
+            #[serde(with = "spaceflake::serde::string")]
+            id: Spaceflake,
+        }
+
+        let sf = spaceflake::generate(spaceflake::GeneratorSettings::default())
+            .expect("Failed generating the Spaceflake");
+        let id = sf.id;
+        let json = serde_json::to_string(&User { id: sf }).expect("Failed serializing the user");
+        assert_eq!(json, format!("{{\"id\":\"{}\"}}", id));
+        let user: User = serde_json::from_str(&json).expect("Failed deserializing the user");
+        assert_eq!(user.id.id, id);
+    }
+
+    #[cfg(feature = "stress")]
+    #[test]
+    fn soak_test() {
+        let config = spaceflake::stress::SoakConfig {
+            duration: Duration::from_millis(200),
+            threads: 2,
+            bloom_capacity: 100_000,
+            ..Default::default()
+        };
+        let report = spaceflake::stress::soak_test(config).expect("Failed running the soak test");
+        assert!(report.generated > 0);
+        assert_eq!(report.threads, 2);
+        assert!(report.to_json().contains("\"monotonicity_violations\":"));
+
+        let report = spaceflake::stress::SoakReport {
+            generated: 10,
+            possible_duplicates: 1,
+            ..Default::default()
+        };
+        assert!(report.passed());
+    }
+
+    #[cfg(feature = "tower")]
+    #[test]
+    fn tower_service() {
+        use tower::ServiceExt;
+
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let mut worker = node.new_worker();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .expect("Failed building the runtime");
+        let sf = runtime
+            .block_on(worker.clone().oneshot(spaceflake::GenerateRequest))
+            .expect("Failed generating the Spaceflake");
+        assert_eq!(sf.node_id(), 1);
+
+        // The sequence is exhausted, so the services wait for the next millisecond without blocking the thread.
+        worker.fail_on_exhaustion = true;
+        let spaceflakes = runtime
+            .block_on(worker.oneshot(spaceflake::BulkGenerateRequest { amount: 10_000 }))
+            .expect("Failed generating the Spaceflakes");
+        assert_eq!(spaceflakes.len(), 10_000);
+        assert_eq!(
+            spaceflakes
+                .iter()
+                .map(|sf| sf.id)
+                .collect::<HashSet<u64>>()
+                .len(),
+            10_000
+        );
+
+        let spaceflakes = runtime
+            .block_on(node.oneshot(spaceflake::BulkGenerateRequest { amount: 10 }))
+            .expect("Failed generating the Spaceflakes");
+        assert_eq!(spaceflakes.len(), 10);
+    }
+
+    #[test]
+    fn event_handler() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::sync::Arc;
+
+        #[derive(Default)]
+        struct Counter {
+            created: AtomicU64,
+            retired: AtomicU64,
+            exhausted: AtomicU64,
+        }
+        impl spaceflake::EventHandler for Counter {
+            fn on_worker_created(&self, _worker: &spaceflake::Worker) {
+                self.created.fetch_add(1, Ordering::Relaxed);
+            }
+            fn on_worker_retired(&self, _worker: &spaceflake::Worker) {
+                self.retired.fetch_add(1, Ordering::Relaxed);
+            }
+            fn on_sequence_exhausted(&self, _worker: &spaceflake::Worker) {
+                self.exhausted.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let counter = Arc::new(Counter::default());
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        node.set_event_handler(counter.clone());
+        let worker = node.new_worker();
+        node.new_worker();
+        node.remove_worker(2);
+        assert_eq!(counter.created.load(Ordering::Relaxed), 2);
+        assert_eq!(counter.retired.load(Ordering::Relaxed), 1);
+
+        let at = spaceflake::EPOCH + 1000;
+        for _ in 0..4095 {
+            worker
+                .generate_at(at)
+                .expect("Failed generating the Spaceflake");
+        }
+        assert!(worker.generate_at(at).is_err());
+        assert_eq!(counter.exhausted.load(Ordering::Relaxed), 1);
+    }
+
     #[test]
     fn worker_unique() {
-        let mut spaceflakes: HashMap<String, Spaceflake> = HashMap::new();
-        let mut node = spaceflake::Node::new(1);
+        let mut spaceflakes: HashSet<Spaceflake> = HashSet::new();
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
         let worker = node.new_worker();
 
         for _ in 0..1000 {
             let sf = worker.generate().expect("Failed generating the Spaceflake");
-            if spaceflakes.contains_key(sf.to_string().as_str()) {
+            if !spaceflakes.insert(sf) {
                 panic!("Spaceflake ID {} is a duplicate", sf.id);
             }
-            spaceflakes.insert(sf.to_string(), sf);
         }
     }
 
     #[test]
     fn same_timestamp_different_base_epoch() {
-        let mut node = spaceflake::Node::new(1);
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
         let mut worker = node.new_worker();
         let sf1 = worker.generate().expect("Failed generating the Spaceflake");
         worker.base_epoch = 1672531200000; // Sunday, January 1, 2023 12:00:00 AM GMT
@@ -101,19 +920,1920 @@ mod tests {
         }
     }
 
+    #[test]
+    fn version_bits() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let mut worker = node.new_worker();
+        worker.version_bits = 2;
+        worker.version = 3;
+        let sf = worker.generate().expect("Failed generating the Spaceflake");
+        assert_eq!(sf.version(), 3);
+        assert_eq!(sf.sequence(), 1);
+        assert_eq!(spaceflake::parse_version(sf.id, 2), 3);
+        assert_eq!(spaceflake::parse_version(sf.id, 13), sf.id & 0xFFF);
+        let parsed: Spaceflake = sf
+            .id
+            .to_string()
+            .parse()
+            .expect("Failed parsing the Spaceflake");
+        assert_eq!(parsed.version(), 0);
+        assert_eq!(parsed.with_version_bits(2).version(), 3);
+        assert_eq!(parsed.with_version_bits(2).sequence(), 1);
+
+        worker.version = 4;
+        assert_eq!(
+            worker.generate().unwrap_err(),
+            SpaceflakeError::InvalidVersion { max: 4 }
+        );
+    }
+
+    #[cfg(feature = "avro")]
+    #[test]
+    fn avro_round_trip() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let worker = node.new_worker();
+        let sf = worker.generate().expect("Failed generating the Spaceflake");
+        let datum = sf.to_avro().expect("Failed encoding the Spaceflake");
+        let decoded = Spaceflake::from_avro(&datum).expect("Failed decoding the Spaceflake");
+        assert_eq!(decoded.id, sf.id);
+        assert_eq!(decoded.time(), sf.time());
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let worker = node.new_worker();
+        let sf1 = worker.generate().expect("Failed generating the Spaceflake");
+        let sf2 = worker.generate().expect("Failed generating the Spaceflake");
+        assert!(sf1.to_bytes() < sf2.to_bytes());
+        let decoded = Spaceflake::from_bytes(sf1.to_bytes(), spaceflake::EPOCH);
+        assert_eq!(decoded.id, sf1.id);
+        assert_eq!(decoded.time(), sf1.time());
+    }
+
+    #[test]
+    fn kafka_partition() {
+        let sf = Spaceflake::from_bytes(1165925685034747967u64.to_be_bytes(), spaceflake::EPOCH);
+        assert_eq!(sf.kafka_partition(1).expect("Failed partitioning"), 0);
+        assert_eq!(sf.kafka_partition(100).expect("Failed partitioning"), 28);
+        assert_eq!(sf.kafka_partition(1000).expect("Failed partitioning"), 828);
+    }
+
+    #[test]
+    fn trace_context_ids() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let worker = node.new_worker();
+        let span_id = worker
+            .generate_span_id()
+            .expect("Failed generating the span ID");
+        assert_ne!(span_id, [0u8; 8]);
+        let trace_id = worker
+            .generate_trace_id()
+            .expect("Failed generating the trace ID");
+        assert_ne!(trace_id, [0u8; 16]);
+        assert!(span_id[..] < trace_id[..8]);
+    }
+
+    #[test]
+    fn checked_string() {
+        let sf = Spaceflake::from_bytes(1165925685034747967u64.to_be_bytes(), spaceflake::EPOCH);
+        let checked = sf.to_checked_string();
+        assert_eq!(checked.len(), sf.to_string().len() + 1);
+        let parsed = Spaceflake::from_checked_string(&checked, spaceflake::EPOCH)
+            .expect("Failed parsing the checked Spaceflake");
+        assert_eq!(parsed.id, sf.id);
+
+        let mut typo = checked.into_bytes();
+        typo.swap(3, 4);
+        let typo = String::from_utf8(typo).unwrap();
+        assert!(Spaceflake::from_checked_string(&typo, spaceflake::EPOCH).is_err());
+        assert!(Spaceflake::from_checked_string("abc", spaceflake::EPOCH).is_err());
+    }
+
+    #[test]
+    fn coarsened_time() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let mut worker = node.new_worker();
+        worker.time_granularity = 60_000;
+        let at = spaceflake::EPOCH + 60_000 * 1000 + 1337;
+        for _ in 0..100 {
+            let sf = worker
+                .generate_at(at)
+                .expect("Failed generating the Spaceflake");
+            assert_eq!(sf.time(), at - 1337);
+        }
+
+        worker.time_granularity = 10;
+        let start = std::time::Instant::now();
+        let mut spaceflakes = HashSet::new();
+        let mut last = 0;
+        while start.elapsed() < Duration::from_millis(300) {
+            let sf = worker.generate().expect("Failed generating the Spaceflake");
+            let window = sf.time() - (sf.time() - spaceflake::EPOCH) % 10;
+            assert!(window <= UNIX_EPOCH.elapsed().unwrap().as_millis() as u64);
+            assert!(sf.id > last, "Spaceflake {} is not increasing", sf);
+            assert!(
+                spaceflakes.insert(sf.id),
+                "Spaceflake {} is a duplicate",
+                sf
+            );
+            last = sf.id;
+        }
+
+        // The time bits freed by the truncation widen the sequence, so a window holds as many Spaceflakes as without it.
+        let clock = spaceflake::MockClock::new(at);
+        let mut worker = spaceflake::Worker::standalone(1, 1).expect("Failed creating the worker");
+        worker.set_clock(std::sync::Arc::new(clock));
+        worker.time_granularity = 60_000;
+        worker.fail_on_exhaustion = true;
+        let mut ids = Vec::new();
+        worker
+            .generate_many(100_000, &mut ids)
+            .expect("Failed generating the Spaceflakes");
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        let last = Spaceflake::from_id(ids[ids.len() - 1], spaceflake::EPOCH);
+        assert!(last.time() - (at - 1337) < 60_000);
+        assert_eq!(worker.available_without_wait(), 60_000 * 4095 - 100_000);
+
+        worker.time_granularity = 0;
+        assert_eq!(
+            worker.generate().unwrap_err(),
+            SpaceflakeError::InvalidTimeGranularity
+        );
+    }
+
+    #[cfg(feature = "chrono-tz")]
+    #[test]
+    fn time_in_timezone() {
+        let sf = spaceflake::Spaceflake::from_bytes(0u64.to_be_bytes(), spaceflake::EPOCH);
+        assert_eq!(
+            sf.time_in(chrono_tz::Asia::Tokyo).to_rfc3339(),
+            "2015-01-01T09:00:00+09:00"
+        );
+    }
+
+    #[cfg(feature = "hmac")]
+    #[test]
+    fn derive_id() {
+        let at = 1698048745164;
+        let settings = spaceflake::GeneratorSettings::default();
+        let derive = |tenant: &str, key: &[u8]| {
+            spaceflake::derive_id(&settings, b"secret", tenant, key, at)
+                .expect("Failed deriving the ID")
+        };
+        let sf = derive("tenant", b"request-1");
+        assert_eq!(sf.id, derive("tenant", b"request-1").id);
+        assert_ne!(sf.id, derive("tenant", b"request-2").id);
+        assert_ne!(sf.id, derive("other", b"request-1").id);
+        assert_ne!(derive("ab", b"c").id, derive("a", b"bc").id);
+        assert_ne!(
+            sf.id,
+            spaceflake::derive_id(&settings, b"other", "tenant", b"request-1", at)
+                .unwrap()
+                .id
+        );
+        assert_eq!(sf.time(), at);
+
+        let settings = spaceflake::GeneratorSettings {
+            base_epoch: 1_600_000_000_000,
+            layout: spaceflake::BitLayout::sonyflake(),
+            ..Default::default()
+        };
+        let sf = spaceflake::derive_id(&settings, b"secret", "tenant", b"request-1", at)
+            .expect("Failed deriving the ID");
+        assert_eq!(sf.time(), at - at % 10);
+        assert_eq!(
+            spaceflake::derive_id(&settings, b"secret", "tenant", b"request-1", 1).unwrap_err(),
+            SpaceflakeError::EpochAfterGenerationTime
+        );
+    }
+
+    #[test]
+    fn diff() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let worker = node.new_worker();
+        let sf1 = worker
+            .generate_at(1532180612064)
+            .expect("Failed generating the Spaceflake");
+        let sf2 = worker
+            .generate_at(1532180613064)
+            .expect("Failed generating the Spaceflake");
+        let diff = sf1.diff(&sf2);
+        assert_eq!(diff.time_delta, 1000);
+        assert!(!diff.node_differs && !diff.worker_differs);
+        assert_eq!(diff.sequence_distance, 0);
+        assert_eq!(
+            diff.to_string(),
+            "Generated 1000ms later, on the same node and worker, with a sequence distance of 0"
+        );
+    }
+
+    #[test]
+    fn export_topology() {
+        let mut cluster = spaceflake::Cluster::new();
+        let node = cluster.new_node(1).expect("Failed creating the node");
+        let mut worker = node.new_worker();
+        worker.set_clock(std::sync::Arc::new(spaceflake::MockClock::new(
+            1698048745164,
+        )));
+        worker.generate().expect("Failed generating the Spaceflake");
+        worker.generate().expect("Failed generating the Spaceflake");
+        assert_eq!(worker.last_generated(), (1698048745164, 2));
+        assert_eq!(
+            cluster.export_topology(),
+            "{\"nodes\":[{\"id\":1,\"workers\":[{\"id\":1,\"base_epoch\":1420070400000,\"issued\":2,\"last_timestamp\":1698048745164,\"sequence\":2}]}]}"
+        );
+        let dot = cluster.export_topology_dot();
+        assert!(dot.contains("\"node_1\" -> \"node_1_worker_1\";"));
+        assert!(dot.contains("last timestamp: 1698048745164\\nsequence: 2"));
+    }
+
+    #[test]
+    fn redis_stream_id() {
+        let sf = Spaceflake::from_bytes(1165925685034747967u64.to_be_bytes(), spaceflake::EPOCH);
+        let entry_id = sf.to_redis_stream_id();
+        assert_eq!(entry_id, format!("{}-{}", sf.time(), sf.id & 0x3FFFFF));
+        let layout = spaceflake::BitLayout::SPACEFLAKE;
+        let parsed = Spaceflake::from_redis_stream_id(&entry_id, spaceflake::EPOCH, layout)
+            .expect("Failed parsing the entry ID");
+        assert_eq!(parsed.id, sf.id);
+        assert!(Spaceflake::from_redis_stream_id("1-99999999", spaceflake::EPOCH, layout).is_err());
+        assert!(Spaceflake::from_redis_stream_id("nope", spaceflake::EPOCH, layout).is_err());
+
+        let layout = spaceflake::BitLayout::sonyflake();
+        let sf = Spaceflake::from_id(1165925685034747967, spaceflake::EPOCH).with_layout(layout);
+        let entry_id = sf.to_redis_stream_id();
+        assert_eq!(entry_id, format!("{}-{}", sf.time(), sf.id & 0xFFFFFF));
+        let parsed = Spaceflake::from_redis_stream_id(&entry_id, spaceflake::EPOCH, layout)
+            .expect("Failed parsing the entry ID");
+        assert_eq!(parsed, sf);
+        assert_eq!(parsed.node_id(), sf.node_id());
+        assert!(Spaceflake::from_redis_stream_id(
+            &format!("{}-{}", sf.time(), 1 << 24),
+            spaceflake::EPOCH,
+            layout
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn timeuuid() {
+        let sf = Spaceflake::from_bytes(1165925685034747967u64.to_be_bytes(), spaceflake::EPOCH);
+        let uuid = sf.to_timeuuid().expect("Failed converting to a timeuuid");
+        assert_eq!(uuid[6] >> 4, 1);
+        let layout = spaceflake::BitLayout::SPACEFLAKE;
+        let parsed = Spaceflake::from_timeuuid(uuid, spaceflake::EPOCH, layout)
+            .expect("Failed parsing the timeuuid");
+        assert_eq!(parsed.id, sf.id);
+        // Cassandra's `toUnixTimestamp` of this timeuuid is the time of the Spaceflake.
+        assert_eq!(sf.time(), 1698048745164);
+        assert_eq!(
+            sf.to_timeuuid_string()
+                .expect("Failed converting to a timeuuid"),
+            "e62c40c0-717b-11ee-8000-01000000083f"
+        );
+        assert!(Spaceflake::from_timeuuid([0u8; 16], spaceflake::EPOCH, layout).is_err());
+
+        let layout = spaceflake::BitLayout::sonyflake();
+        let sf = Spaceflake::from_id(1165925685034747967, spaceflake::EPOCH).with_layout(layout);
+        let uuid = sf.to_timeuuid().expect("Failed converting to a timeuuid");
+        let parsed = Spaceflake::from_timeuuid(uuid, spaceflake::EPOCH, layout)
+            .expect("Failed parsing the timeuuid");
+        assert_eq!(parsed, sf);
+        let layout =
+            spaceflake::BitLayout::new(30, 10, 11, 12).expect("Failed creating the layout");
+        assert!(sf.with_layout(layout).to_timeuuid().is_err());
+    }
+
+    #[test]
+    fn audit_ids() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let worker = node.new_worker();
+        let mut ids = worker
+            .bulk_generate(100)
+            .expect("Failed generating the Spaceflakes")
+            .iter()
+            .map(|sf| sf.id)
+            .collect::<Vec<u64>>();
+        let expectations = spaceflake::AuditExpectations {
+            node_ids: 1..=1,
+            ..Default::default()
+        };
+        assert!(spaceflake::audit_ids(ids.clone(), &expectations).is_conforming());
+
+        let first = ids[0];
+        ids.push(first);
+        let report = spaceflake::audit_ids(ids.clone(), &expectations);
+        assert_eq!(report.possible_duplicates, vec![first]);
+        assert!(report.is_conforming());
+        ids.push(1 << 63);
+        ids.push(2 << 17);
+        let report = spaceflake::audit_ids(ids.clone(), &expectations);
+        assert_eq!(report.scanned, 103);
+        assert_eq!(report.possible_duplicates, vec![first]);
+        assert_eq!(report.sign_bit_set, vec![1 << 63]);
+        assert_eq!(report.future, vec![1 << 63]);
+        assert_eq!(report.unexpected_node, vec![1 << 63, 2 << 17]);
+        let without_dedupe = spaceflake::AuditExpectations {
+            dedupe_capacity: None,
+            ..expectations.clone()
+        };
+        assert!(spaceflake::audit_ids(ids, &without_dedupe)
+            .possible_duplicates
+            .is_empty());
+
+        let layout = spaceflake::BitLayout::twitter();
+        let mut expectations = spaceflake::AuditExpectations {
+            node_ids: 1000..=1000,
+            ..spaceflake::AuditExpectations::with_layout(layout)
+        };
+        let id = layout.compose(1, 1000, 0, 1);
+        assert!(spaceflake::audit_ids([id], &expectations).is_conforming());
+        expectations.layout = spaceflake::BitLayout::SPACEFLAKE;
+        assert!(!spaceflake::audit_ids([id], &expectations).is_conforming());
+    }
+
+    #[test]
+    fn regions() {
+        spaceflake::register_regions(&[("eu", 1), ("us", 2)])
+            .expect("Failed registering the regions");
+        let settings =
+            spaceflake::GeneratorSettings::try_new(5, 1).expect("Failed creating the settings");
+        let sf = spaceflake::generate_in_region(settings, "us")
+            .expect("Failed generating the Spaceflake");
+        assert_eq!(sf.region(), Some(String::from("us")));
+        assert_eq!(sf.region_code(), 2);
+        assert_eq!(sf.node_id(), 0b10101);
+        assert!(spaceflake::generate_in_region(settings, "mars").is_err());
+        assert!(spaceflake::register_regions(&[("mars", 4)]).is_err());
+        assert!(spaceflake::register_regions(&[("eu", 1), ("eu", 2)]).is_err());
+        assert!(spaceflake::register_regions(&[("eu", 1), ("us", 1)]).is_err());
+        assert_eq!(spaceflake::region_code("us"), Some(2));
+
+        let layout = spaceflake::BitLayout::twitter();
+        let settings = spaceflake::GeneratorSettings {
+            node_id: 5,
+            layout,
+            ..Default::default()
+        };
+        let sf = spaceflake::generate_in_region(settings, "us")
+            .expect("Failed generating the Spaceflake");
+        assert_eq!(sf.node_id(), (2 << 8) | 5);
+        assert_eq!(sf.region(), Some(String::from("us")));
+        assert_eq!(
+            spaceflake::region_node_id("eu", 256, layout).unwrap_err(),
+            SpaceflakeError::InvalidNodeId { max: 256 }
+        );
+        let narrow = spaceflake::BitLayout::new(41, 1, 9, 12).expect("Failed creating the layout");
+        assert!(spaceflake::region_node_id("eu", 0, narrow).is_err());
+    }
+
+    #[test]
+    fn checked_time_arithmetic() {
+        let settings =
+            spaceflake::GeneratorSettings::try_new(3, 7).expect("Failed creating the settings");
+        let sf = spaceflake::generate(settings).expect("Failed generating the Spaceflake");
+        let later = sf
+            .checked_add(Duration::from_secs(3600))
+            .expect("Failed shifting the Spaceflake");
+        assert_eq!(later.time(), sf.time() + 3_600_000);
+        assert_eq!(later.node_id(), 3);
+        assert_eq!(later.worker_id(), 7);
+        assert_eq!(later.sequence(), sf.sequence());
+        assert_eq!(
+            later.checked_sub(Duration::from_secs(3600)).unwrap().id,
+            sf.id
+        );
+        assert!(sf
+            .checked_sub(Duration::from_secs(20 * 365 * 86400))
+            .is_err());
+        assert!(sf
+            .checked_add(Duration::from_secs(100 * 365 * 86400))
+            .is_err());
+    }
+
+    #[test]
+    fn commit_timestamp() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::sync::Arc;
+
+        struct Database(AtomicU64);
+        impl spaceflake::CommitTimestamp for Database {
+            fn now(&self) -> Result<u64, SpaceflakeError> {
+                match self.0.fetch_add(1, Ordering::Relaxed) {
+                    0 => Err(SpaceflakeError::Source(String::from(
+                        "Database is unreachable",
+                    ))),
+                    time => Ok(time),
+                }
+            }
+        }
+
+        let time = spaceflake::EPOCH + 1_000;
+        let mut worker = spaceflake::Worker::default();
+        worker.set_commit_timestamp(Arc::new(Database(AtomicU64::new(0))));
+        assert_eq!(
+            worker.generate().unwrap_err().to_string(),
+            "Database is unreachable"
+        );
+        worker.set_commit_timestamp(Arc::new(Database(AtomicU64::new(time))));
+        let first = worker.generate().expect("Failed generating the Spaceflake");
+        let second = worker.generate().expect("Failed generating the Spaceflake");
+        assert_eq!(first.time(), time);
+        assert_eq!(second.time(), time + 1);
+    }
+
+    #[test]
+    fn shared_worker_state() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::sync::Arc;
+
+        struct Rewinding(AtomicU64);
+        impl spaceflake::CommitTimestamp for Rewinding {
+            fn now(&self) -> Result<u64, SpaceflakeError> {
+                Ok(self.0.fetch_sub(10, Ordering::Relaxed))
+            }
+        }
+
+        let time = spaceflake::EPOCH + 1_000;
+        let mut worker = spaceflake::Worker::default();
+        worker.drift_policy = spaceflake::DriftPolicy::BorrowSequence(Duration::from_secs(1));
+        worker.set_commit_timestamp(Arc::new(Rewinding(AtomicU64::new(time))));
+        let clone = worker.clone();
+        let first = worker.generate().expect("Failed generating the Spaceflake");
+        let second = clone.generate().expect("Failed generating the Spaceflake");
+        assert_eq!(second.time(), first.time());
+        assert!(second.id > first.id);
+        assert_eq!(worker.issued(), 2);
+    }
+
+    #[test]
+    fn concurrent_generation() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let worker = node.new_worker();
+        let handles = (0..4)
+            .map(|_| {
+                let worker = worker.clone();
+                thread::spawn(move || {
+                    (0..1_000)
+                        .map(|_| {
+                            worker
+                                .generate()
+                                .expect("Failed generating the Spaceflake")
+                                .id
+                        })
+                        .collect::<Vec<u64>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut ids = std::collections::HashSet::new();
+        for handle in handles {
+            for id in handle.join().unwrap() {
+                assert!(ids.insert(id), "Spaceflake ID {} is a duplicate", id);
+            }
+        }
+        assert_eq!(worker.issued(), 4_000);
+    }
+
+    #[test]
+    fn sequence_exhaustion() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let mut worker = node.new_worker();
+        worker.layout =
+            spaceflake::BitLayout::new(41, 5, 16, 1).expect("Failed creating the layout");
+        let first = worker.generate().expect("Failed generating the Spaceflake");
+        let second = worker.generate().expect("Failed generating the Spaceflake");
+        assert!(second.time() > first.time());
+
+        let at = second.time();
+        assert_eq!(
+            worker.generate_at(at).unwrap_err(),
+            SpaceflakeError::SequenceExhausted
+        );
+        worker.fail_on_exhaustion = true;
+        let exhausted =
+            (0..100).any(|_| worker.generate() == Err(SpaceflakeError::SequenceExhausted));
+        assert!(exhausted);
+    }
+
+    #[test]
+    fn non_blocking_generation() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let mut worker = node.new_worker();
+        worker.layout =
+            spaceflake::BitLayout::new(41, 5, 16, 1).expect("Failed creating the layout");
+        let mut would_block = false;
+        for _ in 0..100 {
+            match worker.try_generate() {
+                Ok(_) => {}
+                Err(SpaceflakeError::WouldBlock { retry_after_ms }) => {
+                    assert!(retry_after_ms >= 1);
+                    would_block = true;
+                    break;
+                }
+                Err(error) => panic!("Unexpected error: {}", error),
+            }
+        }
+        assert!(would_block);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn async_generation() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .expect("Failed building the runtime");
+        runtime.block_on(async {
+            let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+            let mut worker = node.new_worker();
+            worker.layout =
+                spaceflake::BitLayout::new(41, 5, 16, 1).expect("Failed creating the layout");
+            let first = worker
+                .generate_async()
+                .await
+                .expect("Failed generating the Spaceflake");
+            let second = worker
+                .generate_async()
+                .await
+                .expect("Failed generating the Spaceflake");
+            assert!(second.time() > first.time());
+
+            let settings = spaceflake::BulkGeneratorSettings::new(10_000);
+            let bulk = spaceflake::bulk_generate_async(settings)
+                .await
+                .expect("Failed generating the Spaceflakes");
+            let spaceflakes = bulk.into_iter().collect::<HashSet<Spaceflake>>();
+            assert_eq!(spaceflakes.len(), 10_000);
+        });
+    }
+
+    #[test]
+    fn mock_clock() {
+        use std::sync::Arc;
+
+        let at = spaceflake::EPOCH + 1_000_000;
+        let clock = spaceflake::MockClock::new(at);
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let mut worker = node.new_worker();
+        worker.set_clock(Arc::new(clock.clone()));
+        let first = worker.generate().expect("Failed generating the Spaceflake");
+        assert_eq!(first.time(), at);
+        assert_eq!(first.sequence(), 1);
+        clock.advance(Duration::from_millis(5));
+        let second = worker.generate().expect("Failed generating the Spaceflake");
+        assert_eq!(second.time(), at + 5);
+
+        // The time is in the future of the system clock, but not of the mock clock.
+        let future = second.time() + 3_600_000_000;
+        let static_clock: &'static spaceflake::MockClock =
+            Box::leak(Box::new(spaceflake::MockClock::new(future + 1)));
+        let settings = spaceflake::GeneratorSettings {
+            clock: Some(static_clock),
+            ..Default::default()
+        };
+        let sf =
+            spaceflake::generate_at(settings, future).expect("Failed generating the Spaceflake");
+        assert_eq!(sf.time(), future);
+    }
+
+    #[test]
+    fn cached_clock() {
+        use spaceflake::Clock;
+        use std::sync::Arc;
+
+        let clock = spaceflake::CachedClock::shared().expect("Failed starting the cached clock");
+        let system = spaceflake::SystemClock.now_ms();
+        assert!(clock.now_ms().abs_diff(system) <= 50);
+
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let mut worker = node.new_worker();
+        worker.set_clock(Arc::new(clock));
+        let mut spaceflakes = HashSet::new();
+        for _ in 0..10_000 {
+            let sf = worker.generate().expect("Failed generating the Spaceflake");
+            assert!(spaceflakes.insert(sf), "Spaceflake {} is a duplicate", sf);
+        }
+    }
+
+    #[test]
+    fn monotonic_clock() {
+        use spaceflake::Clock;
+
+        let clock = spaceflake::MonotonicClock::with_reanchor_interval(Duration::from_millis(1));
+        assert!(clock.now_ms().abs_diff(spaceflake::SystemClock.now_ms()) <= 50);
+        let mut last = clock.now_ms();
+        for _ in 0..1000 {
+            let now = clock.now_ms();
+            assert!(now >= last);
+            last = now;
+        }
+    }
+
+    #[test]
+    fn drift_policy() {
+        use spaceflake::DriftPolicy;
+        use std::sync::Arc;
+
+        let at = spaceflake::EPOCH + 1_000_000;
+        let clock = spaceflake::MockClock::new(at);
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let mut worker = node.new_worker();
+        worker.set_clock(Arc::new(clock.clone()));
+        assert_eq!(
+            worker.drift_policy,
+            DriftPolicy::WaitUpTo(Duration::from_millis(10))
+        );
+        worker.generate().expect("Failed generating the Spaceflake");
+        clock.set(at - 5);
+
+        worker.drift_policy = DriftPolicy::BorrowSequence(Duration::from_millis(2));
+        assert_eq!(
+            worker.generate(),
+            Err(SpaceflakeError::ClockMovedBackwards { delta_ms: 5 })
+        );
+        worker.drift_policy = DriftPolicy::BorrowSequence(Duration::from_millis(10));
+        let borrowed = worker.generate().expect("Failed generating the Spaceflake");
+        assert_eq!(borrowed.time(), at);
+        worker.drift_policy = DriftPolicy::Error;
+        assert_eq!(
+            worker.generate(),
+            Err(SpaceflakeError::ClockMovedBackwards { delta_ms: 5 })
+        );
+        worker.drift_policy = DriftPolicy::WaitUpTo(Duration::from_millis(2));
+        assert!(worker.generate().is_err());
+        worker.drift_policy = DriftPolicy::WaitUpTo(Duration::from_millis(10));
+        assert_eq!(
+            worker.try_generate(),
+            Err(SpaceflakeError::WouldBlock { retry_after_ms: 5 })
+        );
+        clock.set(at + 1);
+        let caught_up = worker.generate().expect("Failed generating the Spaceflake");
+        assert_eq!(caught_up.time(), at + 1);
+    }
+
+    #[test]
+    fn borrow_sequence() {
+        use std::sync::Arc;
+
+        let at = spaceflake::EPOCH + 1_000_000;
+        let clock = spaceflake::MockClock::new(at);
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let mut worker = node.new_worker();
+        worker.set_clock(Arc::new(clock.clone()));
+        worker.layout =
+            spaceflake::BitLayout::new(41, 5, 16, 1).expect("Failed creating the layout");
+        worker.drift_policy = spaceflake::DriftPolicy::BorrowSequence(Duration::from_millis(6));
+        worker.generate().expect("Failed generating the Spaceflake");
+        clock.set(at - 5);
+
+        // The mock clock does not move, so waiting for it would never return.
+        let first = worker.generate().expect("Failed generating the Spaceflake");
+        let second = worker.generate().expect("Failed generating the Spaceflake");
+        assert_eq!(first.time(), at + 1);
+        assert_eq!(second.time(), at + 2);
+        // The borrowing is bounded, so the Spaceflakes do not run ahead of the clock.
+        assert_eq!(
+            worker.generate(),
+            Err(SpaceflakeError::ClockMovedBackwards { delta_ms: 7 })
+        );
+        clock.set(at + 10);
+        let caught_up = worker.generate().expect("Failed generating the Spaceflake");
+        assert_eq!(caught_up.time(), at + 10);
+    }
+
+    #[test]
+    fn state_persistence() {
+        use spaceflake::{DriftPolicy, FileStatePersistence, StatePersistence};
+        use std::sync::Arc;
+
+        let path = std::env::temp_dir().join(format!("spaceflake-state-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let persistence = Arc::new(FileStatePersistence::new(&path));
+        let at = spaceflake::EPOCH + 1_000_000;
+        let clock = spaceflake::MockClock::new(at);
+
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let mut worker = node.new_worker();
+        worker.set_clock(Arc::new(clock.clone()));
+        worker
+            .set_state_persistence(persistence.clone())
+            .expect("Failed restoring the worker state");
+        worker.generate().expect("Failed generating the Spaceflake");
+        let recorded = persistence.load(1, 1).unwrap().expect("No recorded time");
+        assert!(recorded >= at);
+
+        // The clock went backwards while the worker was restarting.
+        clock.set(at - 10);
+        let mut restarted = spaceflake::Node::try_new(1)
+            .expect("Failed creating the node")
+            .new_worker();
+        restarted.set_clock(Arc::new(clock.clone()));
+        restarted.drift_policy = DriftPolicy::Error;
+        restarted
+            .set_state_persistence(Arc::new(FileStatePersistence::new(&path)))
+            .expect("Failed restoring the worker state");
+        assert!(restarted.generate().is_err());
+
+        // The worker waits for the clock to pass the restored time instead of borrowing, whatever its drift policy.
+        restarted.drift_policy = DriftPolicy::BorrowSequence(Duration::from_secs(10));
+        clock.set(recorded - 5);
+        assert_eq!(
+            restarted.try_generate(),
+            Err(SpaceflakeError::WouldBlock { retry_after_ms: 6 })
+        );
+        clock.set(recorded + 1);
+        let sf = restarted
+            .generate()
+            .expect("Failed generating the Spaceflake");
+        assert!(sf.time() > recorded);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn file_id_allocator() {
+        use spaceflake::{FileIdAllocator, IdAllocator};
+
+        let directory =
+            std::env::temp_dir().join(format!("spaceflake-leases-{}", std::process::id()));
+        let first = FileIdAllocator::new(&directory).expect("Failed creating the allocator");
+        let second = FileIdAllocator::new(&directory).expect("Failed creating the allocator");
+        let ttl = Duration::from_secs(30);
+
+        let lease = first.acquire(ttl).expect("Failed acquiring a lease");
+        let other = second.acquire(ttl).expect("Failed acquiring a lease");
+        assert_ne!(
+            (lease.node_id, lease.worker_id),
+            (other.node_id, other.worker_id)
+        );
+        let renewed = first.renew(&lease, ttl).expect("Failed renewing the lease");
+        assert!(renewed.expires_at >= lease.expires_at);
+        assert!(second.renew(&lease, ttl).is_err());
+        let worker = lease.worker().expect("Failed creating the worker");
+        assert_eq!(worker.node_id, lease.node_id);
+
+        first
+            .release(lease.clone())
+            .expect("Failed releasing the lease");
+        let reclaimed = second.acquire(ttl).expect("Failed acquiring a lease");
+        assert_eq!(
+            (reclaimed.node_id, reclaimed.worker_id),
+            (lease.node_id, lease.worker_id)
+        );
+        drop((first, second));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "redis")]
+    fn redis_id_allocator() {
+        use spaceflake::{IdAllocator, RedisIdAllocator};
+
+        let allocator = RedisIdAllocator::new("redis://127.0.0.1:1/", "spaceflake:")
+            .expect("Failed creating the allocator");
+        let error = allocator.acquire(Duration::from_secs(30)).unwrap_err();
+        assert!(error.to_string().starts_with("Redis"));
+    }
+
+    #[test]
+    #[cfg(feature = "etcd")]
+    fn etcd_id_allocator() {
+        use spaceflake::{EtcdIdAllocator, IdAllocator};
+
+        let allocator = EtcdIdAllocator::new("http://127.0.0.1:1", "spaceflake/");
+        let error = allocator.acquire(Duration::from_secs(30)).unwrap_err();
+        assert!(error.to_string().starts_with("etcd"));
+    }
+
+    #[test]
+    fn automatic_node_id() {
+        use spaceflake::NodeIdStrategy;
+
+        let node =
+            spaceflake::Node::auto(NodeIdStrategy::Fixed(7)).expect("Failed creating the node");
+        assert_eq!(node.id, 7);
+        assert!(spaceflake::Node::auto(NodeIdStrategy::Fixed(32)).is_err());
+        let node =
+            spaceflake::Node::auto(NodeIdStrategy::FromHostname).expect("Failed creating the node");
+        assert_eq!(NodeIdStrategy::FromHostname.node_id(), Ok(node.id));
+        assert!(NodeIdStrategy::FromEnv("SPACEFLAKE_UNSET_NODE_ID")
+            .node_id()
+            .is_err());
+    }
+
+    #[test]
+    fn statefulset_ordinal() {
+        let layout = spaceflake::BitLayout::SPACEFLAKE;
+        assert_eq!(
+            spaceflake::parse_statefulset_ordinal("api-3", &layout),
+            Ok(3)
+        );
+        assert_eq!(
+            spaceflake::parse_statefulset_ordinal("my-api-31", &layout),
+            Ok(31)
+        );
+        assert_eq!(
+            spaceflake::parse_statefulset_ordinal("api-32", &layout),
+            Err(SpaceflakeError::InvalidNodeId { max: 31 })
+        );
+        assert!(spaceflake::parse_statefulset_ordinal("api", &layout).is_err());
+        let wide = spaceflake::BitLayout::twitter();
+        assert_eq!(
+            spaceflake::parse_statefulset_ordinal("api-512", &wide),
+            Ok(512)
+        );
+    }
+
+    #[test]
+    fn id_range_helpers() {
+        let timestamp = spaceflake::EPOCH + 1_000;
+        let first = Spaceflake::first_at(timestamp, spaceflake::EPOCH).unwrap();
+        let last = Spaceflake::last_at(timestamp, spaceflake::EPOCH).unwrap();
+        assert_eq!(first.id, 1_000 << 22);
+        assert_eq!(last.id, (1_000 << 22) | ((1 << 22) - 1));
+        assert_eq!(first.time(), timestamp);
+        assert_eq!(last.time(), timestamp);
+        assert_eq!(last.node_id(), 31);
+
+        let settings = spaceflake::GeneratorSettings {
+            base_epoch: spaceflake::EPOCH,
+            ..Default::default()
+        };
+        let sf = spaceflake::generate_at(settings, timestamp + 5).unwrap();
+        let range = Spaceflake::range_for(timestamp..timestamp + 10, spaceflake::EPOCH).unwrap();
+        assert!(range.contains(&sf.id));
+        assert!(
+            !Spaceflake::range_for(timestamp..timestamp + 5, spaceflake::EPOCH)
+                .unwrap()
+                .contains(&sf.id)
+        );
+
+        assert!(Spaceflake::range_for(timestamp..timestamp, spaceflake::EPOCH).is_err());
+        assert_eq!(
+            Spaceflake::first_at(spaceflake::EPOCH - 1, spaceflake::EPOCH),
+            Err(SpaceflakeError::EpochAfterGenerationTime)
+        );
+    }
+
+    #[test]
+    fn spaceflake_range() {
+        use std::collections::BTreeMap;
+        use std::ops::RangeBounds;
+
+        let start = spaceflake::EPOCH + 1_000;
+        let range = spaceflake::SpaceflakeRange::new(start..start + 10, spaceflake::EPOCH).unwrap();
+        assert_eq!(range.start(), 1_000 << 22);
+        assert_eq!(range.end(), (1_010 << 22) - 1);
+        assert!(range.contains(&(1_005 << 22)));
+        assert!(!range.contains(&(1_010 << 22)));
+
+        let rows: BTreeMap<u64, &str> = [
+            (999 << 22, "before"),
+            (1_003 << 22, "in"),
+            (1_010 << 22, "after"),
+        ]
+        .into_iter()
+        .collect();
+        let found: Vec<_> = rows.range(range).map(|(_, row)| *row).collect();
+        assert_eq!(found, vec!["in"]);
+
+        let buckets: Vec<_> = range.buckets(Duration::from_millis(4)).unwrap().collect();
+        assert_eq!(buckets.len(), 3);
+        assert_eq!(buckets[0].times(), start..start + 4);
+        assert_eq!(buckets[2].times(), start + 8..start + 10);
+        assert_eq!(buckets[0].start(), range.start());
+        assert_eq!(buckets[2].end(), range.end());
+        assert!(range.buckets(Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn base_epoch_at() {
+        // The 1st of January 2023 at 00:00:00 GMT.
+        let base_epoch = 1672531200000;
+        let epoch = UNIX_EPOCH + Duration::from_millis(base_epoch);
+        let settings = spaceflake::GeneratorSettings::default()
+            .with_base_epoch_at(epoch)
+            .unwrap();
+        assert_eq!(settings.base_epoch, base_epoch);
+        let at = base_epoch + 1_234;
+        let sf = spaceflake::generate_at(settings, at).unwrap();
+        assert_eq!(sf.time(), at);
+        assert_eq!(spaceflake::parse_time(sf.id, base_epoch), at);
+        assert_eq!(sf.id >> 22, 1_234);
+        let sf = spaceflake::generate(settings).unwrap();
+        assert_eq!(sf.id >> 22, sf.time() - base_epoch);
+        assert!(spaceflake::GeneratorSettings::default()
+            .with_base_epoch_at(UNIX_EPOCH - Duration::from_millis(1))
+            .is_err());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_timestamps() {
+        let base_epoch = 1672531200000;
+        let epoch = chrono::DateTime::from_timestamp_millis(base_epoch as i64).unwrap();
+        let settings = spaceflake::GeneratorSettings::default()
+            .with_base_epoch_at(epoch)
+            .unwrap();
+        assert_eq!(settings.base_epoch, base_epoch);
+        let sf = spaceflake::generate_at(settings, base_epoch + 1_234).unwrap();
+        let decoded = Spaceflake::from_id(sf.id, base_epoch);
+        assert_eq!(
+            decoded.created_at_chrono().timestamp_millis() as u64,
+            base_epoch + 1_234
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_timestamps() {
+        let base_epoch = 1672531200000;
+        let epoch = time::OffsetDateTime::from_unix_timestamp_nanos(base_epoch as i128 * 1_000_000)
+            .unwrap();
+        let settings = spaceflake::GeneratorSettings::default()
+            .with_base_epoch_at(epoch)
+            .unwrap();
+        assert_eq!(settings.base_epoch, base_epoch);
+        let sf = spaceflake::generate_at(settings, base_epoch + 1_234).unwrap();
+        let decoded = Spaceflake::from_id(sf.id, base_epoch);
+        assert_eq!(
+            decoded.created_at_time().unix_timestamp_nanos(),
+            (base_epoch + 1_234) as i128 * 1_000_000
+        );
+    }
+
+    #[test]
+    fn spaceflake_age() {
+        let settings = spaceflake::GeneratorSettings::default();
+        let now = UNIX_EPOCH.elapsed().unwrap().as_millis() as u64;
+        let sf = spaceflake::generate_at(settings, now - 60_000).unwrap();
+        assert_eq!(
+            sf.system_time(),
+            UNIX_EPOCH + Duration::from_millis(sf.time())
+        );
+        assert!(sf.age() >= Duration::from_secs(60));
+        assert!(sf.is_older_than(Duration::from_secs(30)));
+        assert!(!sf.is_older_than(Duration::from_secs(3600)));
+
+        let fresh = spaceflake::generate(settings).unwrap();
+        assert!(!fresh.is_older_than(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn base62_encoding() {
+        let sf = spaceflake::generate(spaceflake::GeneratorSettings::default()).unwrap();
+        let encoded = sf.to_base62();
+        assert!(encoded.len() <= 11);
+        assert_eq!(
+            Spaceflake::from_base62(&encoded, spaceflake::EPOCH).unwrap(),
+            sf
+        );
+
+        assert_eq!(spaceflake::encode_base62(0), "0");
+        assert_eq!(spaceflake::encode_base62(61), "z");
+        assert_eq!(spaceflake::encode_base62(62), "10");
+        assert_eq!(spaceflake::encode_base62(u64::MAX), "LygHa16AHYF");
+        assert_eq!(spaceflake::decode_base62("LygHa16AHYF"), Ok(u64::MAX));
+        assert!(spaceflake::decode_base62("LygHa16AHYG").is_err());
+        assert!(spaceflake::decode_base62("").is_err());
+        assert!(spaceflake::decode_base62("abc-").is_err());
+    }
+
+    #[test]
+    fn formatting_traits() {
+        let sf = Spaceflake::from_id(0x1A2B_3C4D, spaceflake::EPOCH);
+        assert_eq!(format!("{:016x}", sf), "000000001a2b3c4d");
+        assert_eq!(format!("{:X}", sf), "1A2B3C4D");
+        assert_eq!(format!("{:#x}", sf), "0x1a2b3c4d");
+        assert_eq!(format!("{:o}", sf), format!("{:o}", sf.id));
+        assert_eq!(format!("{:064b}", sf), sf.to_binary());
+    }
+
+    #[cfg(feature = "ulid")]
+    #[test]
+    fn ulid_conversion() {
+        let sf = spaceflake::generate(spaceflake::GeneratorSettings::default()).unwrap();
+        let ulid = sf.to_ulid();
+        assert_eq!(ulid.timestamp_ms(), sf.time());
+        assert_eq!(ulid, sf.to_ulid());
+        assert_eq!(
+            Spaceflake::try_from_ulid(ulid, spaceflake::EPOCH).unwrap(),
+            sf
+        );
+
+        let random = ulid::Ulid::from_parts(sf.time(), 1 << 22);
+        assert!(Spaceflake::try_from_ulid(random, spaceflake::EPOCH).is_err());
+        let before_epoch = ulid::Ulid::from_parts(spaceflake::EPOCH - 1, 0);
+        assert_eq!(
+            Spaceflake::try_from_ulid(before_epoch, spaceflake::EPOCH),
+            Err(SpaceflakeError::EpochAfterGenerationTime)
+        );
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuid_conversion() {
+        let sf = spaceflake::generate(spaceflake::GeneratorSettings::default()).unwrap();
+        let v8 = sf.to_uuid_v8();
+        assert_eq!(v8.get_version_num(), 8);
+        assert_eq!(Spaceflake::try_from_uuid_v8(v8).unwrap(), sf);
+        let max = Spaceflake::from_id(u64::MAX >> 1, spaceflake::EPOCH);
+        assert_eq!(Spaceflake::try_from_uuid_v8(max.to_uuid_v8()).unwrap(), max);
+
+        let v7 = sf.to_uuid_v7_lossy();
+        assert_eq!(v7.get_version_num(), 7);
+        let (seconds, nanos) = v7.get_timestamp().unwrap().to_unix();
+        assert_eq!(seconds * 1_000 + nanos as u64 / 1_000_000, sf.time());
+        assert!(Spaceflake::try_from_uuid_v8(v7).is_err());
+    }
+
+    #[test]
+    fn i64_conversion() {
+        let sf = spaceflake::generate(spaceflake::GeneratorSettings::default()).unwrap();
+        assert!(sf.as_i64() > 0);
+        assert!(sf.layout().is_sign_safe(sf.id));
+        assert_eq!(
+            Spaceflake::try_from_i64(sf.as_i64(), spaceflake::EPOCH).unwrap(),
+            sf
+        );
+        assert!(Spaceflake::try_from_i64(-1, spaceflake::EPOCH).is_err());
+        assert!(!spaceflake::BitLayout::SPACEFLAKE.is_sign_safe(1 << 63));
+    }
+
+    #[cfg(feature = "sqlx")]
+    #[test]
+    fn sqlx_columns() {
+        use sqlx::{Connection, SqliteConnection};
+
+        let sf = spaceflake::generate(spaceflake::GeneratorSettings::default()).unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let (decoded, raw): (Spaceflake, i64) = runtime.block_on(async {
+            let mut connection = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+            sqlx::query("CREATE TABLE items (id BIGINT PRIMARY KEY)")
+                .execute(&mut connection)
+                .await
+                .unwrap();
+            sqlx::query("INSERT INTO items (id) VALUES (?)")
+                .bind(sf)
+                .execute(&mut connection)
+                .await
+                .unwrap();
+            sqlx::query_as("SELECT id, id FROM items")
+                .fetch_one(&mut connection)
+                .await
+                .unwrap()
+        });
+        assert_eq!(decoded, sf);
+        assert_eq!(raw, sf.as_i64());
+    }
+
+    #[cfg(feature = "diesel")]
+    #[test]
+    fn diesel_columns() {
+        use diesel::prelude::*;
+
+        diesel::table! {
+            items (id) {
+                id -> BigInt,
+            }
+        }
+
+        let sf = spaceflake::generate(spaceflake::GeneratorSettings::default()).unwrap();
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        diesel::sql_query("CREATE TABLE items (id BIGINT PRIMARY KEY)")
+            .execute(&mut connection)
+            .unwrap();
+        diesel::insert_into(items::table)
+            .values(items::id.eq(sf))
+            .execute(&mut connection)
+            .unwrap();
+        let found: Spaceflake = items::table
+            .select(items::id)
+            .filter(items::id.eq(sf))
+            .get_result(&mut connection)
+            .unwrap();
+        assert_eq!(found, sf);
+        let raw: i64 = items::table
+            .select(items::id)
+            .first(&mut connection)
+            .unwrap();
+        assert_eq!(raw, sf.as_i64());
+    }
+
+    #[cfg(feature = "sea-orm")]
+    #[test]
+    fn sea_orm_columns() {
+        use sea_orm::entity::prelude::*;
+        use sea_orm::{DatabaseBackend, MockDatabase};
+
+        mod item {
+            use sea_orm::entity::prelude::*;
+
+            #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+            #[sea_orm(table_name = "items")]
+            pub struct Model {
+                #[sea_orm(primary_key, auto_increment = false)]
+                pub id: spaceflake::Spaceflake,
+            }
+
+            #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+            pub enum Relation {}
+
+            impl ActiveModelBehavior for ActiveModel {}
+        }
+
+        let sf = spaceflake::generate(spaceflake::GeneratorSettings::default()).unwrap();
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![item::Model { id: sf }]])
+            .into_connection();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let found = runtime
+            .block_on(item::Entity::find_by_id(sf).one(&db))
+            .unwrap();
+        assert_eq!(found, Some(item::Model { id: sf }));
+        let log = db.into_transaction_log();
+        let values = log[0].statements()[0].values.as_ref().unwrap();
+        assert_eq!(values.0[0], Value::BigInt(Some(sf.as_i64())));
+
+        let mut node = spaceflake::Node::try_new(1).unwrap();
+        let worker = node.new_worker();
+        let id = worker.active_value().unwrap();
+        assert!(id.is_set());
+    }
+
+    #[cfg(feature = "async-graphql")]
+    #[test]
+    fn async_graphql_scalar() {
+        use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema};
+
+        struct Query;
+
+        #[Object]
+        impl Query {
+            async fn echo(&self, id: Spaceflake) -> Spaceflake {
+                id
+            }
+        }
+
+        let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+        assert!(schema.sdl().contains("scalar Snowflake"));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let response = runtime.block_on(schema.execute(r#"{ echo(id: "1560497923970564097") }"#));
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({ "echo": "1560497923970564097" })
+        );
+        let response = runtime.block_on(schema.execute(r#"{ echo(id: "abc") }"#));
+        assert!(!response.errors.is_empty());
+    }
+
+    #[cfg(feature = "juniper")]
+    #[test]
+    fn juniper_scalar() {
+        use juniper::{graphql_object, EmptyMutation, EmptySubscription, RootNode, Variables};
+
+        struct Query;
+
+        #[graphql_object]
+        impl Query {
+            fn echo(id: Spaceflake) -> Spaceflake {
+                id
+            }
+        }
+
+        let schema = RootNode::new(
+            Query,
+            EmptyMutation::<()>::new(),
+            EmptySubscription::<()>::new(),
+        );
+        let (data, errors) = juniper::execute_sync(
+            r#"{ echo(id: "1560497923970564097") __type(name: "Snowflake") { kind } }"#,
+            None,
+            &schema,
+            &Variables::new(),
+            &(),
+        )
+        .unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(
+            serde_json::to_value(&data).unwrap(),
+            serde_json::json!({ "echo": "1560497923970564097", "__type": { "kind": "SCALAR" } })
+        );
+        assert!(juniper::execute_sync(
+            r#"{ echo(id: "abc") }"#,
+            None,
+            &schema,
+            &Variables::new(),
+            &()
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "utoipa")]
+    #[test]
+    fn openapi_schema() {
+        use utoipa::{PartialSchema, ToSchema};
+
+        assert_eq!(Spaceflake::name(), "Spaceflake");
+        let schema = serde_json::to_value(Spaceflake::schema()).unwrap();
+        assert_eq!(schema["type"], "string");
+        assert_eq!(schema["format"], "int64-as-string");
+    }
+
+    #[cfg(feature = "axum")]
+    #[test]
+    fn axum_extractor() {
+        use axum::body::{to_bytes, Body};
+        use axum::http::{Request, StatusCode};
+        use axum::routing::get;
+        use axum::Router;
+        use tower::ServiceExt;
+
+        async fn user_by_id(
+            spaceflake::SpaceflakePath(id): spaceflake::SpaceflakePath,
+        ) -> Spaceflake {
+            id
+        }
+
+        let router = Router::new().route("/users/{id}", get(user_by_id));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let (ok, malformed) = runtime.block_on(async {
+            let ok = router
+                .clone()
+                .oneshot(
+                    Request::get("/users/1560497923970564097")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            let malformed = router
+                .oneshot(Request::get("/users/abc").body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            (
+                (ok.status(), to_bytes(ok.into_body(), 1024).await.unwrap()),
+                (
+                    malformed.status(),
+                    to_bytes(malformed.into_body(), 1024).await.unwrap(),
+                ),
+            )
+        });
+        assert_eq!(ok.0, StatusCode::OK);
+        assert_eq!(&ok.1[..], b"1560497923970564097");
+        assert_eq!(malformed.0, StatusCode::BAD_REQUEST);
+        let error: serde_json::Value = serde_json::from_slice(&malformed.1).unwrap();
+        assert_eq!(error["code"], 12);
+    }
+
+    #[cfg(feature = "tower")]
+    #[test]
+    fn request_id_layer() {
+        use tower::{service_fn, Layer, ServiceExt};
+
+        let mut node = spaceflake::Node::try_new(1).unwrap();
+        let layer = spaceflake::SetSpaceflakeRequestIdLayer::new(node.new_worker());
+        let service = layer.layer(service_fn(|request: http::Request<()>| async move {
+            let header = request.headers()[spaceflake::REQUEST_ID_HEADER].clone();
+            let id = *request.extensions().get::<Spaceflake>().unwrap();
+            Ok::<_, std::convert::Infallible>((header, id))
+        }));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let (header, id) = runtime
+            .block_on(service.clone().oneshot(http::Request::new(())))
+            .unwrap();
+        assert_eq!(header.to_str().unwrap(), id.to_string());
+        assert_eq!(id.node_id(), 1);
+
+        // A single Spaceflake fits in a millisecond, so the requests wait for the next one without blocking the thread.
+        let mut worker = node.new_worker();
+        worker.layout =
+            spaceflake::BitLayout::new(41, 5, 16, 1).expect("Failed creating the layout");
+        let narrow = spaceflake::SetSpaceflakeRequestIdLayer::new(worker).layer(service_fn(
+            |request: http::Request<()>| async move {
+                Ok::<_, std::convert::Infallible>(
+                    *request.extensions().get::<Spaceflake>().unwrap(),
+                )
+            },
+        ));
+        let ids = runtime.block_on(async {
+            let mut ids = HashSet::new();
+            for _ in 0..20 {
+                ids.insert(
+                    narrow
+                        .clone()
+                        .oneshot(http::Request::new(()))
+                        .await
+                        .unwrap(),
+                );
+            }
+            ids
+        });
+        assert_eq!(ids.len(), 20);
+
+        let (header, _) = runtime
+            .block_on(service.oneshot(http::Request::new(())))
+            .unwrap();
+        assert_ne!(header.to_str().unwrap(), id.to_string());
+    }
+
+    #[cfg(feature = "actix")]
+    #[test]
+    fn actix_integration() {
+        use actix_web::http::StatusCode;
+        use actix_web::{test, web, App, HttpMessage, HttpRequest};
+
+        async fn user_by_id(
+            request: HttpRequest,
+            spaceflake::SpaceflakePath(id): spaceflake::SpaceflakePath,
+        ) -> String {
+            let request_id = *request.extensions().get::<Spaceflake>().unwrap();
+            format!("{} {}", id, request_id.node_id())
+        }
+
+        let mut node = spaceflake::Node::try_new(1).unwrap();
+        let worker = node.new_worker();
+        actix_web::rt::System::new().block_on(async {
+            let app = test::init_service(
+                App::new()
+                    .wrap(spaceflake::SpaceflakeRequestId::new(worker))
+                    .route("/users/{id}", web::get().to(user_by_id)),
+            )
+            .await;
+
+            let request = test::TestRequest::get()
+                .uri("/users/1560497923970564097")
+                .to_request();
+            let response = test::call_service(&app, request).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(
+                &test::read_body(response).await[..],
+                b"1560497923970564097 1"
+            );
+
+            let request = test::TestRequest::get().uri("/users/abc").to_request();
+            let response = test::call_service(&app, request).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            let error: serde_json::Value = test::read_body_json(response).await;
+            assert_eq!(error["code"], 12);
+        });
+    }
+
+    #[test]
+    fn generate_many() {
+        let mut node = spaceflake::Node::try_new(1).unwrap();
+        let worker = node.new_worker();
+        let mut ids = vec![42];
+        worker.generate_many(10_000, &mut ids).unwrap();
+        assert_eq!(ids.len(), 10_001);
+        assert_eq!(ids[0], 42);
+        let unique: HashSet<_> = ids[1..].iter().collect();
+        assert_eq!(unique.len(), 10_000);
+        assert!(ids[1..].windows(2).all(|pair| pair[0] < pair[1]));
+
+        let next = worker.generate().unwrap();
+        assert!(next.id > ids[10_000]);
+        assert_eq!(Spaceflake::from_id(ids[1], spaceflake::EPOCH).node_id(), 1);
+    }
+
+    #[test]
+    fn sequence_block() {
+        let mut node = spaceflake::Node::try_new(1).unwrap();
+        let worker = node.new_worker();
+        let block = worker.reserve_block(5_000).unwrap();
+        assert_eq!(block.len(), 5_000);
+        let after = worker.generate().unwrap();
+
+        let ids: Vec<Spaceflake> = block.iter().collect::<Result<_, _>>().unwrap();
+        assert_eq!(ids.len(), 5_000);
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(ids.iter().all(|sf| *sf < after));
+        assert_eq!(block.iter().next().unwrap().unwrap(), ids[0]);
+        assert!(worker.reserve_block(0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn lazy_bulk_generation() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let worker = node.new_worker();
+        let lazy = worker
+            .iter()
+            .take(5_000)
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Error generating Spaceflakes lazily");
+        assert_eq!(lazy.len(), 5_000);
+        assert!(lazy.windows(2).all(|pair| pair[0].id < pair[1].id));
+
+        let mut iter = spaceflake::bulk_iter(spaceflake::BulkGeneratorSettings::new(10_000))
+            .expect("Failed creating the bulk iterator");
+        assert_eq!(iter.size_hint(), (0, Some(10_000)));
+        let mut ids = std::collections::HashSet::new();
+        for spaceflake in iter.by_ref() {
+            let spaceflake = spaceflake.expect("Error generating Spaceflakes lazily");
+            assert!(ids.insert(spaceflake.id));
+        }
+        assert_eq!(ids.len(), 10_000);
+        assert!(iter.next().is_none());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_bulk_generation() {
+        let node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let spaceflakes = node
+            .par_bulk_generate(100_000)
+            .expect("Error generating Spaceflakes in parallel");
+        assert_eq!(spaceflakes.len(), 100_000);
+        assert!(spaceflakes.windows(2).all(|pair| pair[0].id < pair[1].id));
+        assert!(spaceflakes
+            .iter()
+            .all(|spaceflake| spaceflake.node_id() == 1));
+        assert_eq!(
+            spaceflakes
+                .iter()
+                .map(|spaceflake| spaceflake.worker_id())
+                .collect::<std::collections::HashSet<_>>()
+                .len(),
+            31
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn stream_generation() {
+        use futures_core::Stream;
+        use std::pin::Pin;
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .expect("Failed building the runtime");
+        runtime.block_on(async {
+            let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+            let worker = node.new_worker();
+            let mut stream = worker.stream();
+            let mut previous = 0;
+            for _ in 0..10_000 {
+                let spaceflake = std::future::poll_fn(|cx| Pin::new(&mut stream).poll_next(cx))
+                    .await
+                    .expect("The stream ended");
+                assert!(spaceflake.id > previous);
+                previous = spaceflake.id;
+            }
+
+            let rate = std::num::NonZeroU32::new(100).unwrap();
+            let mut limited = worker.stream().rate_limit(rate);
+            let start = std::time::Instant::now();
+            for _ in 0..11 {
+                std::future::poll_fn(|cx| Pin::new(&mut limited).poll_next(cx))
+                    .await
+                    .expect("The stream ended");
+            }
+            assert!(start.elapsed() >= std::time::Duration::from_millis(100));
+        });
+    }
+
+    #[test]
+    fn id_pool() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        assert!(spaceflake::IdPool::try_new(node.new_worker(), 0, 0).is_err());
+        assert!(spaceflake::IdPool::try_new(node.new_worker(), 100, 100).is_err());
+
+        let pool = spaceflake::IdPool::try_new(node.new_worker(), 1_000, 100)
+            .expect("Failed creating the ID pool");
+        let start = std::time::Instant::now();
+        while pool.available() < 1_000 && start.elapsed() < std::time::Duration::from_secs(5) {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        assert_eq!(pool.available(), 1_000);
+
+        let mut previous = 0;
+        for _ in 0..10_000 {
+            let spaceflake = pool
+                .take()
+                .expect("Failed taking a Spaceflake from the pool");
+            assert!(spaceflake.id > previous);
+            previous = spaceflake.id;
+        }
+    }
+
+    #[test]
+    fn generator_service() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let service = spaceflake::GeneratorService::spawn(node.new_worker())
+            .expect("Failed spawning the generator service");
+        let handles = (0..4)
+            .map(|_| {
+                let service = service.clone();
+                std::thread::spawn(move || {
+                    (0..2_500)
+                        .map(|_| {
+                            service
+                                .generate()
+                                .expect("Failed generating the Spaceflake")
+                                .id
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut ids = std::collections::HashSet::new();
+        for handle in handles {
+            for id in handle.join().expect("The thread panicked") {
+                assert!(ids.insert(id));
+            }
+        }
+        assert_eq!(ids.len(), 10_000);
+
+        #[cfg(feature = "async")]
+        {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_time()
+                .build()
+                .expect("Failed building the runtime");
+            let spaceflake = runtime
+                .block_on(service.generate_async())
+                .expect("Failed generating the Spaceflake");
+            assert!(ids.insert(spaceflake.id));
+        }
+    }
+
+    #[test]
+    fn bulk_node_and_worker_ids() {
+        let settings = spaceflake::BulkGeneratorSettings::new(10_000)
+            .with_node_id(7)
+            .with_worker_id_range(3..=4);
+        let spaceflakes =
+            spaceflake::bulk_generate(settings).expect("Failed generating the Spaceflakes");
+        assert_eq!(spaceflakes.len(), 10_000);
+        assert!(spaceflakes
+            .iter()
+            .all(|spaceflake| spaceflake.node_id() == 7));
+        assert!(spaceflakes
+            .iter()
+            .all(|spaceflake| (3..=4).contains(&spaceflake.worker_id())));
+        let ids = spaceflakes
+            .iter()
+            .map(|spaceflake| spaceflake.id)
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(ids.len(), 10_000);
+
+        let invalid = spaceflake::BulkGeneratorSettings::new(1).with_node_id(32);
+        assert!(spaceflake::bulk_generate(invalid).is_err());
+        let invalid = spaceflake::BulkGeneratorSettings::new(1).with_worker_id_range(30..=32);
+        assert!(spaceflake::bulk_generate(invalid).is_err());
+        #[allow(clippy::reversed_empty_ranges)]
+        let invalid = spaceflake::BulkGeneratorSettings::new(1).with_worker_id_range(4..=3);
+        assert!(spaceflake::bulk_generate(invalid).is_err());
+    }
+
+    #[test]
+    fn global_worker() {
+        let settings =
+            spaceflake::GeneratorSettings::try_new(3, 7).expect("Failed creating the settings");
+        spaceflake::init(settings).expect("Failed initializing the global worker");
+        assert!(spaceflake::init(settings).is_err());
+
+        let handles = (0..4)
+            .map(|_| {
+                thread::spawn(|| {
+                    (0..1_000)
+                        .map(|_| {
+                            spaceflake::global()
+                                .generate()
+                                .expect("Failed generating the Spaceflake")
+                        })
+                        .collect::<Vec<Spaceflake>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut spaceflakes = HashSet::new();
+        for handle in handles {
+            for sf in handle.join().unwrap() {
+                assert_eq!(sf.node_id(), 3);
+                assert_eq!(sf.worker_id(), 7);
+                assert!(spaceflakes.insert(sf), "Spaceflake {} is a duplicate", sf);
+            }
+        }
+    }
+
+    #[test]
+    fn worker_from_settings() {
+        let settings = spaceflake::GeneratorSettings {
+            node_id: 1000,
+            worker_id: 0,
+            version_bits: 2,
+            version: 3,
+            layout: spaceflake::BitLayout::twitter(),
+            drift_policy: spaceflake::DriftPolicy::Error,
+            ..Default::default()
+        };
+        let worker =
+            spaceflake::Worker::from_settings(settings).expect("Failed creating the worker");
+        assert_eq!(worker.layout, settings.layout);
+        assert_eq!(worker.drift_policy, spaceflake::DriftPolicy::Error);
+        let sf = worker.generate().expect("Failed generating the Spaceflake");
+        assert_eq!(sf.node_id(), 1000);
+        assert_eq!(sf.version(), 3);
+        assert_eq!(
+            spaceflake::Worker::from_settings(spaceflake::GeneratorSettings {
+                worker_id: 1,
+                ..settings
+            })
+            .unwrap_err(),
+            SpaceflakeError::InvalidWorkerId { max: 0 }
+        );
+    }
+
+    #[test]
+    fn standalone_worker() {
+        let worker = spaceflake::Worker::standalone(4, 9).expect("Failed creating the worker");
+        let sf = worker.generate().expect("Failed generating the Spaceflake");
+        assert_eq!(sf.node_id(), 4);
+        assert_eq!(sf.worker_id(), 9);
+        assert!(spaceflake::Worker::standalone(32, 1).is_err());
+        assert!(spaceflake::Worker::standalone(1, 32).is_err());
+
+        let sf = spaceflake::Worker::default()
+            .generate()
+            .expect("Failed generating the Spaceflake");
+        assert_eq!(sf.node_id(), 0);
+        assert_eq!(sf.worker_id(), 0);
+    }
+
+    #[test]
+    fn fallible_constructors() {
+        assert_eq!(
+            spaceflake::Node::try_new(32).unwrap_err(),
+            SpaceflakeError::InvalidNodeId { max: 31 }
+        );
+        assert_eq!(
+            spaceflake::GeneratorSettings::try_new(1, 32).unwrap_err(),
+            SpaceflakeError::InvalidWorkerId { max: 31 }
+        );
+        let mut cluster = spaceflake::Cluster::new();
+        assert!(cluster.new_node(32).is_err());
+    }
+
+    #[test]
+    fn machine_field() {
+        let settings = spaceflake::GeneratorSettings::default();
+        let sf = spaceflake::generate_on_machine(settings, 1000)
+            .expect("Failed generating the Spaceflake");
+        assert_eq!(sf.machine_id(), 1000);
+        assert_eq!(
+            spaceflake::split_machine_id(1000),
+            Ok((sf.node_id(), sf.worker_id()))
+        );
+        assert_eq!(spaceflake::split_machine_id(1023), Ok((31, 31)));
+        assert_eq!(
+            spaceflake::split_machine_id(1024).unwrap_err().to_string(),
+            "Machine ID must be less than or equals to 1023"
+        );
+        assert!(spaceflake::generate_on_machine(settings, 1024).is_err());
+
+        let worker = spaceflake::machine_worker(33).expect("Failed creating the worker");
+        let sf = worker.generate().expect("Failed generating the Spaceflake");
+        assert_eq!(spaceflake::parse_machine_id(sf.id), 33);
+    }
+
+    #[test]
+    fn parse_spaceflake() {
+        let sf = spaceflake::generate(spaceflake::GeneratorSettings::default())
+            .expect("Failed generating the Spaceflake");
+        let parsed: Spaceflake = sf
+            .to_string()
+            .parse()
+            .expect("Failed parsing the Spaceflake");
+        assert_eq!(parsed.id, sf.id);
+        assert_eq!(parsed.time(), sf.time());
+        assert_eq!(Spaceflake::try_from(sf.id).unwrap().id, sf.id);
+        assert!(Spaceflake::try_from(u64::MAX).is_err());
+        assert!("spaceflake".parse::<Spaceflake>().is_err());
+
+        let epoch = 1640995200000;
+        let sf = Spaceflake::from_id(sf.id, epoch);
+        assert_eq!(sf.time(), parsed.time() - spaceflake::EPOCH + epoch);
+    }
+
+    #[test]
+    fn ordering() {
+        let mut node = spaceflake::Node::try_new(1).expect("Failed creating the node");
+        let worker = node.new_worker();
+        let first = worker.generate().expect("Failed generating the Spaceflake");
+        let second = worker.generate().expect("Failed generating the Spaceflake");
+        assert!(first < second);
+        let mut spaceflakes = vec![second, first];
+        spaceflakes.sort();
+        assert_eq!(spaceflakes, vec![first, second]);
+        assert_eq!(first, Spaceflake::from_id(first.id, spaceflake::EPOCH));
+
+        // Handles to the same ID are equal, whatever their base epoch or layout.
+        let other = Spaceflake::from_id(first.id, spaceflake::EPOCH_TWITTER)
+            .with_layout(spaceflake::BitLayout::twitter());
+        assert_eq!(first, other);
+        assert_eq!(first.cmp(&other), std::cmp::Ordering::Equal);
+        assert_eq!(HashSet::from([first, other]).len(), 1);
+        assert!(Spaceflake::from_id(second.id, 0) > first);
+    }
+
+    #[test]
+    fn bit_layout() {
+        use spaceflake::BitLayout;
+
+        assert_eq!(BitLayout::default(), BitLayout::SPACEFLAKE);
+        assert_eq!(
+            BitLayout::new(41, 10, 0, 11).unwrap_err(),
+            SpaceflakeError::InvalidBitLayout
+        );
+        assert!(BitLayout::new(41, 0, 0, 22).is_err());
+
+        let layout = BitLayout::new(41, 10, 0, 12).expect("Failed creating the layout");
+        let mut worker = spaceflake::Worker::default();
+        worker.layout = layout;
+        worker.node_id = 1000;
+        let sf = worker
+            .generate_at(1532180612064)
+            .expect("Failed generating the Spaceflake");
+        assert_eq!(sf.layout(), layout);
+        assert_eq!(sf.time(), 1532180612064);
+        assert_eq!(sf.node_id(), 1000);
+        assert_eq!(sf.worker_id(), 0);
+        assert_eq!(sf.sequence(), 1);
+        assert_eq!(layout.parse_node_id(sf.id), 1000);
+        assert_eq!(
+            Spaceflake::from_id(sf.id, spaceflake::EPOCH)
+                .with_layout(layout)
+                .decompose()["node_id"],
+            1000
+        );
+
+        worker.node_id = 1024;
+        assert_eq!(
+            worker.generate().unwrap_err(),
+            SpaceflakeError::InvalidNodeId { max: 1023 }
+        );
+
+        let settings = spaceflake::GeneratorSettings {
+            layout: BitLayout::new(40, 5, 5, 13).expect("Failed creating the layout"),
+            sequence: 8000,
+            ..Default::default()
+        };
+        let sf = spaceflake::generate(settings).expect("Failed generating the Spaceflake");
+        assert_eq!(sf.sequence(), 8000);
+    }
+
+    #[test]
+    fn twitter_snowflakes() {
+        // A Snowflake created on the 1st of May 2019 at 16:00:00.004 UTC by the machine 375.
+        let sf = spaceflake::parse_twitter(1123618076900159488);
+        assert_eq!(sf.time(), 1556726400004);
+        assert_eq!(sf.machine_id(), 375);
+        assert_eq!(sf.sequence(), 0);
+        assert_eq!(sf.decompose()["machine_id"], 375);
+
+        let worker = spaceflake::twitter_worker(375).expect("Failed creating the worker");
+        let generated = worker
+            .generate_at(1556726400004)
+            .expect("Failed generating the Snowflake");
+        assert_eq!(generated.id, sf.id + 1);
+        assert!(spaceflake::twitter_worker(1024).is_err());
+    }
+
+    #[test]
+    fn sonyflakes() {
+        // A Sonyflake created on the 1st of May 2019 at 16:00:00.000 UTC by the machine 375, with the sequence 3.
+        let id = ((1556726400000 - spaceflake::EPOCH_SONYFLAKE) / 10) << 24 | 3 << 16 | 375;
+        let sf = spaceflake::parse_sonyflake(id);
+        assert_eq!(sf.time(), 1556726400000);
+        assert_eq!(sf.machine_id(), 375);
+        assert_eq!(sf.node_id(), 375);
+        assert_eq!(sf.sequence(), 3);
+
+        let worker = spaceflake::sonyflake_worker(375).expect("Failed creating the worker");
+        let first = worker
+            .generate_at(1556726400004)
+            .expect("Failed generating the Sonyflake");
+        let second = worker
+            .generate_at(1556726400009)
+            .expect("Failed generating the Sonyflake");
+        assert_eq!(first.time(), 1556726400000);
+        assert_eq!(first.machine_id(), 375);
+        assert_eq!(second.sequence(), first.sequence() + 1);
+        assert_eq!(worker.generate().unwrap().time() % 10, 0);
+    }
+
+    #[test]
+    fn instagram_ids() {
+        // The milliseconds, shard ID and sequence of the example of Instagram's sharding article.
+        let id = 1387263000 << 23 | 1341 << 10 | 905;
+        let parsed = spaceflake::parse_instagram(id);
+        assert_eq!(parsed.time, spaceflake::EPOCH_INSTAGRAM + 1387263000);
+        assert_eq!(parsed.shard_id, 1341);
+        assert_eq!(parsed.sequence, 905);
+
+        let worker = spaceflake::instagram_worker(1341).expect("Failed creating the worker");
+        let sf = worker
+            .generate_at(spaceflake::EPOCH_INSTAGRAM + 1387263000)
+            .expect("Failed generating the ID");
+        assert_eq!(spaceflake::parse_instagram(sf.id).shard_id, 1341);
+        assert_eq!(sf.id >> 23, 1387263000);
+        assert!(spaceflake::instagram_worker(8192).is_err());
+    }
+
+    #[test]
+    fn discord_snowflakes() {
+        // The example Snowflake of Discord's documentation.
+        let sf = spaceflake::parse_discord(175928847299117063);
+        assert_eq!(sf.timestamp, 1462015105796);
+        assert_eq!(sf.worker_id, 1);
+        assert_eq!(sf.process_id, 0);
+        assert_eq!(sf.increment, 7);
+    }
+
+    #[test]
+    fn structured_parts() {
+        let mut settings =
+            spaceflake::GeneratorSettings::try_new(5, 7).expect("Failed creating the settings");
+        settings.sequence = 1337;
+        let sf = spaceflake::generate(settings).expect("Failed generating the Spaceflake");
+        let parts = sf.parts();
+        assert_eq!(parts.id, sf.id);
+        assert_eq!(parts.time, sf.time());
+        assert_eq!(parts.node_id, 5);
+        assert_eq!(parts.worker_id, 7);
+        assert_eq!(parts.sequence, 1337);
+        assert_eq!(parts.version, 0);
+        assert_eq!(sf.decompose()["time"], parts.time);
+    }
+
+    #[test]
+    fn shard_map() {
+        let config = "
+            # Initial placement
+            [version 1420070400000]
+            0 = 1:1, 1:2
+            1 = 2:1
+        ";
+        let mut map =
+            spaceflake::ShardMap::from_config(config).expect("Failed loading the shard map");
+        assert_eq!(map.assignments(0), vec![(1, 1), (1, 2)]);
+
+        let settings =
+            spaceflake::GeneratorSettings::try_new(1, 2).expect("Failed creating the settings");
+        let old = spaceflake::generate(settings).expect("Failed generating the Spaceflake");
+        assert_eq!(map.shard_of(old.id), Some(0));
+
+        map.rebalance(old.time() + 1, vec![(0, 1, 1), (1, 1, 2), (1, 2, 1)])
+            .expect("Failed rebalancing the shard map");
+        assert_eq!(map.shard_of(old.id), Some(0));
+        let later = old.checked_add(Duration::from_millis(1)).unwrap();
+        assert_eq!(map.shard_of(later.id), Some(1));
+        assert_eq!(
+            map.shard_of(
+                spaceflake::generate(
+                    spaceflake::GeneratorSettings::try_new(3, 1)
+                        .expect("Failed creating the settings")
+                )
+                .unwrap()
+                .id
+            ),
+            None
+        );
+
+        let reloaded = spaceflake::ShardMap::from_config(&map.to_string()).unwrap();
+        assert_eq!(reloaded, map);
+        assert!(map.rebalance(old.time(), Vec::new()).is_err());
+
+        let layout = spaceflake::BitLayout::twitter();
+        assert!(spaceflake::ShardMap::new(0, vec![(0, 1000, 0)]).is_err());
+        let map = spaceflake::ShardMap::with_layout(0, vec![(0, 1000, 0)], layout)
+            .expect("Failed creating the shard map");
+        assert_eq!(map.shard_of(layout.compose(1, 1000, 0, 1)), Some(0));
+        assert!(spaceflake::ShardMap::with_layout(0, vec![(0, 1, 1)], layout).is_err());
+    }
+
     #[test]
     fn generate_unique() {
-        let mut spaceflakes: HashMap<String, Spaceflake> = HashMap::new();
+        let mut spaceflakes: HashSet<Spaceflake> = HashSet::new();
         let settings = spaceflake::GeneratorSettings::default();
 
         for _ in 0..1000 {
             let sf = spaceflake::generate(settings).expect("Failed generating the Spaceflake");
-            if spaceflakes.contains_key(sf.to_string().as_str()) {
+            if !spaceflakes.insert(sf) {
                 panic!("Spaceflake ID {} is a duplicate", sf.id);
             }
-            spaceflakes.insert(sf.to_string(), sf);
-            // When using random there is a chance that the sequence will be twice the same due to Rust's speed, hence using a worker is better. We wait a millisecond to make sure it's different.
-            thread::sleep(Duration::from_millis(1));
         }
     }
 }